@@ -1,8 +1,13 @@
 // Copyright 2022, Offchain Labs, Inc.
 // For license information, see https://github.com/nitro/blob/master/LICENSE
 
+use eyre::{bail, eyre, Result};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
 use siphasher::sip::SipHasher24;
 use std::mem::MaybeUninit;
+use substrate_bn::{pairing_batch, AffineG1, AffineG2, Fq, Fq2, Group, Gt, G1, G2};
 use tiny_keccak::{Hasher, Keccak};
 
 pub fn keccak<T: AsRef<[u8]>>(preimage: T) -> [u8; 32] {
@@ -17,6 +22,81 @@ pub fn keccak<T: AsRef<[u8]>>(preimage: T) -> [u8; 32] {
     }
 }
 
+pub fn sha256<T: AsRef<[u8]>>(preimage: T) -> [u8; 32] {
+    Sha256::digest(preimage.as_ref()).into()
+}
+
+pub fn ripemd160<T: AsRef<[u8]>>(preimage: T) -> [u8; 20] {
+    Ripemd160::digest(preimage.as_ref()).into()
+}
+
+/// Recovers the address that signed a hash on secp256k1, following the EVM's `ECRECOVER`
+/// precompile. The input is the hash, `v`, `r`, and `s` as 32-byte words, where `v` is 27 or 28.
+/// Like the precompile, signatures with a high `s` are accepted. Returns `None` on failure.
+pub fn ecrecover(input: &[u8; 128]) -> Option<[u8; 20]> {
+    let word = |i: usize| -> [u8; 32] { input[32 * i..32 * (i + 1)].try_into().unwrap() };
+    let (hash, v, r, s) = (word(0), word(1), word(2), word(3));
+    if v[..31] != [0; 31] || !matches!(v[31], 27 | 28) {
+        return None;
+    }
+
+    let mut signature = Signature::from_scalars(r, s).ok()?;
+    let mut recovery = RecoveryId::from_byte(v[31] - 27)?;
+    if let Some(normalized) = signature.normalize_s() {
+        // negating s recovers the same key from the other point with that x coordinate
+        signature = normalized;
+        recovery = RecoveryId::new(!recovery.is_y_odd(), recovery.is_x_reduced());
+    }
+    let key = VerifyingKey::recover_from_prehash(&hash, &signature, recovery).ok()?;
+    let point = key.to_encoded_point(false);
+    let digest = keccak(&point.as_bytes()[1..]);
+    digest[12..].try_into().ok()
+}
+
+/// The size of one (G1, G2) pair in the input of the bn256 pairing check.
+pub const BN256_PAIR_LEN: usize = 192;
+
+/// Checks whether the product of the bn256 pairings of the given (G1, G2) points is one.
+/// The encoding matches that of the EVM's `ECPAIRING` precompile: each G1 point is `x || y`,
+/// and each G2 point is `x_imag || x_real || y_imag || y_real`, with the point at infinity
+/// being all zeros. Errors if the input is malformed or a point isn't on its curve.
+pub fn bn256_pairing(input: &[u8]) -> Result<bool> {
+    if input.len() % BN256_PAIR_LEN != 0 {
+        bail!(
+            "bn256 pairing input of length {} isn't a multiple of {BN256_PAIR_LEN}",
+            input.len()
+        );
+    }
+
+    fn field(data: &[u8]) -> Result<Fq> {
+        Fq::from_slice(data).map_err(|_| eyre!("bn256 field element out of range"))
+    }
+
+    let mut pairs = vec![];
+    for chunk in input.chunks_exact(BN256_PAIR_LEN) {
+        let word = |i: usize| field(&chunk[32 * i..32 * (i + 1)]);
+        let (ax, ay) = (word(0)?, word(1)?);
+        let (bx, by) = (Fq2::new(word(3)?, word(2)?), Fq2::new(word(5)?, word(4)?));
+
+        let a = match ax == Fq::zero() && ay == Fq::zero() {
+            true => G1::zero(),
+            false => match AffineG1::new(ax, ay) {
+                Ok(point) => point.into(),
+                Err(_) => bail!("bn256 G1 point not on curve"),
+            },
+        };
+        let b = match bx == Fq2::zero() && by == Fq2::zero() {
+            true => G2::zero(),
+            false => match AffineG2::new(bx, by) {
+                Ok(point) => point.into(),
+                Err(_) => bail!("bn256 G2 point not on curve"),
+            },
+        };
+        pairs.push((a, b));
+    }
+    Ok(pairing_batch(&pairs) == Gt::one())
+}
+
 pub fn siphash(preimage: &[u8], key: &[u8; 16]) -> u64 {
     use std::hash::Hasher;
     let mut hasher = SipHasher24::new_with_key(key);
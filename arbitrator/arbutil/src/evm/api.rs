@@ -1,7 +1,10 @@
 // Copyright 2023-2024, Offchain Labs, Inc.
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
-use crate::{evm::user::UserOutcomeKind, Bytes20, Bytes32};
+use crate::{
+    evm::{user::UserOutcomeKind, ChainParam},
+    Bytes20, Bytes32,
+};
 use eyre::Result;
 use num_enum::IntoPrimitive;
 use std::sync::Arc;
@@ -44,6 +47,9 @@ pub enum EvmApiMethod {
     AccountCodeHash,
     AddPages,
     CaptureHostIO,
+    Checkpoint,
+    Rollback,
+    ChainParam,
 }
 
 /// This offset is added to EvmApiMethod when sending a request
@@ -186,4 +192,21 @@ pub trait EvmApi<D: DataReader>: Send + 'static {
         start_ink: u64,
         end_ink: u64,
     );
+
+    /// Marks a point in the EVM's state journal that a later `rollback` reverts to.
+    /// Checkpoints nest, with each `rollback` consuming the most recent one.
+    /// Analogous to `StateDB.Snapshot`.
+    ///
+    /// Only dry runs use this. Ordinary calls never checkpoint, since the EVM already snapshots
+    /// each call and reverts it on failure, in every runtime alike.
+    fn checkpoint(&mut self);
+
+    /// Reverts all storage writes, logs, and other state changes since the last `checkpoint`.
+    /// A rollback without a matching checkpoint does nothing.
+    /// Analogous to `StateDB.RevertToSnapshot`.
+    fn rollback(&mut self);
+
+    /// Gets a constant of the chain's configuration.
+    /// Not analogous to any EVM opcode.
+    fn chain_param(&mut self, param: ChainParam) -> u64;
 }
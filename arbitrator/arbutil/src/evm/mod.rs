@@ -2,8 +2,10 @@
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
 use crate::{Bytes20, Bytes32};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 
 pub mod api;
+pub mod replay;
 pub mod req;
 pub mod storage;
 pub mod user;
@@ -11,6 +13,12 @@ pub mod user;
 // params.SstoreSentryGasEIP2200
 pub const SSTORE_SENTRY_GAS: u64 = 2300;
 
+// params.SstoreSetGasEIP2200
+pub const SSTORE_SET_GAS: u64 = 20000;
+
+// params.SstoreResetGasEIP2200
+pub const SSTORE_RESET_GAS: u64 = 5000;
+
 // params.ColdAccountAccessCostEIP2929
 pub const COLD_ACCOUNT_GAS: u64 = 2600;
 
@@ -35,6 +43,24 @@ pub const COPY_WORD_GAS: u64 = 3;
 pub const KECCAK_256_GAS: u64 = 30;
 pub const KECCAK_WORD_GAS: u64 = 6;
 
+// params.Sha256BaseGas and params.Sha256PerWordGas
+pub const SHA256_GAS: u64 = 60;
+pub const SHA256_WORD_GAS: u64 = 12;
+
+// params.Ripemd160BaseGas and params.Ripemd160PerWordGas
+pub const RIPEMD160_GAS: u64 = 600;
+pub const RIPEMD160_WORD_GAS: u64 = 120;
+
+// The minimum gas of the MODEXP precompile (see EIP-2565)
+pub const MODEXP_MIN_GAS: u64 = 200;
+
+// params.EcrecoverGas
+pub const ECRECOVER_GAS: u64 = 3000;
+
+// params.Bn256PairingBaseGasIstanbul and params.Bn256PairingPerPointGasIstanbul (see EIP-1108)
+pub const ECPAIRING_GAS: u64 = 45000;
+pub const ECPAIRING_POINT_GAS: u64 = 34000;
+
 // vm.GasQuickStep (see gas.go)
 pub const GAS_QUICK_STEP: u64 = 2;
 
@@ -78,24 +104,127 @@ pub const ORIGIN_GAS: u64 = GAS_QUICK_STEP;
 #[repr(C)]
 pub struct EvmData {
     pub block_basefee: Bytes32,
+    pub block_blob_basefee: Bytes32,
+    pub block_excess_blob_gas: u64,
     pub chainid: u64,
     pub block_coinbase: Bytes20,
     pub block_gas_limit: u64,
     pub block_number: u64,
+    pub l2_block_number: u64,
     pub block_timestamp: u64,
+    pub block_prevrandao: Bytes32,
     pub contract_address: Bytes20,
     pub module_hash: Bytes32,
     pub msg_sender: Bytes20,
     pub msg_value: Bytes32,
     pub tx_gas_price: Bytes32,
+    pub tx_gas_fee_cap: Bytes32,
+    pub tx_gas_tip_cap: Bytes32,
     pub tx_origin: Bytes20,
+    pub l1_sender: Bytes20,
     pub reentrant: u32,
+    pub is_static: bool,
     pub return_data_len: u32,
     pub cached: bool,
     pub tracing: bool,
 }
 
+impl EvmData {
+    /// Computes the effective gas price of [`EIP-1559`]: the basefee plus the tip, capped by the
+    /// max fee per gas. Saturates rather than overflowing.
+    ///
+    /// [`EIP-1559`]: https://eips.ethereum.org/EIPS/eip-1559
+    pub fn effective_gas_price(&self) -> Bytes32 {
+        type U256 = ruint2::Uint<256, 4>;
+        let basefee = U256::from(self.block_basefee);
+        let tip = U256::from(self.tx_gas_tip_cap);
+        let cap = U256::from(self.tx_gas_fee_cap);
+        basefee.saturating_add(tip).min(cap).into()
+    }
+}
+
 /// Returns the minimum number of EVM words needed to store `bytes` bytes.
 pub fn evm_words(bytes: u32) -> u32 {
     crate::math::div_ceil::<32>(bytes as usize) as u32
 }
+
+/// Computes the gas cost of the [`MODEXP`] precompile, as specified in [`EIP-2565`].
+///
+/// [`MODEXP`]: https://www.evm.codes/precompiled#0x05
+/// [`EIP-2565`]: https://eips.ethereum.org/EIPS/eip-2565
+pub fn modexp_gas(base_len: u32, exponent: &[u8], mod_len: u32) -> u64 {
+    let words = crate::math::div_ceil::<8>(base_len.max(mod_len) as usize) as u64;
+    let complexity = words.saturating_mul(words);
+
+    // only the leading 32 bytes of the exponent are inspected bit-by-bit
+    let head = &exponent[..exponent.len().min(32)];
+    let head_bits = match head.iter().position(|&x| x != 0) {
+        Some(i) => 8 * (head.len() - i - 1) as u64 + (8 - head[i].leading_zeros()) as u64,
+        None => 0,
+    };
+    let extra_bits = 8 * exponent.len().saturating_sub(32) as u64;
+    let iterations = extra_bits
+        .saturating_add(head_bits.saturating_sub(1))
+        .max(1);
+
+    let gas = complexity.saturating_mul(iterations) / 3;
+    gas.max(MODEXP_MIN_GAS)
+}
+
+/// A chain constant readable via the `arb_chain_param` hostio. Unknown ids read as zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u32)]
+pub enum ChainParam {
+    /// The L2 block number the chain started at.
+    GenesisBlockNum,
+    /// The ArbOS version the chain started with.
+    InitialArbOSVersion,
+    /// The most bytes of code a contract may have.
+    MaxCodeSize,
+    /// The most bytes of init code a contract deployment may have.
+    MaxInitCodeSize,
+}
+
+/// A storage operation whose gas cost can be computed via [`storage_op_cost`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum StorageOp {
+    Load,
+    Store,
+}
+
+/// Computes the gas cost of an `SLOAD` or `SSTORE`, as specified in [`EIP-2200`], [`EIP-2929`],
+/// and [`EIP-3529`]. Mirrors Geth's `gasSLoadEIP2929` and `makeGasSStoreFunc`, which price these
+/// operations for Stylus' storage hostios. Refunds aren't included, nor is the `SSTORE` sentry,
+/// which is a requirement rather than a cost.
+///
+/// The `original` value is the slot's at the start of the transaction, `current` its value now,
+/// and `new` the value being stored. Only `cold` matters for loads.
+///
+/// [`EIP-2200`]: https://eips.ethereum.org/EIPS/eip-2200
+/// [`EIP-2929`]: https://eips.ethereum.org/EIPS/eip-2929
+/// [`EIP-3529`]: https://eips.ethereum.org/EIPS/eip-3529
+pub fn storage_op_cost(
+    op: StorageOp,
+    cold: bool,
+    original: Bytes32,
+    current: Bytes32,
+    new: Bytes32,
+) -> u64 {
+    let access = match cold {
+        true => COLD_SLOAD_GAS,
+        false => 0,
+    };
+    if op == StorageOp::Load {
+        return access.max(WARM_SLOAD_GAS);
+    }
+
+    // no-ops and writes to dirty slots only pay for the warm read
+    if current == new || original != current {
+        return access + WARM_SLOAD_GAS;
+    }
+    match original == Bytes32::default() {
+        true => access + SSTORE_SET_GAS,
+        false => access + SSTORE_RESET_GAS - COLD_SLOAD_GAS,
+    }
+}
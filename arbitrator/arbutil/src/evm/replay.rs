@@ -0,0 +1,181 @@
+// Copyright 2024, Offchain Labs, Inc.
+// For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+use crate::evm::{
+    api::{DataReader, EvmApiMethod, VecReader},
+    req::RequestHandler,
+};
+use eyre::{bail, Result};
+use std::{collections::VecDeque, fs, path::Path};
+
+const TRACE_VERSION: u8 = 1;
+
+/// A request made of the EVM during a call, along with the response received.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedRequest {
+    pub req_type: u32,
+    pub req_data: Vec<u8>,
+    pub result: Vec<u8>,
+    pub raw_data: Vec<u8>,
+    pub cost: u64,
+}
+
+/// The EVM requests made during a call, in order. Replaying these reproduces the call exactly.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub requests: Vec<RecordedRequest>,
+}
+
+impl Trace {
+    /// Serializes the trace into a versioned binary format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![TRACE_VERSION];
+        data.extend((self.requests.len() as u32).to_be_bytes());
+        for request in &self.requests {
+            data.extend(request.req_type.to_be_bytes());
+            data.extend(request.cost.to_be_bytes());
+            for field in [&request.req_data, &request.result, &request.raw_data] {
+                data.extend((field.len() as u32).to_be_bytes());
+                data.extend(field);
+            }
+        }
+        data
+    }
+
+    /// Deserializes a trace produced by `to_bytes`.
+    pub fn from_bytes(mut data: &[u8]) -> Result<Self> {
+        fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+            if data.len() < len {
+                bail!("truncated trace");
+            }
+            let (head, tail) = data.split_at(len);
+            *data = tail;
+            Ok(head)
+        }
+        macro_rules! take {
+            (vec) => {{
+                let len = take!(u32) as usize;
+                take(&mut data, len)?.to_vec()
+            }};
+            ($ty:ty) => {
+                <$ty>::from_be_bytes(take(&mut data, std::mem::size_of::<$ty>())?.try_into()?)
+            };
+        }
+
+        let version = take!(u8);
+        if version != TRACE_VERSION {
+            bail!("unsupported trace version {version}");
+        }
+        // each request takes at least 24 bytes, which bounds what an untrusted count may reserve
+        let count = take!(u32);
+        let mut requests = Vec::with_capacity((count as usize).min(data.len() / 24));
+        for _ in 0..count {
+            let req_type = take!(u32);
+            let cost = take!(u64);
+            requests.push(RecordedRequest {
+                req_type,
+                req_data: take!(vec),
+                result: take!(vec),
+                raw_data: take!(vec),
+                cost,
+            });
+        }
+        if !data.is_empty() {
+            bail!("trace has trailing data");
+        }
+        Ok(Self { requests })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+
+/// Wraps a request handler, optionally recording each request and its response for later replay.
+pub struct Recorder<H> {
+    handler: H,
+    trace: Option<Trace>,
+}
+
+impl<H> Recorder<H> {
+    pub fn new(handler: H, record: bool) -> Self {
+        let trace = record.then(Trace::default);
+        Self { handler, trace }
+    }
+
+    /// Takes the requests recorded so far, if recording.
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        self.trace.as_mut().map(std::mem::take)
+    }
+
+    fn record<D: DataReader>(
+        &mut self,
+        req_type: EvmApiMethod,
+        req_data: &[u8],
+        response: &(Vec<u8>, D, u64),
+    ) {
+        if let Some(trace) = &mut self.trace {
+            let (result, raw_data, cost) = response;
+            trace.requests.push(RecordedRequest {
+                req_type: req_type as u32,
+                req_data: req_data.to_vec(),
+                result: result.clone(),
+                raw_data: raw_data.slice().to_vec(),
+                cost: *cost,
+            });
+        }
+    }
+}
+
+impl<D: DataReader, H: RequestHandler<D>> RequestHandler<D> for Recorder<H> {
+    fn request(&mut self, req_type: EvmApiMethod, req_data: impl AsRef<[u8]>) -> (Vec<u8>, D, u64) {
+        let req_data = req_data.as_ref();
+        let response = self.handler.request(req_type, req_data);
+        self.record(req_type, req_data, &response);
+        response
+    }
+}
+
+/// Answers requests from a recorded trace instead of the EVM.
+/// Panics if the program makes a request other than the one recorded, since the replay has diverged.
+pub struct Replayer {
+    requests: VecDeque<RecordedRequest>,
+}
+
+impl Replayer {
+    pub fn new(trace: Trace) -> Self {
+        Self {
+            requests: trace.requests.into(),
+        }
+    }
+
+    /// The number of recorded requests not yet replayed.
+    pub fn remaining(&self) -> usize {
+        self.requests.len()
+    }
+}
+
+impl RequestHandler<VecReader> for Replayer {
+    fn request(
+        &mut self,
+        req_type: EvmApiMethod,
+        req_data: impl AsRef<[u8]>,
+    ) -> (Vec<u8>, VecReader, u64) {
+        let Some(request) = self.requests.pop_front() else {
+            panic!("replay diverged: unexpected {req_type:?} request past the end of the trace");
+        };
+        if request.req_type != req_type as u32 || request.req_data != req_data.as_ref() {
+            panic!(
+                "replay diverged: expected request {}, got {req_type:?}",
+                request.req_type
+            );
+        }
+        let raw_data = VecReader::new(request.raw_data);
+        (request.result, raw_data, request.cost)
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
         api::{DataReader, EvmApi, EvmApiMethod, EvmApiStatus},
         storage::{StorageCache, StorageWord},
         user::UserOutcomeKind,
+        ChainParam,
     },
     format::Utf8OrHex,
     pricing::EVM_API_INK,
@@ -284,4 +285,20 @@ impl<D: DataReader, H: RequestHandler<D>> EvmApi<D> for EvmApiRequestor<D, H> {
         request.extend(outs);
         self.request(EvmApiMethod::CaptureHostIO, request);
     }
+
+    fn checkpoint(&mut self) {
+        self.request(EvmApiMethod::Checkpoint, Vec::<u8>::new());
+    }
+
+    fn rollback(&mut self) {
+        // cached values may no longer reflect the state
+        self.storage_cache.clear();
+        self.request(EvmApiMethod::Rollback, Vec::<u8>::new());
+    }
+
+    fn chain_param(&mut self, param: ChainParam) -> u64 {
+        let param: u32 = param.into();
+        let (res, ..) = self.request(EvmApiMethod::ChainParam, param.to_be_bytes());
+        u64::from_be_bytes(res.try_into().unwrap())
+    }
 }
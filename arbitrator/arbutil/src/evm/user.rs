@@ -36,6 +36,15 @@ impl UserOutcome {
         (kind, data)
     }
 
+    /// Separates successful output from everything else, which keeps its kind and data as
+    /// produced by `into_data`.
+    pub fn into_result(self) -> Result<Vec<u8>, (UserOutcomeKind, Vec<u8>)> {
+        match self {
+            Self::Success(out) => Ok(out),
+            outcome => Err(outcome.into_data()),
+        }
+    }
+
     pub fn kind(&self) -> UserOutcomeKind {
         self.into()
     }
@@ -89,3 +98,31 @@ impl Display for UserOutcomeKind {
         }
     }
 }
+
+#[test]
+fn test_into_result() {
+    use eyre::eyre;
+
+    assert_eq!(
+        UserOutcome::Success(vec![1, 2]).into_result(),
+        Ok(vec![1, 2])
+    );
+
+    let revert = UserOutcome::Revert(vec![3]).into_result();
+    assert_eq!(revert, Err((UserOutcomeKind::Revert, vec![3])));
+
+    let (kind, data) = UserOutcome::Failure(eyre!("oops"))
+        .into_result()
+        .unwrap_err();
+    assert_eq!(kind, UserOutcomeKind::Failure);
+    assert!(data.starts_with(b"oops"));
+
+    assert_eq!(
+        UserOutcome::OutOfInk.into_result(),
+        Err((UserOutcomeKind::OutOfInk, vec![]))
+    );
+    assert_eq!(
+        UserOutcome::OutOfStack.into_result(),
+        Err((UserOutcomeKind::OutOfStack, vec![]))
+    );
+}
@@ -10,6 +10,12 @@ pub const PTR_INK: u64 = 13440 - HOSTIO_INK;
 /// For hostios that involve an API cost.
 pub const EVM_API_INK: u64 = 59673;
 
+/// For hostios that involve an add.
+pub const ADD_INK: u64 = 4000;
+
+/// For hostios that involve a mul.
+pub const MUL_INK: u64 = 16000;
+
 /// For hostios that involve a div or mod.
 pub const DIV_INK: u64 = 20000;
 
@@ -18,3 +24,93 @@ pub const MUL_MOD_INK: u64 = 24100;
 
 /// For hostios that involve an addmod.
 pub const ADD_MOD_INK: u64 = 21000;
+
+/// For hostios that hash at most two words with keccak.
+pub const KECCAK_INK: u64 = 121800;
+
+/// The fixed ink each hostio charges, before any costs that depend on its arguments or on the EVM.
+pub const HOSTIO_BASE_INK: [(&str, u64); 68] = [
+    ("read_args", HOSTIO_INK),
+    ("write_result", HOSTIO_INK),
+    ("exit_early", 0),
+    ("finish_return", HOSTIO_INK),
+    ("finish_revert", HOSTIO_INK),
+    ("storage_load_bytes32", HOSTIO_INK + 2 * PTR_INK),
+    ("storage_load_bytes", HOSTIO_INK + 2 * PTR_INK),
+    ("storage_cache_bytes32", HOSTIO_INK + 2 * PTR_INK),
+    ("storage_flush_cache", HOSTIO_INK + EVM_API_INK),
+    (
+        "transient_load_bytes32",
+        HOSTIO_INK + 2 * PTR_INK + EVM_API_INK,
+    ),
+    (
+        "transient_store_bytes32",
+        HOSTIO_INK + 2 * PTR_INK + EVM_API_INK,
+    ),
+    (
+        "transient_compare_and_set",
+        HOSTIO_INK + 3 * PTR_INK + EVM_API_INK,
+    ),
+    ("call_contract", HOSTIO_INK + 3 * PTR_INK + EVM_API_INK),
+    (
+        "delegate_call_contract",
+        HOSTIO_INK + 3 * PTR_INK + EVM_API_INK,
+    ),
+    (
+        "static_call_contract",
+        HOSTIO_INK + 3 * PTR_INK + EVM_API_INK,
+    ),
+    ("create1", HOSTIO_INK + 3 * PTR_INK + EVM_API_INK),
+    ("create2", HOSTIO_INK + 4 * PTR_INK + EVM_API_INK),
+    ("read_return_data", HOSTIO_INK + EVM_API_INK),
+    ("return_data_size", HOSTIO_INK),
+    ("emit_log", HOSTIO_INK + EVM_API_INK),
+    ("emit_log_with_signature", HOSTIO_INK + EVM_API_INK),
+    ("account_balance", HOSTIO_INK + 2 * PTR_INK + EVM_API_INK),
+    ("account_code", HOSTIO_INK + EVM_API_INK),
+    ("account_codehash", HOSTIO_INK + 2 * PTR_INK + EVM_API_INK),
+    ("account_is_contract", HOSTIO_INK + PTR_INK + EVM_API_INK),
+    ("account_code_size", HOSTIO_INK + EVM_API_INK),
+    ("evm_gas_left", HOSTIO_INK),
+    ("evm_ink_left", HOSTIO_INK),
+    ("block_basefee", HOSTIO_INK + PTR_INK),
+    ("block_blob_basefee", HOSTIO_INK + PTR_INK),
+    ("block_excess_blob_gas", HOSTIO_INK),
+    ("chainid", HOSTIO_INK),
+    ("arb_chain_param", HOSTIO_INK + EVM_API_INK),
+    ("block_coinbase", HOSTIO_INK + PTR_INK),
+    ("block_prevrandao", HOSTIO_INK + PTR_INK),
+    ("block_gas_limit", HOSTIO_INK),
+    ("block_number", HOSTIO_INK),
+    ("arb_l1_block_number", HOSTIO_INK),
+    ("arb_l2_block_number", HOSTIO_INK),
+    ("block_timestamp", HOSTIO_INK),
+    ("contract_address", HOSTIO_INK + PTR_INK),
+    ("contract_code_copy", HOSTIO_INK + EVM_API_INK),
+    ("contract_code_size", HOSTIO_INK + EVM_API_INK),
+    ("math_div", HOSTIO_INK + 3 * PTR_INK + DIV_INK),
+    ("math_mod", HOSTIO_INK + 3 * PTR_INK + DIV_INK),
+    ("math_add", HOSTIO_INK + 3 * PTR_INK + ADD_INK),
+    ("math_mul", HOSTIO_INK + 3 * PTR_INK + MUL_INK),
+    ("math_pow", HOSTIO_INK + 3 * PTR_INK),
+    ("math_add_mod", HOSTIO_INK + 4 * PTR_INK + ADD_MOD_INK),
+    ("math_mul_mod", HOSTIO_INK + 4 * PTR_INK + MUL_MOD_INK),
+    ("msg_reentrant", HOSTIO_INK),
+    ("is_static", HOSTIO_INK),
+    ("msg_sender", HOSTIO_INK + PTR_INK),
+    ("msg_value", HOSTIO_INK + PTR_INK),
+    ("tx_gas_price", HOSTIO_INK + PTR_INK),
+    ("effective_gas_price", HOSTIO_INK + PTR_INK),
+    ("tx_ink_price", HOSTIO_INK),
+    ("tx_origin", HOSTIO_INK + PTR_INK),
+    ("arb_l1_sender", HOSTIO_INK + PTR_INK),
+    ("pay_for_memory_grow", HOSTIO_INK),
+    ("memory_gas_charged", HOSTIO_INK),
+    ("native_keccak256", KECCAK_INK),
+    ("keccak256_pair", KECCAK_INK),
+    ("native_sha256", HOSTIO_INK + 2 * PTR_INK),
+    ("native_ripemd160", HOSTIO_INK + 2 * PTR_INK),
+    ("native_modexp", HOSTIO_INK + 4 * PTR_INK),
+    ("native_ecpairing", HOSTIO_INK + PTR_INK),
+    ("native_ecrecover", HOSTIO_INK + 2 * PTR_INK),
+];
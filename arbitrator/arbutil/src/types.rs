@@ -22,6 +22,8 @@ pub enum PreimageType {
     EthVersionedHash,
 }
 
+/// A 32-byte EVM word. Like the EVM, the bytes are always big-endian: hostios copy them
+/// to and from guest memory verbatim, and integer conversions go through the impls below.
 /// cbindgen:field-names=[bytes]
 #[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[repr(C)]
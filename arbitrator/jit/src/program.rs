@@ -208,12 +208,16 @@ pub fn create_stylus_config(
     version: u16,
     max_depth: u32,
     ink_price: u32,
+    max_logs: u32,
     debug: u32,
 ) -> Result<u64, Escape> {
     let stylus = StylusConfig {
         version,
         max_depth,
-        pricing: PricingParams { ink_price },
+        pricing: PricingParams::new(ink_price),
+        max_logs,
+        max_ops: 0,
+        max_memory_grows: 0,
     };
     let compile = CompileConfig::version(version, debug != 0);
     let res = heapify(JitConfig { stylus, compile });
@@ -224,37 +228,53 @@ pub fn create_stylus_config(
 pub fn create_evm_data(
     mut env: WasmEnvMut,
     block_basefee_ptr: GuestPtr,
+    block_blob_basefee_ptr: GuestPtr,
+    block_excess_blob_gas: u64,
     chainid: u64,
     block_coinbase_ptr: GuestPtr,
     block_gas_limit: u64,
     block_number: u64,
+    l2_block_number: u64,
     block_timestamp: u64,
+    block_prevrandao_ptr: GuestPtr,
     contract_address_ptr: GuestPtr,
     module_hash_ptr: GuestPtr,
     msg_sender_ptr: GuestPtr,
     msg_value_ptr: GuestPtr,
     tx_gas_price_ptr: GuestPtr,
+    tx_gas_fee_cap_ptr: GuestPtr,
+    tx_gas_tip_cap_ptr: GuestPtr,
     tx_origin_ptr: GuestPtr,
+    l1_sender_ptr: GuestPtr,
     cached: u32,
     reentrant: u32,
+    is_static: u32,
 ) -> Result<u64, Escape> {
     let (mut mem, _) = env.jit_env();
 
     let evm_data = EvmData {
         block_basefee: mem.read_bytes32(block_basefee_ptr),
+        block_blob_basefee: mem.read_bytes32(block_blob_basefee_ptr),
+        block_excess_blob_gas,
         cached: cached != 0,
         chainid,
         block_coinbase: mem.read_bytes20(block_coinbase_ptr),
         block_gas_limit,
         block_number,
+        l2_block_number,
         block_timestamp,
+        block_prevrandao: mem.read_bytes32(block_prevrandao_ptr),
         contract_address: mem.read_bytes20(contract_address_ptr),
         module_hash: mem.read_bytes32(module_hash_ptr),
         msg_sender: mem.read_bytes20(msg_sender_ptr),
         msg_value: mem.read_bytes32(msg_value_ptr),
         tx_gas_price: mem.read_bytes32(tx_gas_price_ptr),
+        tx_gas_fee_cap: mem.read_bytes32(tx_gas_fee_cap_ptr),
+        tx_gas_tip_cap: mem.read_bytes32(tx_gas_tip_cap_ptr),
         tx_origin: mem.read_bytes20(tx_origin_ptr),
+        l1_sender: mem.read_bytes20(l1_sender_ptr),
         reentrant,
+        is_static: is_static != 0,
         return_data_len: 0,
         tracing: false,
     };
@@ -3,9 +3,15 @@
 
 use crate::{
     programs::{
-        config::CompileConfig, counter::Counter, depth::DepthChecker, dynamic::DynamicMeter,
-        heap::HeapBound, meter::Meter, start::StartMover, FuncMiddleware, Middleware, ModuleMod,
-        StylusData, STYLUS_ENTRY_POINT,
+        config::{CompileConfig, StylusConfig},
+        counter::{Counter, OperatorLimit},
+        depth::DepthChecker,
+        dynamic::DynamicMeter,
+        heap::HeapBound,
+        meter::Meter,
+        safe_div::SafeDivision,
+        start::StartMover,
+        FuncMiddleware, Middleware, ModuleMod, StylusData, STYLUS_ENTRY_POINT,
     },
     value::{ArbValueType, FunctionType, IntegerValType, Value},
 };
@@ -22,9 +28,9 @@ use serde::{Deserialize, Serialize};
 use std::{convert::TryInto, fmt::Debug, hash::Hash, mem, path::Path, str::FromStr};
 use wasmer_types::{entity::EntityRef, FunctionIndex, LocalFunctionIndex};
 use wasmparser::{
-    Data, Element, Export, ExternalKind, Global, Import, MemoryType, Name, NameSectionReader,
-    Naming, Operator, Parser, Payload, SectionReader, SectionWithLimitedItems, TableType, Type,
-    TypeRef, ValType, Validator, WasmFeatures,
+    Data, DataKind, Element, Export, ExternalKind, Global, Import, MemoryType, Name,
+    NameSectionReader, Naming, Operator, Parser, Payload, SectionReader, SectionWithLimitedItems,
+    TableType, Type, TypeRef, ValType, Validator, WasmFeatures,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -260,6 +266,14 @@ pub struct Local {
     pub value: ArbValueType,
 }
 
+/// A data segment, as embedded in the wasm.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataSegment {
+    /// Where in memory an active segment is written, or `None` for passive segments.
+    pub offset: Option<u32>,
+    pub data: Vec<u8>,
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct NameCustomSection {
     pub module: String,
@@ -507,21 +521,92 @@ impl<'a> Debug for WasmBinary<'a> {
     }
 }
 
+/// The custom section in which a program may declare the selectors of its ABI. Each line of its
+/// UTF-8 contents is a 4-byte selector in hex followed by the function's name, as in
+/// `a9059cbb transfer(address,uint256)`.
+pub const STYLUS_ABI_SECTION: &str = "stylus_abi";
+
+/// Finds the function whose selector leads the calldata, as declared in [`STYLUS_ABI_SECTION`].
+/// Returns `None` when the calldata is too short, no selector matches, or there's no such section.
+/// The module isn't otherwise validated, making this cheap enough to reject malformed calls early.
+pub fn match_selector(input: &[u8], calldata: &[u8]) -> Result<Option<String>> {
+    let Some(selector) = calldata.get(..4) else {
+        return Ok(None);
+    };
+    for payload in Parser::new(0).parse_all(input) {
+        let Payload::CustomSection(reader) = payload? else {
+            continue;
+        };
+        if reader.name() != STYLUS_ABI_SECTION {
+            continue;
+        }
+        let abi = std::str::from_utf8(reader.data()).wrap_err("abi section isn't utf8")?;
+        for line in abi.lines().map(str::trim).filter(|x| !x.is_empty()) {
+            let Some((declared, name)) = line.split_once(char::is_whitespace) else {
+                bail!("abi entry {} has no name", line.red());
+            };
+            let declared = declared.strip_prefix("0x").unwrap_or(declared);
+            let declared = hex::decode(declared).wrap_err_with(|| eyre!("bad selector {line}"))?;
+            ensure!(declared.len() == 4, "selector {} isn't 4 bytes", line.red());
+
+            if declared == selector {
+                return Ok(Some(name.trim().to_owned()));
+            }
+        }
+    }
+    Ok(None)
+}
+
 impl<'a> WasmBinary<'a> {
+    /// Reads the module's data segments in order, for inspecting embedded constants without executing.
+    pub fn data_segments(&self) -> Result<Vec<DataSegment>> {
+        let mut segments = Vec::with_capacity(self.datas.len());
+        for data in &self.datas {
+            let offset = match data.kind {
+                DataKind::Active {
+                    memory_index,
+                    offset_expr,
+                } => {
+                    ensure!(memory_index == 0, "data segment for nonexistent memory");
+                    let mut init = offset_expr.get_operators_reader();
+                    match (init.read()?, init.read()?, init.eof()) {
+                        (Operator::I32Const { value }, Operator::End, true) => Some(value as u32),
+                        x => bail!("non-constant data segment offset expression {:?}", x),
+                    }
+                }
+                DataKind::Passive => None,
+            };
+            let data = data.data.to_vec();
+            segments.push(DataSegment { offset, data });
+        }
+        Ok(segments)
+    }
+
     /// Instruments a user wasm, producing a version bounded via configurable instrumentation.
     pub fn instrument(&mut self, compile: &CompileConfig) -> Result<StylusData> {
-        let meter = Meter::new(&compile.pricing);
+        let meter = Meter::with_overrides(&compile.pricing, &compile.debug.cost_overrides);
         let dygas = DynamicMeter::new(&compile.pricing);
-        let depth = DepthChecker::new(compile.bounds);
+        let depth = DepthChecker::with_interval(compile.bounds, compile.debug.depth_check_interval);
         let bound = HeapBound::new(compile.bounds);
         let start = StartMover::default();
 
+        // rewriting division happens first, so that the other middlewares see the result
+        let safe_div = compile.debug.safe_division.then(SafeDivision::new);
+        if let Some(safe_div) = &safe_div {
+            safe_div.update_module(self)?;
+        }
+
         meter.update_module(self)?;
         dygas.update_module(self)?;
         depth.update_module(self)?;
         bound.update_module(self)?;
         start.update_module(self)?;
 
+        let limit = compile.debug.op_limit.then(OperatorLimit::new);
+        if let Some(limit) = &limit {
+            limit.update_module(self)?;
+        }
+
         let count = compile.debug.count_ops.then(Counter::new);
         if let Some(count) = &count {
             count.update_module(self)?;
@@ -551,12 +636,18 @@ impl<'a> WasmBinary<'a> {
 
             // add the instrumentation in the order of application
             // note: this must be consistent with native execution
+            if let Some(safe_div) = &safe_div {
+                apply!(*safe_div);
+            }
             apply!(meter);
             apply!(dygas);
             apply!(depth);
             apply!(bound);
             apply!(start);
 
+            if let Some(limit) = &limit {
+                apply!(*limit);
+            }
             if let Some(count) = &count {
                 apply!(*count);
             }
@@ -589,6 +680,8 @@ impl<'a> WasmBinary<'a> {
             asm_estimate,
             footprint,
             user_main,
+            page_limit: u16::MAX,
+            version: compile.version,
         })
     }
 
@@ -599,7 +692,24 @@ impl<'a> WasmBinary<'a> {
         compile: &CompileConfig,
     ) -> Result<(WasmBinary<'a>, StylusData)> {
         let mut bin = parse(wasm, Path::new("user"))?;
-        let stylus_data = bin.instrument(compile)?;
+
+        // instrumentation moves the start function to an export, so we must check beforehand
+        if bin.start.is_some() {
+            bail!("wasm start functions not allowed");
+        }
+
+        // hostios are only available to programs of the versions that introduced them
+        for import in bin.imports.iter().filter(|x| x.module == "vm_hooks") {
+            let version = StylusConfig::hostio_version(import.name);
+            if version > compile.version {
+                bail!(
+                    "hostio {} requires Stylus version {version}",
+                    import.name.red()
+                );
+            }
+        }
+        let mut stylus_data = bin.instrument(compile)?;
+        stylus_data.page_limit = page_limit;
 
         let Some(memory) = bin.memories.first() else {
             bail!("missing memory with export name \"memory\"")
@@ -654,9 +764,6 @@ impl<'a> WasmBinary<'a> {
         if bin.names.module.len() > max_len {
             too_long!("module name", bin.names.module.len())
         }
-        if bin.start.is_some() {
-            bail!("wasm start functions not allowed");
-        }
         Ok((bin, stylus_data))
     }
 
@@ -4,7 +4,9 @@
 #![allow(clippy::field_reassign_with_default)]
 
 use crate::{programs::meter, value::FunctionType};
+use arbutil::operator::OperatorCode;
 use derivative::Derivative;
+use eyre::{bail, Result};
 use fnv::FnvHashMap as HashMap;
 use std::fmt::Debug;
 use wasmer_types::{Pages, SignatureIndex, WASM_PAGE_SIZE};
@@ -13,8 +15,14 @@ use wasmparser::Operator;
 #[cfg(feature = "native")]
 use {
     super::{
-        counter::Counter, depth::DepthChecker, dynamic::DynamicMeter, heap::HeapBound,
-        meter::Meter, start::StartMover, MiddlewareWrapper,
+        counter::{Counter, OperatorLimit},
+        depth::DepthChecker,
+        dynamic::DynamicMeter,
+        heap::HeapBound,
+        meter::Meter,
+        safe_div::SafeDivision,
+        start::StartMover,
+        MiddlewareWrapper,
     },
     std::sync::Arc,
     wasmer::{Cranelift, CraneliftOptLevel, Engine, Store},
@@ -30,6 +38,14 @@ pub struct StylusConfig {
     pub max_depth: u32,
     /// Pricing parameters supplied at runtime
     pub pricing: PricingParams,
+    /// The maximum number of logs a call may emit, or 0 for no limit
+    pub max_logs: u32,
+    /// The maximum number of operators a call may execute, or 0 for no limit.
+    /// Nonzero limits require programs compiled with [`CompileDebugParams::op_limit`].
+    pub max_ops: u64,
+    /// The maximum number of successful, nonzero memory grows a call may make, or 0 for no limit.
+    /// Native-only, so consensus runtimes always leave it unlimited.
+    pub max_memory_grows: u32,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -45,6 +61,9 @@ impl Default for StylusConfig {
             version: 0,
             max_depth: u32::MAX,
             pricing: PricingParams::default(),
+            max_logs: 0,
+            max_ops: 0,
+            max_memory_grows: 0,
         }
     }
 }
@@ -56,28 +75,126 @@ impl Default for PricingParams {
 }
 
 impl StylusConfig {
+    /// The earliest Stylus version this runtime supports.
+    pub const MIN_VERSION: u16 = 0;
+
+    /// The latest Stylus version this runtime supports.
+    pub const MAX_VERSION: u16 = 2;
+
+    /// The hostios added in Stylus version 2, which programs of earlier versions may not import.
+    pub const V2_HOSTIOS: &[&str] = &[
+        "account_is_contract",
+        "arb_chain_param",
+        "arb_l1_block_number",
+        "arb_l1_sender",
+        "arb_l2_block_number",
+        "block_blob_basefee",
+        "block_excess_blob_gas",
+        "block_prevrandao",
+        "contract_code_copy",
+        "contract_code_size",
+        "effective_gas_price",
+        "emit_log_with_signature",
+        "finish_return",
+        "finish_revert",
+        "is_static",
+        "keccak256_pair",
+        "math_add",
+        "math_mul",
+        "memory_gas_charged",
+        "native_ecpairing",
+        "native_ecrecover",
+        "native_modexp",
+        "native_ripemd160",
+        "native_sha256",
+        "storage_load_bytes",
+        "transient_compare_and_set",
+    ];
+
+    /// The earliest Stylus version whose programs may import the given hostio.
+    pub fn hostio_version(hostio: &str) -> u16 {
+        match Self::V2_HOSTIOS.contains(&hostio) {
+            true => 2,
+            false => 0,
+        }
+    }
+
+    /// Errors if this runtime doesn't support the given Stylus version.
+    pub fn check_version(version: u16) -> Result<()> {
+        let (min, max) = (Self::MIN_VERSION, Self::MAX_VERSION);
+        if !(min..=max).contains(&version) {
+            bail!(
+                "unsupported Stylus version {version}: this runtime supports {min} through {max}"
+            );
+        }
+        Ok(())
+    }
+
     pub const fn new(version: u16, max_depth: u32, ink_price: u32) -> Self {
         let pricing = PricingParams::new(ink_price);
         Self {
             version,
             max_depth,
             pricing,
+            max_logs: 0,
+            max_ops: 0,
+            max_memory_grows: 0,
+        }
+    }
+
+    /// Checks the config for internal consistency, reporting the first problem found.
+    pub fn validate(&self) -> Result<()> {
+        Self::check_version(self.version)?;
+        if self.max_depth == 0 {
+            bail!("max depth must be nonzero");
         }
+        self.pricing.validate()
     }
 }
 
 #[allow(clippy::inconsistent_digit_grouping)]
 impl PricingParams {
+    /// The largest ink price ArbOS can represent, since it's stored as a `uint24`.
+    pub const MAX_INK_PRICE: u32 = (1 << 24) - 1;
+
+    /// Never panics, since prices arrive over FFI on the consensus path. Unvalidated prices may
+    /// be zero, which [`Self::ink_to_gas`] treats as 1. Use [`Self::validated`] to reject them.
     pub const fn new(ink_price: u32) -> Self {
         Self { ink_price }
     }
 
+    /// Like `new`, but rejects prices that are zero or that ArbOS can't represent.
+    /// For validating configs, rather than for building them during execution.
+    pub fn validated(ink_price: u32) -> Result<Self> {
+        let pricing = Self { ink_price };
+        pricing.validate()?;
+        Ok(pricing)
+    }
+
+    /// Ensures the ink price is nonzero and representable by ArbOS.
+    pub fn validate(&self) -> Result<()> {
+        if self.ink_price == 0 {
+            bail!("ink price must be nonzero");
+        }
+        if self.ink_price > Self::MAX_INK_PRICE {
+            bail!(
+                "ink price {} exceeds {}",
+                self.ink_price,
+                Self::MAX_INK_PRICE
+            );
+        }
+        Ok(())
+    }
+
+    /// Converts gas to ink, saturating on overflow.
     pub fn gas_to_ink(&self, gas: u64) -> u64 {
         gas.saturating_mul(self.ink_price.into())
     }
 
+    /// Converts ink to gas, rounding down. An unvalidated zero ink price is treated as 1, so that
+    /// no ink is ever worth more gas than it was bought for.
     pub fn ink_to_gas(&self, ink: u64) -> u64 {
-        ink / self.ink_price as u64 // never 0
+        ink / u64::from(self.ink_price.max(1))
     }
 }
 
@@ -124,10 +241,29 @@ pub struct CompilePricingParams {
 pub struct CompileDebugParams {
     /// Allow debug functions
     pub debug_funcs: bool,
+    /// Allow the extended debug functions, like `debug::print_i64`. Requires `debug_funcs`.
+    /// Never set by [`CompileConfig::version`], so debug chains must opt in separately.
+    pub extra_funcs: bool,
     /// Add instrumentation to count the number of times each kind of opcode is executed
     pub count_ops: bool,
     /// Whether to use the Cranelift compiler
     pub cranelift: bool,
+    /// Check the stack depth only on every n-th function entry. Values of 0 and 1 check every entry.
+    /// For speed in non-consensus tooling: never set by [`CompileConfig::version`].
+    pub depth_check_interval: u32,
+    /// Per-opcode ink costs that replace the default schedule when metering.
+    /// For research only: never set by [`CompileConfig::version`], so consensus is unaffected.
+    pub cost_overrides: HashMap<OperatorCode, u64>,
+    /// Make integer division and remainder produce defined values instead of trapping.
+    /// For fuzzing only: changes wasm semantics and is never set by [`CompileConfig::version`].
+    pub safe_division: bool,
+    /// Add instrumentation that traps once a call executes [`StylusConfig::max_ops`] operators.
+    /// For differential testing only: slows execution and is never set by [`CompileConfig::version`].
+    pub op_limit: bool,
+    /// Reject hostio pointers to fixed-size values, like words and addresses, that aren't a
+    /// multiple of this many bytes. Values of 0 and 1 accept any pointer, as wasm itself does.
+    /// For catching codegen bugs only: native-only and never set by [`CompileConfig::version`].
+    pub hostio_alignment: u32,
 }
 
 impl Default for CompilePricingParams {
@@ -159,7 +295,7 @@ impl CompileConfig {
 
         match version {
             0 => {}
-            1 => {
+            1 | 2 => {
                 // TODO: settle on reasonable values for the v1 release
                 config.bounds.heap_bound = Pages(128); // 8 mb
                 config.bounds.max_frame_size = 10 * 1024;
@@ -190,20 +326,34 @@ impl CompileConfig {
         compiler.canonicalize_nans(true);
         compiler.enable_verifier();
 
-        let meter = MiddlewareWrapper::new(Meter::new(&self.pricing));
+        let meter = MiddlewareWrapper::new(Meter::with_overrides(
+            &self.pricing,
+            &self.debug.cost_overrides,
+        ));
         let dygas = MiddlewareWrapper::new(DynamicMeter::new(&self.pricing));
-        let depth = MiddlewareWrapper::new(DepthChecker::new(self.bounds));
+        let depth = MiddlewareWrapper::new(DepthChecker::with_interval(
+            self.bounds,
+            self.debug.depth_check_interval,
+        ));
         let bound = MiddlewareWrapper::new(HeapBound::new(self.bounds));
         let start = MiddlewareWrapper::new(StartMover::default());
 
         // add the instrumentation in the order of application
         // note: this must be consistent with the prover
+        if self.debug.safe_division {
+            let safe_div = SafeDivision::new();
+            compiler.push_middleware(Arc::new(MiddlewareWrapper::new(safe_div)));
+        }
         compiler.push_middleware(Arc::new(meter));
         compiler.push_middleware(Arc::new(dygas));
         compiler.push_middleware(Arc::new(depth));
         compiler.push_middleware(Arc::new(bound));
         compiler.push_middleware(Arc::new(start));
 
+        if self.debug.op_limit {
+            let limit = OperatorLimit::new();
+            compiler.push_middleware(Arc::new(MiddlewareWrapper::new(limit)));
+        }
         if self.debug.count_ops {
             let counter = Counter::new();
             compiler.push_middleware(Arc::new(MiddlewareWrapper::new(counter)));
@@ -217,3 +367,25 @@ impl CompileConfig {
         self.store().engine().clone()
     }
 }
+
+#[test]
+fn test_ink_price() {
+    assert!(PricingParams::validated(0).is_err());
+    assert!(PricingParams::validated(PricingParams::MAX_INK_PRICE + 1).is_err());
+    assert!(PricingParams::validated(u32::MAX).is_err());
+
+    let pricing = PricingParams::validated(PricingParams::MAX_INK_PRICE).unwrap();
+    assert_eq!(pricing.gas_to_ink(u64::MAX), u64::MAX);
+    assert_eq!(pricing.ink_to_gas(pricing.gas_to_ink(1000)), 1000);
+
+    // unvalidated extremes saturate rather than panicking or wrapping
+    let pricing = PricingParams::new(u32::MAX);
+    assert_eq!(pricing.gas_to_ink(u64::MAX / 2), u64::MAX);
+    assert_eq!(pricing.ink_to_gas(u32::MAX.into()), 1);
+
+    // zero prices are only rejected when validating, and never cause a division by zero
+    let pricing = PricingParams::new(0);
+    assert!(pricing.validate().is_err());
+    assert_eq!(pricing.gas_to_ink(u64::MAX), 0);
+    assert_eq!(pricing.ink_to_gas(1), 1);
+}
@@ -10,9 +10,13 @@ use fnv::FnvHashMap as HashMap;
 use lazy_static::lazy_static;
 use parking_lot::Mutex;
 use std::collections::BTreeMap;
-use std::{clone::Clone, fmt::Debug, sync::Arc};
+use std::{
+    clone::Clone,
+    fmt::{self, Debug, Display},
+    sync::Arc,
+};
 use wasmer_types::{GlobalIndex, GlobalInit, LocalFunctionIndex, Type};
-use wasmparser::Operator;
+use wasmparser::{BlockType, Operator};
 
 lazy_static! {
     /// Assigns each operator a sequential offset
@@ -135,8 +139,93 @@ impl<'a> FuncMiddleware<'a> for FuncCounter<'a> {
     }
 }
 
+/// The global tracking how many more operators the program may execute.
+pub const STYLUS_OPS_LEFT: &str = "stylus_ops_left";
+
+/// The error of any `Failure` caused by exhausting the operator limit.
+pub const OP_LIMIT_ERROR: &str = "operator limit reached";
+
+/// Traps once the program has executed a given number of operators, which is set at runtime
+/// via [`STYLUS_OPS_LEFT`]. Unlike [`Counter`], every operator is checked individually, so
+/// execution stops at exactly the same operator every time, making partial state reproducible.
+///
+/// This is expensive and for differential testing only, so it must never be used in consensus.
+#[derive(Debug, Default)]
+pub struct OperatorLimit {
+    global: Mutex<Option<GlobalIndex>>,
+}
+
+impl OperatorLimit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<M> Middleware<M> for OperatorLimit
+where
+    M: ModuleMod,
+{
+    type FM<'a> = FuncOperatorLimit;
+
+    fn update_module(&self, module: &mut M) -> Result<()> {
+        let unlimited = GlobalInit::I64Const(-1); // u64::MAX
+        let global = module.add_global(STYLUS_OPS_LEFT, Type::I64, unlimited)?;
+        *self.global.lock() = Some(global);
+        Ok(())
+    }
+
+    fn instrument<'a>(&self, _: LocalFunctionIndex) -> Result<Self::FM<'a>> {
+        let global = self.global.lock().ok_or_else(|| eyre!("no global"))?;
+        Ok(FuncOperatorLimit { global })
+    }
+
+    fn name(&self) -> &'static str {
+        "operator limit"
+    }
+}
+
+#[derive(Debug)]
+pub struct FuncOperatorLimit {
+    /// Counts down the operators left
+    global: GlobalIndex,
+}
+
+impl<'a> FuncMiddleware<'a> for FuncOperatorLimit {
+    fn feed<O>(&mut self, op: Operator<'a>, out: &mut O) -> Result<()>
+    where
+        O: Extend<Operator<'a>>,
+    {
+        use Operator::*;
+
+        let global_index = self.global.as_u32();
+        let blockty = BlockType::Empty;
+        out.extend([
+            // if left == 0 => panic
+            GlobalGet { global_index },
+            I64Eqz,
+            If { blockty },
+            Unreachable,
+            End,
+            // left -= 1
+            GlobalGet { global_index },
+            I64Const { value: 1 },
+            I64Sub,
+            GlobalSet { global_index },
+            op,
+        ]);
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "operator limit"
+    }
+}
+
 pub trait CountingMachine {
     fn operator_counts(&mut self) -> Result<BTreeMap<OperatorCode, u64>>;
+
+    /// Reads the count of a single opcode, which is 0 for those no program has used.
+    fn operator_count(&mut self, op: OperatorCode) -> Result<u64>;
 }
 
 impl CountingMachine for Machine {
@@ -152,4 +241,66 @@ impl CountingMachine for Machine {
         }
         Ok(counts)
     }
+
+    fn operator_count(&mut self, op: OperatorCode) -> Result<u64> {
+        let Some(&offset) = OP_OFFSETS.lock().get(&op) else {
+            return Ok(0);
+        };
+        let count = self.get_global(&Counter::global_name(offset))?;
+        Ok(count.try_into()?)
+    }
+}
+
+/// The difference between two sets of operator counts, like those of `CountingMachine`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CountDiff {
+    /// Operators only counted in the second set, with their counts.
+    pub added: BTreeMap<OperatorCode, u64>,
+    /// Operators only counted in the first set, with their counts.
+    pub removed: BTreeMap<OperatorCode, u64>,
+    /// Operators counted in both sets whose counts changed, with the change.
+    pub changed: BTreeMap<OperatorCode, i128>,
+}
+
+impl CountDiff {
+    pub fn new(before: &BTreeMap<OperatorCode, u64>, after: &BTreeMap<OperatorCode, u64>) -> Self {
+        let mut diff = Self::default();
+        for (&op, &count) in before {
+            match after.get(&op) {
+                Some(&new) if new != count => {
+                    diff.changed.insert(op, new as i128 - count as i128);
+                }
+                Some(_) => {}
+                None => {
+                    diff.removed.insert(op, count);
+                }
+            }
+        }
+        for (&op, &count) in after {
+            if !before.contains_key(&op) {
+                diff.added.insert(op, count);
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Prints one operator per line, which makes for stable test snapshots.
+impl Display for CountDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (op, count) in &self.added {
+            writeln!(f, "+ {op} {count}")?;
+        }
+        for (op, count) in &self.removed {
+            writeln!(f, "- {op} {count}")?;
+        }
+        for (op, delta) in &self.changed {
+            writeln!(f, "~ {op} {delta:+}")?;
+        }
+        Ok(())
+    }
 }
@@ -18,6 +18,8 @@ use wasmer_types::{
 use wasmparser::{BlockType, Operator, ValType};
 
 pub const STYLUS_STACK_LEFT: &str = "stylus_stack_left";
+pub const STYLUS_DEPTH_POLLS: &str = "stylus_depth_polls";
+pub const STYLUS_DEPTH_DEBT: &str = "stylus_depth_debt";
 
 /// This middleware ensures stack overflows are deterministic across different compilers and targets.
 /// The internal notion of "stack space left" that makes this possible is strictly smaller than that of
@@ -33,6 +35,10 @@ pub struct DepthChecker {
     frame_limit: u32,
     /// The maximum number of overlapping value lifetimes in a frame
     frame_contention: u16,
+    /// The number of frames between checks, which is 1 for consensus
+    interval: u32,
+    /// When checks are sparse, the countdown to the next check and the space owed by unchecked frames
+    polls: RwLock<Option<(GlobalIndex, GlobalIndex)>>,
     /// The function types of the module being instrumented
     funcs: RwLock<Option<Arc<HashMap<FunctionIndex, FunctionType>>>>,
     /// The types of the module being instrumented
@@ -41,10 +47,20 @@ pub struct DepthChecker {
 
 impl DepthChecker {
     pub fn new(params: CompileMemoryParams) -> Self {
+        Self::with_interval(params, 1)
+    }
+
+    /// Checks the stack only on the first and every `interval`-th function entry thereafter.
+    /// Unchecked frames that don't fit take what space is left, owing the rest, which they repay
+    /// before reclaiming any on return. Overflows still trap, but up to `interval - 1` frames late.
+    /// Debug only.
+    pub fn with_interval(params: CompileMemoryParams, interval: u32) -> Self {
         Self {
             global: RwLock::default(),
             frame_limit: params.max_frame_size,
             frame_contention: params.max_frame_contention,
+            interval: interval.max(1),
+            polls: RwLock::default(),
             funcs: RwLock::default(),
             sigs: RwLock::default(),
         }
@@ -62,6 +78,12 @@ impl<M: ModuleMod> Middleware<M> for DepthChecker {
         let limit = GlobalInit::I32Const(0);
         let space = module.add_global(STYLUS_STACK_LEFT, Type::I32, limit)?;
         *self.global.write() = Some(space);
+        if self.interval > 1 {
+            let polls =
+                module.add_global(STYLUS_DEPTH_POLLS, Type::I32, GlobalInit::I32Const(1))?;
+            let debt = module.add_global(STYLUS_DEPTH_DEBT, Type::I32, GlobalInit::I32Const(0))?;
+            *self.polls.write() = Some((polls, debt));
+        }
         *self.funcs.write() = Some(Arc::new(module.all_functions()?));
         *self.sigs.write() = Some(Arc::new(module.all_signatures()?));
         Ok(())
@@ -74,6 +96,9 @@ impl<M: ModuleMod> Middleware<M> for DepthChecker {
             self.sigs.read().clone().expect("no sigs"),
             self.frame_limit,
             self.frame_contention,
+            self.polls
+                .read()
+                .map(|(polls, debt)| (polls, debt, self.interval)),
             func,
         ))
     }
@@ -99,6 +124,8 @@ pub struct FuncDepthChecker<'a> {
     frame_limit: u32,
     /// The maximum number of overlapping value lifetimes in a frame
     frame_contention: u16,
+    /// The countdown and debt globals, and the interval, when checks are sparse
+    polls: Option<(GlobalIndex, GlobalIndex, u32)>,
     /// The number of open scopes
     scopes: isize,
     /// The entirety of the func's original instructions
@@ -114,6 +141,7 @@ impl<'a> FuncDepthChecker<'a> {
         sigs: Arc<HashMap<SignatureIndex, FunctionType>>,
         frame_limit: u32,
         frame_contention: u16,
+        polls: Option<(GlobalIndex, GlobalIndex, u32)>,
         func: LocalFunctionIndex,
     ) -> Self {
         Self {
@@ -124,6 +152,7 @@ impl<'a> FuncDepthChecker<'a> {
             func,
             frame_limit,
             frame_contention,
+            polls,
             scopes: 1, // a function starts with an open scope
             code: vec![],
             done: false,
@@ -177,7 +206,7 @@ impl<'a> FuncMiddleware<'a> for FuncDepthChecker<'a> {
         }
 
         let blockty = BlockType::Empty;
-        out.extend([
+        let check = [
             // if space <= size => panic with depth = 0
             GlobalGet { global_index },
             I32Const { value: size as i32 },
@@ -187,21 +216,101 @@ impl<'a> FuncMiddleware<'a> for FuncDepthChecker<'a> {
             GlobalSet { global_index },
             Unreachable,
             End,
-            // space -= size
-            GlobalGet { global_index },
-            I32Const { value: size as i32 },
-            I32Sub,
-            GlobalSet { global_index },
-        ]);
+        ];
 
-        let reclaim = |out: &mut O| {
-            out.extend([
+        match self.polls {
+            None => out.extend(check.into_iter().chain([
+                // space -= size
+                GlobalGet { global_index },
+                I32Const { value: size as i32 },
+                I32Sub,
+                GlobalSet { global_index },
+            ])),
+            Some((polls, debt, interval)) => {
+                let (polls, debt) = (polls.as_u32(), debt.as_u32());
+                out.extend([
+                    // if --polls == 0 => polls = interval and check
+                    GlobalGet {
+                        global_index: polls,
+                    },
+                    I32Const { value: 1 },
+                    I32Sub,
+                    GlobalSet {
+                        global_index: polls,
+                    },
+                    GlobalGet {
+                        global_index: polls,
+                    },
+                    I32Eqz,
+                    If { blockty },
+                    I32Const {
+                        value: interval as i32,
+                    },
+                    GlobalSet {
+                        global_index: polls,
+                    },
+                ]);
+                out.extend(check);
+                out.extend([
+                    End,
+                    // if space < size => debt += size - space and space = 0
+                    GlobalGet { global_index },
+                    I32Const { value: size as i32 },
+                    I32LtU,
+                    If { blockty },
+                    GlobalGet { global_index: debt },
+                    I32Const { value: size as i32 },
+                    I32Add,
+                    GlobalGet { global_index },
+                    I32Sub,
+                    GlobalSet { global_index: debt },
+                    I32Const { value: 0 },
+                    GlobalSet { global_index },
+                    // else space -= size
+                    Else,
+                    GlobalGet { global_index },
+                    I32Const { value: size as i32 },
+                    I32Sub,
+                    GlobalSet { global_index },
+                    End,
+                ]);
+            }
+        }
+
+        let polls = self.polls;
+        let reclaim = |out: &mut O| match polls {
+            None => out.extend([
                 // space += size
                 GlobalGet { global_index },
                 I32Const { value: size as i32 },
                 I32Add,
                 GlobalSet { global_index },
-            ])
+            ]),
+            Some((_, debt, _)) => {
+                let debt = debt.as_u32();
+                out.extend([
+                    // if debt < size => space += size - debt and debt = 0
+                    GlobalGet { global_index: debt },
+                    I32Const { value: size as i32 },
+                    I32LtU,
+                    If { blockty },
+                    GlobalGet { global_index },
+                    I32Const { value: size as i32 },
+                    I32Add,
+                    GlobalGet { global_index: debt },
+                    I32Sub,
+                    GlobalSet { global_index },
+                    I32Const { value: 0 },
+                    GlobalSet { global_index: debt },
+                    // else debt -= size
+                    Else,
+                    GlobalGet { global_index: debt },
+                    I32Const { value: size as i32 },
+                    I32Sub,
+                    GlobalSet { global_index: debt },
+                    End,
+                ])
+            }
         };
 
         // add an extraneous return instruction to the end to match Arbitrator
@@ -515,5 +624,10 @@ impl DepthCheckedMachine for Machine {
 
     fn set_stack(&mut self, size: u32) {
         self.set_global(STYLUS_STACK_LEFT, size.into()).unwrap();
+
+        // sparse checks restart their countdown, with nothing owed
+        if self.set_global(STYLUS_DEPTH_POLLS, 1_u32.into()).is_ok() {
+            self.set_global(STYLUS_DEPTH_DEBT, 0_u32.into()).unwrap();
+        }
     }
 }
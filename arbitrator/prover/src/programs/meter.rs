@@ -9,7 +9,11 @@ use crate::{
     value::FunctionType,
     Machine,
 };
-use arbutil::{evm, operator::OperatorInfo, Bytes32};
+use arbutil::{
+    evm,
+    operator::{OperatorCode, OperatorInfo},
+    pricing, Bytes32,
+};
 use derivative::Derivative;
 use eyre::Result;
 use fnv::FnvHashMap as HashMap;
@@ -53,6 +57,25 @@ impl Meter<OpCosts> {
             sigs: RwLock::default(),
         }
     }
+
+    /// Like [`Meter::new`], but prices the opcodes in `overrides` by their given costs instead.
+    pub fn with_overrides(
+        pricing: &CompilePricingParams,
+        overrides: &HashMap<OperatorCode, u64>,
+    ) -> Meter<impl OpcodePricer> {
+        let costs = pricing.costs;
+        let overrides = Arc::new(overrides.clone());
+        let pricer = move |op: &Operator, sigs: &SigMap| match overrides.get(&op.into()) {
+            Some(cost) => *cost,
+            None => costs(op, sigs),
+        };
+        Meter {
+            costs: pricer,
+            header_cost: pricing.ink_header_cost,
+            globals: RwLock::default(),
+            sigs: RwLock::default(),
+        }
+    }
 }
 
 impl<F: OpcodePricer> Meter<F> {
@@ -241,6 +264,13 @@ pub trait MeteredMachine {
     fn ink_left(&self) -> MachineMeter;
     fn set_meter(&mut self, meter: MachineMeter);
 
+    /// Sets the ink left without touching the status, which may leave an exhausted meter with ink.
+    /// Most callers want `set_ink` instead.
+    fn set_ink_left(&mut self, ink: u64);
+
+    /// Sets the ink status without touching the ink left. Any nonzero status means exhausted.
+    fn set_ink_status(&mut self, status: u32);
+
     fn set_ink(&mut self, ink: u64) {
         self.set_meter(MachineMeter::Ready(ink));
     }
@@ -288,7 +318,7 @@ pub trait MeteredMachine {
     /// Pays for both I/O and keccak.
     fn pay_for_keccak(&mut self, bytes: u32) -> Result<(), OutOfInkError> {
         let words = evm::evm_words(bytes).saturating_sub(2);
-        self.buy_ink(sat_add_mul(121800, 21000, words))
+        self.buy_ink(sat_add_mul(pricing::KECCAK_INK, 21000, words))
     }
 
     /// Pays for copying bytes from geth.
@@ -365,6 +395,14 @@ impl MeteredMachine for Machine {
         self.set_global(STYLUS_INK_LEFT, ink.into()).unwrap();
         self.set_global(STYLUS_INK_STATUS, status.into()).unwrap();
     }
+
+    fn set_ink_left(&mut self, ink: u64) {
+        self.set_global(STYLUS_INK_LEFT, ink.into()).unwrap();
+    }
+
+    fn set_ink_status(&mut self, status: u32) {
+        self.set_global(STYLUS_INK_STATUS, status.into()).unwrap();
+    }
 }
 
 pub fn pricing_v1(op: &Operator, tys: &HashMap<SignatureIndex, FunctionType>) -> u64 {
@@ -11,12 +11,12 @@ use crate::{
 use arbutil::{math::SaturatingSum, Color};
 use eyre::{bail, eyre, Report, Result, WrapErr};
 use fnv::FnvHashMap as HashMap;
-use std::fmt::Debug;
+use std::{fmt::Debug, ops::RangeInclusive};
 use wasmer_types::{
     entity::EntityRef, FunctionIndex, GlobalIndex, GlobalInit, ImportIndex, LocalFunctionIndex,
     SignatureIndex, Type,
 };
-use wasmparser::{Operator, ValType};
+use wasmparser::{Operator, Parser, Payload, ValType};
 
 #[cfg(feature = "native")]
 use {
@@ -36,6 +36,7 @@ pub mod heap;
 pub mod memory;
 pub mod meter;
 pub mod prelude;
+pub mod safe_div;
 pub mod start;
 
 pub const STYLUS_ENTRY_POINT: &str = "user_entrypoint";
@@ -383,6 +384,10 @@ pub struct StylusData {
     pub footprint: u16,
     /// Entrypoint offset.
     pub user_main: u32,
+    /// The page limit enforced during activation, or `u16::MAX` if none was.
+    pub page_limit: u16,
+    /// The Stylus version the program was activated under.
+    pub version: u16,
 }
 
 impl StylusData {
@@ -395,6 +400,42 @@ impl StylusData {
     }
 }
 
+/// The gas activation charges for each of the wasm's features.
+mod activation_gas {
+    /// Converts a number of microseconds to gas.
+    // TODO: collapse to a single value after finalizing factors
+    fn us_to_gas(us: u64) -> u64 {
+        let fudge = 2;
+        let sync_rate = 1_000_000 / 2;
+        let speed = 7_000_000;
+        us.saturating_mul(fudge * speed) / sync_rate
+    }
+
+    pub fn wasm(len: u64) -> u64 {
+        us_to_gas(len.saturating_mul(31_733 / 100_000))
+    }
+
+    pub fn funcs(count: u64) -> u64 {
+        us_to_gas(count.saturating_mul(17_263) / 100_000)
+    }
+
+    pub fn data(bytes: u64) -> u64 {
+        us_to_gas(bytes.saturating_mul(17_376) / 100_000)
+    }
+
+    pub fn elems(count: u64) -> u64 {
+        us_to_gas(count.saturating_mul(17_376) / 100_000)
+    }
+
+    pub fn memory(pages: u64) -> u64 {
+        us_to_gas(pages.saturating_mul(2217))
+    }
+
+    pub fn code(ops: u64) -> u64 {
+        us_to_gas(ops.saturating_mul(535) / 1_000)
+    }
+}
+
 impl Module {
     pub fn activate(
         wasm: &[u8],
@@ -403,18 +444,9 @@ impl Module {
         debug: bool,
         gas: &mut u64,
     ) -> Result<(Self, StylusData)> {
-        // converts a number of microseconds to gas
-        // TODO: collapse to a single value after finalizing factors
-        let us_to_gas = |us: u64| {
-            let fudge = 2;
-            let sync_rate = 1_000_000 / 2;
-            let speed = 7_000_000;
-            us.saturating_mul(fudge * speed) / sync_rate
-        };
-
         macro_rules! pay {
-            ($us:expr) => {
-                let amount = us_to_gas($us);
+            ($amount:expr) => {
+                let amount = $amount;
                 if *gas < amount {
                     *gas = 0;
                     bail!("out of gas");
@@ -425,7 +457,7 @@ impl Module {
 
         // pay for wasm
         let wasm_len = wasm.len() as u64;
-        pay!(wasm_len.saturating_mul(31_733 / 100_000));
+        pay!(activation_gas::wasm(wasm_len));
 
         let compile = CompileConfig::version(version, debug);
         let (bin, stylus_data) =
@@ -433,27 +465,153 @@ impl Module {
 
         // pay for funcs
         let funcs = bin.functions.len() as u64;
-        pay!(funcs.saturating_mul(17_263) / 100_000);
+        pay!(activation_gas::funcs(funcs));
 
         // pay for data
         let data = bin.datas.iter().map(|x| x.data.len()).saturating_sum() as u64;
-        pay!(data.saturating_mul(17_376) / 100_000);
+        pay!(activation_gas::data(data));
 
         // pay for elements
         let elems = bin.elements.iter().map(|x| x.range.len()).saturating_sum() as u64;
-        pay!(elems.saturating_mul(17_376) / 100_000);
+        pay!(activation_gas::elems(elems));
 
         // pay for memory
         let mem = bin.memories.first().map(|x| x.initial).unwrap_or_default();
-        pay!(mem.saturating_mul(2217));
+        pay!(activation_gas::memory(mem));
 
         // pay for code
         let code = bin.codes.iter().map(|x| x.expr.len()).saturating_sum() as u64;
-        pay!(code.saturating_mul(535) / 1_000);
+        pay!(activation_gas::code(code));
 
         let module = Self::from_user_binary(&bin, compile.debug.debug_funcs, Some(stylus_data))
             .wrap_err("failed to build user module")?;
 
         Ok((module, stylus_data))
     }
+
+    /// Cheaply checks that a program's declared maximum memory fits within `page_limit`, without
+    /// validating or instrumenting the wasm. Memories without a maximum are bounded by their
+    /// initial size instead. This is stricter than `activate`, which only bounds the initial size,
+    /// letting gateways fail fast on programs that could grow too large.
+    pub fn check_page_limit(wasm: &[u8], page_limit: u16) -> Result<()> {
+        for payload in Parser::new(0).parse_all(wasm) {
+            let Payload::MemorySection(mut memories) = payload? else {
+                continue;
+            };
+            if memories.get_count() > 0 {
+                let memory = memories.read()?;
+                let pages = memory.maximum.unwrap_or(memory.initial);
+                if pages > page_limit.into() {
+                    let limit = page_limit.red();
+                    bail!("memory exceeds limit: {} > {limit}", pages.red());
+                }
+            }
+            break; // later sections don't matter
+        }
+        Ok(())
+    }
+
+    /// Cheaply computes a lower bound on the gas `activate` charges, without validating or
+    /// instrumenting the wasm. Every program that activates successfully costs at least this much,
+    /// so programs whose floor exceeds a budget can be rejected early.
+    pub fn activation_gas_floor(wasm: &[u8]) -> Result<u64> {
+        Ok(StaticCounts::new(wasm)?.floor(wasm.len() as u64))
+    }
+
+    /// Cheaply bounds the gas `activate` charges using only the wasm's static properties, without
+    /// validating or instrumenting it. The lower bound is the `activation_gas_floor`. The upper
+    /// bound estimates how much instrumentation expands each op and function.
+    /// Imports are free to activate, so they don't affect either bound.
+    pub fn activation_gas_bounds(wasm: &[u8]) -> Result<RangeInclusive<u64>> {
+        // heuristics, not derived from the middlewares: roughly the most ops instrumentation adds
+        // to any single op (memory.fill's dynamic pricing) and to each function (the depth
+        // checker's prologue and return), which new middlewares may need to raise
+        const OP_EXPANSION: u64 = 20;
+        const FUNC_EXPANSION: u64 = 30;
+
+        let counts = StaticCounts::new(wasm)?;
+        let ops = counts.ops.saturating_mul(OP_EXPANSION);
+        let code = ops.saturating_add(counts.bodies.saturating_mul(FUNC_EXPANSION));
+
+        let wasm_len = wasm.len() as u64;
+        let upper = [
+            counts.base_gas(wasm_len),
+            activation_gas::elems(counts.elems),
+            activation_gas::code(code),
+        ];
+        Ok(counts.floor(wasm_len)..=upper.into_iter().saturating_sum())
+    }
+}
+
+/// The static properties of a wasm that bound its activation gas, counted without validation.
+#[derive(Default)]
+struct StaticCounts {
+    funcs: u64,
+    data: u64,
+    elems: u64,
+    pages: u64,
+    /// The number of ops across all function bodies.
+    ops: u64,
+    bodies: u64,
+}
+
+impl StaticCounts {
+    fn new(wasm: &[u8]) -> Result<Self> {
+        let mut counts = Self::default();
+        let mut pages = None;
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Payload::FunctionSection(functions) => {
+                    counts.funcs = counts.funcs.saturating_add(functions.get_count().into());
+                }
+                Payload::DataSection(mut datas) => {
+                    for _ in 0..datas.get_count() {
+                        let len = datas.read()?.data.len() as u64;
+                        counts.data = counts.data.saturating_add(len);
+                    }
+                }
+                Payload::ElementSection(mut elements) => {
+                    for _ in 0..elements.get_count() {
+                        let len = elements.read()?.range.len() as u64;
+                        counts.elems = counts.elems.saturating_add(len);
+                    }
+                }
+                Payload::MemorySection(mut memories) if pages.is_none() => {
+                    if memories.get_count() > 0 {
+                        pages = Some(memories.read()?.initial);
+                    }
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let mut ops = body.get_operators_reader()?;
+                    while !ops.eof() {
+                        ops.read()?;
+                        counts.ops = counts.ops.saturating_add(1);
+                    }
+                    counts.bodies = counts.bodies.saturating_add(1);
+                }
+                _ => {}
+            }
+        }
+        counts.pages = pages.unwrap_or_default();
+        Ok(counts)
+    }
+
+    /// The least gas `activate` charges. Elements are left out, since counting them precisely
+    /// requires validation.
+    fn floor(&self, wasm_len: u64) -> u64 {
+        let code = activation_gas::code(self.ops);
+        self.base_gas(wasm_len).saturating_add(code)
+    }
+
+    /// The gas `activate` charges for everything but elements and code.
+    fn base_gas(&self, wasm_len: u64) -> u64 {
+        let gas = [
+            activation_gas::wasm(wasm_len),
+            activation_gas::funcs(self.funcs),
+            activation_gas::data(self.data),
+            activation_gas::memory(self.pages),
+        ];
+        gas.into_iter().saturating_sum()
+    }
 }
@@ -0,0 +1,143 @@
+// Copyright 2024, Offchain Labs, Inc.
+// For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+use super::{FuncMiddleware, Middleware, ModuleMod};
+use eyre::Result;
+use parking_lot::RwLock;
+use wasmer_types::{GlobalIndex, GlobalInit, LocalFunctionIndex, Type};
+use wasmparser::Operator;
+
+pub const SAFE_DIV_GLOBALS: [&str; 4] = [
+    "stylus_safe_div_i32_lhs",
+    "stylus_safe_div_i32_rhs",
+    "stylus_safe_div_i64_lhs",
+    "stylus_safe_div_i64_rhs",
+];
+
+/// Rewrites integer division and remainder so that they produce defined values instead of trapping.
+/// Dividing by zero produces `0`, and the signed overflow of `MIN / -1` wraps to `MIN`, which
+/// matches the EVM's `DIV`, `SDIV`, `MOD`, and `SMOD`.
+///
+/// This changes the semantics of wasm, so it's for fuzzing only and must never be used in consensus.
+#[derive(Debug, Default)]
+pub struct SafeDivision {
+    globals: RwLock<Option<[GlobalIndex; 4]>>,
+}
+
+impl SafeDivision {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<M: ModuleMod> Middleware<M> for SafeDivision {
+    type FM<'a> = FuncSafeDivision;
+
+    fn update_module(&self, module: &mut M) -> Result<()> {
+        let [a32, b32, a64, b64] = SAFE_DIV_GLOBALS;
+        let globals = [
+            module.add_global(a32, Type::I32, GlobalInit::I32Const(0))?,
+            module.add_global(b32, Type::I32, GlobalInit::I32Const(0))?,
+            module.add_global(a64, Type::I64, GlobalInit::I64Const(0))?,
+            module.add_global(b64, Type::I64, GlobalInit::I64Const(0))?,
+        ];
+        *self.globals.write() = Some(globals);
+        Ok(())
+    }
+
+    fn instrument<'a>(&self, _: LocalFunctionIndex) -> Result<Self::FM<'a>> {
+        let globals = self.globals.read().expect("no globals");
+        Ok(FuncSafeDivision { globals })
+    }
+
+    fn name(&self) -> &'static str {
+        "safe division"
+    }
+}
+
+#[derive(Debug)]
+pub struct FuncSafeDivision {
+    globals: [GlobalIndex; 4],
+}
+
+impl<'a> FuncMiddleware<'a> for FuncSafeDivision {
+    fn feed<O>(&mut self, op: Operator<'a>, out: &mut O) -> Result<()>
+    where
+        O: Extend<Operator<'a>>,
+    {
+        use Operator::*;
+
+        let wide = match op {
+            I32DivS | I32DivU | I32RemS | I32RemU => false,
+            I64DivS | I64DivU | I64RemS | I64RemU => true,
+            _ => {
+                out.extend([op]);
+                return Ok(());
+            }
+        };
+        let [a32, b32, a64, b64] = self.globals.map(|x| x.as_u32());
+        let (lhs, rhs) = match wide {
+            true => (a64, b64),
+            false => (a32, b32),
+        };
+        let one = match wide {
+            true => I64Const { value: 1 },
+            false => I32Const { value: 1 },
+        };
+        let zero = match wide {
+            true => I64Const { value: 0 },
+            false => I32Const { value: 0 },
+        };
+        let eqz = match wide {
+            true => I64Eqz,
+            false => I32Eqz,
+        };
+
+        // save the operands, leaving the dividend on the stack
+        out.extend([
+            GlobalSet { global_index: rhs },
+            GlobalSet { global_index: lhs },
+            GlobalGet { global_index: lhs },
+        ]);
+
+        // replace a trapping divisor with 1: the result is discarded for 0 and is right for -1
+        out.extend([
+            one,
+            GlobalGet { global_index: rhs },
+            GlobalGet { global_index: rhs },
+            eqz.clone(),
+        ]);
+        match op {
+            I32DivS => out.extend([
+                GlobalGet { global_index: lhs },
+                I32Const { value: i32::MIN },
+                I32Eq,
+                GlobalGet { global_index: rhs },
+                I32Const { value: -1 },
+                I32Eq,
+                I32And,
+                I32Or,
+            ]),
+            I64DivS => out.extend([
+                GlobalGet { global_index: lhs },
+                I64Const { value: i64::MIN },
+                I64Eq,
+                GlobalGet { global_index: rhs },
+                I64Const { value: -1 },
+                I64Eq,
+                I32And,
+                I32Or,
+            ]),
+            _ => {}
+        }
+        out.extend([Select, op]);
+
+        // produce 0 when dividing by zero
+        out.extend([zero, GlobalGet { global_index: rhs }, eqz, I32Eqz, Select]);
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "safe division"
+    }
+}
@@ -1,10 +1,19 @@
 // Copyright 2022-2023, Offchain Labs, Inc.
 // For license information, see https://github.com/nitro/blob/master/LICENSE
 
-use crate::{env::WasmEnv, native::NativeInstance};
+use crate::{
+    cache::{InitCache, StorePoolConfig},
+    env::WasmEnv,
+    native::{self, NativeInstance},
+    run::RunProgram,
+    test::{api::TestEvmApi, random_bytes32, test_configs, TestInstance},
+};
 use arbutil::{crypto, format};
 use eyre::Result;
-use prover::programs::{config::StylusConfig, STYLUS_ENTRY_POINT};
+use prover::programs::{
+    config::{CompileConfig, StylusConfig},
+    STYLUS_ENTRY_POINT,
+};
 use std::time::{Duration, Instant};
 use wasmer::{CompilerConfig, Imports, Instance, Module, Store};
 use wasmer_compiler_cranelift::{Cranelift, CraneliftOptLevel};
@@ -90,3 +99,43 @@ fn benchmark_wasmer() -> Result<()> {
     println!("Stylus:  {}", format::time(stylus()?));
     Ok(())
 }
+
+#[test]
+fn benchmark_store_pool() -> Result<()> {
+    // compares cached calls with and without pooling stores
+
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (asm, _, _) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    let (_, config, ink) = test_configs();
+
+    let module_hash = random_bytes32();
+    InitCache::insert(module_hash, &asm, 1, true)?;
+
+    let calls = |count: usize| -> Result<Duration> {
+        let time = Instant::now();
+        for _ in 0..count {
+            let (evm, mut evm_data) = TestEvmApi::new(CompileConfig::version(1, true));
+            evm_data.module_hash = module_hash;
+            let mut native =
+                unsafe { TestInstance::deserialize_cached(&[], 1, evm, evm_data, true, false)? };
+            native.run_main(&[0x00], config, ink)?;
+            native.recycle();
+        }
+        Ok(time.elapsed())
+    };
+
+    let unpooled = calls(1000)?;
+    InitCache::set_store_pool(Some(StorePoolConfig {
+        capacity: 4,
+        max_uses: 64,
+    }));
+    let pooled = calls(1000)?;
+    InitCache::set_store_pool(None);
+    InitCache::evict(module_hash, 1, true);
+
+    println!("Unpooled: {}", format::time(unpooled));
+    println!("Pooled:   {}", format::time(pooled));
+    Ok(())
+}
@@ -1,19 +1,26 @@
 // Copyright 2022-2024, Offchain Labs, Inc.
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
-use arbutil::Bytes32;
-use eyre::Result;
+use crate::native::check_memory_bound;
+use arbutil::{crypto, Bytes32};
+use brotli::{Dictionary, DEFAULT_WINDOW_SIZE};
+use eyre::{bail, eyre, Result};
 use lazy_static::lazy_static;
 use lru::LruCache;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, MutexGuard};
 use prover::programs::config::CompileConfig;
-use std::{collections::HashMap, num::NonZeroUsize};
+use std::{collections::HashMap, fs, mem, num::NonZeroUsize, path::Path, sync::Arc};
 use wasmer::{Engine, Module, Store};
 
 lazy_static! {
     static ref INIT_CACHE: Mutex<InitCache> = Mutex::new(InitCache::new(256));
+    static ref COMPILE_CACHE: Mutex<CompileCache> = Mutex::new(CompileCache::new(256));
 }
 
+/// The version of the archive format produced by `InitCache::export`.
+/// Version 2 added optional brotli compression of each module.
+const ARCHIVE_VERSION: u8 = 2;
+
 macro_rules! cache {
     () => {
         INIT_CACHE.lock()
@@ -23,6 +30,78 @@ macro_rules! cache {
 pub struct InitCache {
     arbos: HashMap<CacheKey, CacheItem>,
     lru: LruCache<CacheKey, CacheItem>,
+    pool: Option<StorePoolConfig>,
+    collisions: CollisionPolicy,
+    eviction_hook: Option<EvictionHook>,
+    /// Evictions awaiting the hook, which is only called once the lock is released.
+    evictions: Vec<(CacheKey, EvictionReason)>,
+    /// Whether caching has been disabled, after which nothing is ever cached again.
+    disabled: bool,
+    /// The largest serialized module that may be cached, in bytes, if bounded.
+    max_module_size: Option<usize>,
+}
+
+/// Why an item left the cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvictionReason {
+    /// The LRU cache was full.
+    Lru,
+    /// The item was evicted or invalidated by request.
+    Explicit,
+    /// A reorg demoted the long-term cache to an LRU cache too small to hold it.
+    Reorg,
+}
+
+/// Observes evictions, receiving the module hash, version, and debug flag of each evicted item.
+/// The hook runs without the cache's lock held, so it may use the cache, such as to re-pin modules.
+pub type EvictionHook = Arc<dyn Fn(Bytes32, u16, bool, EvictionReason) + Send + Sync>;
+
+/// What inserting does when an item with the same key but different bytes is already cached.
+/// Module hashes should never collide, so a mismatch indicates a bug elsewhere.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Replace the cached item with the new bytes.
+    #[default]
+    Overwrite,
+    /// Fail the insertion, keeping the cached item.
+    Reject,
+}
+
+/// Limits on the pool of reusable stores each cached module keeps when pooling is enabled.
+/// Pooling amortizes store creation for tooling making many calls, and is off by default.
+#[derive(Clone, Copy, Debug)]
+pub struct StorePoolConfig {
+    /// The most idle stores to keep per module.
+    pub capacity: usize,
+    /// The most instances a store may host before it's discarded.
+    /// A store retains the objects of every instance created in it until the store itself is dropped,
+    /// so unbounded reuse would grow memory without bound.
+    pub max_uses: u32,
+}
+
+/// The idle stores of a cached module, each with the number of instances it has hosted.
+type StorePool = Arc<Mutex<Vec<(Store, u32)>>>;
+
+/// A store drawn from a module's pool, to be returned once the instance using it is dropped.
+#[derive(Debug)]
+pub struct PooledStore {
+    pool: StorePool,
+    config: StorePoolConfig,
+    uses: u32,
+}
+
+impl PooledStore {
+    /// Returns a store to its pool, unless it's been used too many times or the pool is full.
+    /// Pools belong to cache items, so a store is never reused after its item is replaced.
+    pub(crate) fn restore(self, store: Store) {
+        if self.uses >= self.config.max_uses {
+            return;
+        }
+        let mut pool = self.pool.lock();
+        if pool.len() < self.config.capacity {
+            pool.push((store, self.uses));
+        }
+    }
 }
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
@@ -42,20 +121,111 @@ impl CacheKey {
     }
 }
 
+/// Asm compiled ahead of time by `native::precompile`, keyed by the hash of its wasm.
+/// Activation checks here before compiling. Compilation is deterministic, so a hit yields the same
+/// asm activation would have produced, and warming the cache never affects consensus.
+pub struct CompileCache {
+    lru: LruCache<CacheKey, Arc<[u8]>>,
+    stats: CompileCacheStats,
+}
+
+/// How often activation found its asm in the `CompileCache`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompileCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CompileCache {
+    fn new(size: usize) -> Self {
+        Self {
+            lru: LruCache::new(NonZeroUsize::new(size).unwrap()),
+            stats: CompileCacheStats::default(),
+        }
+    }
+
+    /// Caches the asm compiled from a wasm.
+    pub fn insert(wasm: &[u8], asm: &[u8], version: u16, debug: bool) {
+        let key = CacheKey::new(crypto::keccak(wasm).into(), version, debug);
+        COMPILE_CACHE.lock().lru.put(key, asm.into());
+    }
+
+    /// Whether the asm compiled from a wasm is cached, without affecting the stats.
+    pub fn contains(wasm: &[u8], version: u16, debug: bool) -> bool {
+        let key = CacheKey::new(crypto::keccak(wasm).into(), version, debug);
+        COMPILE_CACHE.lock().lru.contains(&key)
+    }
+
+    /// Retrieves the asm compiled from a wasm, recording whether it was cached.
+    pub fn get(wasm: &[u8], version: u16, debug: bool) -> Option<Vec<u8>> {
+        let key = CacheKey::new(crypto::keccak(wasm).into(), version, debug);
+        let mut cache = COMPILE_CACHE.lock();
+        let asm = cache.lru.get(&key).map(|asm| asm.to_vec());
+        match asm {
+            Some(_) => cache.stats.hits += 1,
+            None => cache.stats.misses += 1,
+        }
+        asm
+    }
+
+    /// Removes the asm compiled from a wasm, so that the next activation compiles it afresh.
+    pub fn invalidate(wasm: &[u8], version: u16, debug: bool) {
+        let key = CacheKey::new(crypto::keccak(wasm).into(), version, debug);
+        COMPILE_CACHE.lock().lru.pop(&key);
+    }
+
+    /// How often activation has found its asm in the cache.
+    pub fn stats() -> CompileCacheStats {
+        COMPILE_CACHE.lock().stats
+    }
+}
+
 #[derive(Clone)]
 struct CacheItem {
     module: Module,
     engine: Engine,
+    /// The size of the serialized module, in bytes.
+    size: usize,
+    /// The hash of the serialized module, for detecting collisions.
+    digest: Bytes32,
+    stores: StorePool,
+    /// Opaque bytes supplied by the operator at insertion, dropped along with the item.
+    metadata: Option<Arc<[u8]>>,
 }
 
 impl CacheItem {
-    fn new(module: Module, engine: Engine) -> Self {
-        Self { module, engine }
+    fn new(module: Module, engine: Engine, size: usize, digest: Bytes32) -> Self {
+        Self {
+            module,
+            engine,
+            size,
+            digest,
+            stores: StorePool::default(),
+            metadata: None,
+        }
     }
 
     fn data(&self) -> (Module, Store) {
         (self.module.clone(), Store::new(self.engine.clone()))
     }
+
+    /// Like `data`, but draws the store from the item's pool when pooling is enabled.
+    fn pooled(&self, config: Option<StorePoolConfig>) -> (Module, Store, Option<PooledStore>) {
+        let Some(config) = config else {
+            let (module, store) = self.data();
+            return (module, store, None);
+        };
+        let (store, uses) = self.stores.lock().pop().unwrap_or_else(|| {
+            let store = Store::new(self.engine.clone());
+            (store, 0)
+        });
+        let pooled = PooledStore {
+            pool: self.stores.clone(),
+            config,
+            uses: uses + 1,
+        };
+        (self.module.clone(), store, Some(pooled))
+    }
 }
 
 impl InitCache {
@@ -63,6 +233,138 @@ impl InitCache {
         Self {
             arbos: HashMap::new(),
             lru: LruCache::new(NonZeroUsize::new(size).unwrap()),
+            pool: None,
+            collisions: CollisionPolicy::default(),
+            eviction_hook: None,
+            evictions: vec![],
+            disabled: false,
+            max_module_size: None,
+        }
+    }
+
+    /// Disables caching for the rest of the process, dropping everything already cached.
+    /// Afterward, inserting only deserializes the module and lookups always miss, so each call
+    /// reconstructs its module from the bytes provided. This suits deterministic benchmarking and
+    /// memory-constrained environments.
+    pub fn disable() {
+        let mut cache = cache!();
+        cache.disabled = true;
+        let arbos: Vec<_> = cache.arbos.drain().map(|(key, _)| key).collect();
+        let lru: Vec<_> = cache.lru.iter().map(|(key, _)| *key).collect();
+        cache.lru.clear();
+        for key in arbos.into_iter().chain(lru) {
+            cache.evicted(key, EvictionReason::Explicit);
+        }
+        Self::unlock(cache);
+    }
+
+    /// Whether caching has been disabled via `disable`.
+    pub fn disabled() -> bool {
+        cache!().disabled
+    }
+
+    /// Bounds the size of the serialized modules that may be cached, in bytes, protecting the
+    /// cache's memory. Larger modules are never cached, though calls may still use them.
+    /// Modules already cached are unaffected. A `size` of `None` removes the bound.
+    pub fn set_max_module_size(size: Option<usize>) {
+        cache!().max_module_size = size;
+    }
+
+    /// Whether a serialized module of `size` bytes is too large to be cached.
+    pub fn oversized(size: usize) -> bool {
+        cache!().max_module_size.is_some_and(|max| size > max)
+    }
+
+    /// Deserializes a module without caching it.
+    fn uncached(module: &[u8], version: u16, debug: bool) -> Result<(Module, Store)> {
+        let (module, engine) = Self::deserialize(module, version, debug)?;
+        Ok((module, Store::new(engine)))
+    }
+
+    /// Deserializes a module, rejecting it if its memory exceeds the version's bound.
+    /// Only modules that pass may be cached.
+    fn deserialize(module: &[u8], version: u16, debug: bool) -> Result<(Module, Engine)> {
+        let compile = CompileConfig::version(version, debug);
+        let engine = compile.engine();
+        let module = unsafe { Module::deserialize_unchecked(&engine, module)? };
+        check_memory_bound(&module, &compile)?;
+        Ok((module, engine))
+    }
+
+    /// Installs or removes the hook observing evictions.
+    pub fn set_eviction_hook(hook: Option<EvictionHook>) {
+        cache!().eviction_hook = hook;
+    }
+
+    /// Notes an eviction for the hook. Nothing is recorded when there isn't one.
+    fn evicted(&mut self, key: CacheKey, reason: EvictionReason) {
+        if self.eviction_hook.is_some() {
+            self.evictions.push((key, reason));
+        }
+    }
+
+    /// Puts an item in the LRU cache, noting any other item it pushes out.
+    fn put_lru(&mut self, key: CacheKey, item: CacheItem, reason: EvictionReason) {
+        if let Some((evicted, _)) = self.lru.push(key, item) {
+            if evicted != key {
+                self.evicted(evicted, reason);
+            }
+        }
+    }
+
+    /// Moves every long-term item to the LRU cache. Not all will fit.
+    fn demote_arbos(&mut self) {
+        let items: Vec<_> = self.arbos.drain().collect();
+        for (key, item) in items {
+            self.put_lru(key, item, EvictionReason::Reorg);
+        }
+    }
+
+    /// Releases the lock, then reports any evictions to the hook.
+    fn unlock(mut cache: MutexGuard<'_, InitCache>) {
+        let evictions = mem::take(&mut cache.evictions);
+        let Some(hook) = cache.eviction_hook.clone() else {
+            return;
+        };
+        drop(cache);
+        for (key, reason) in evictions {
+            hook(key.module_hash, key.version, key.debug, reason);
+        }
+    }
+
+    /// Sets what happens when inserting different bytes under an already-cached key.
+    pub fn set_collision_policy(policy: CollisionPolicy) {
+        cache!().collisions = policy;
+    }
+
+    /// Applies the collision policy to an insertion of the given bytes.
+    fn check_collision(&self, key: &CacheKey, digest: Bytes32) -> Result<()> {
+        let item = match self.arbos.get(key) {
+            Some(item) => item,
+            None => match self.lru.peek(key) {
+                Some(item) => item,
+                None => return Ok(()),
+            },
+        };
+        if item.digest != digest && self.collisions == CollisionPolicy::Reject {
+            bail!(
+                "module hash {} collides with a cached module of different bytes",
+                key.module_hash
+            );
+        }
+        Ok(())
+    }
+
+    /// Enables or disables pooling the stores of cached modules. Disabling drops all idle stores.
+    pub fn set_store_pool(config: Option<StorePoolConfig>) {
+        let mut cache = cache!();
+        cache.pool = config;
+        if config.is_none() {
+            let arbos = cache.arbos.values();
+            let lru = cache.lru.iter().map(|(_, item)| item);
+            for item in arbos.chain(lru) {
+                item.stores.lock().clear();
+            }
         }
     }
 
@@ -83,29 +385,106 @@ impl InitCache {
         None
     }
 
+    /// Like `get`, but draws the store from the module's pool when pooling is enabled.
+    pub fn get_pooled(
+        module_hash: Bytes32,
+        version: u16,
+        debug: bool,
+    ) -> Option<(Module, Store, Option<PooledStore>)> {
+        let mut cache = cache!();
+        let cache = &mut *cache;
+        let key = CacheKey::new(module_hash, version, debug);
+
+        let item = match cache.arbos.get(&key) {
+            Some(item) => item,
+            None => cache.lru.get(&key)?,
+        };
+        Some(item.pooled(cache.pool))
+    }
+
+    /// The summed sizes of the serialized modules resident in either cache, in bytes.
+    /// This excludes the overhead of the compiled code and the cache's own bookkeeping.
+    pub fn size_bytes() -> u64 {
+        cache!().resident_bytes()
+    }
+
+    fn resident_bytes(&self) -> u64 {
+        let arbos = self.arbos.values().map(|item| item.size as u64);
+        let lru = self.lru.iter().map(|(_, item)| item.size as u64);
+        arbos.chain(lru).sum()
+    }
+
+    /// Whether an item is in either cache. Unlike `get`, this doesn't promote LRU items.
+    pub fn contains(module_hash: Bytes32, version: u16, debug: bool) -> bool {
+        let cache = cache!();
+        let key = CacheKey::new(module_hash, version, debug);
+        cache.arbos.contains_key(&key) || cache.lru.contains(&key)
+    }
+
+    /// Retrieves the metadata an item was inserted with, if any.
+    /// Like `contains`, this doesn't promote LRU items.
+    pub fn metadata(module_hash: Bytes32, version: u16, debug: bool) -> Option<Vec<u8>> {
+        let cache = cache!();
+        let key = CacheKey::new(module_hash, version, debug);
+        let item = match cache.arbos.get(&key) {
+            Some(item) => item,
+            None => cache.lru.peek(&key)?,
+        };
+        item.metadata.as_deref().map(<[u8]>::to_vec)
+    }
+
     /// Inserts an item into the long term cache, stealing from the LRU cache if able.
     pub fn insert(
         module_hash: Bytes32,
         module: &[u8],
         version: u16,
         debug: bool,
+    ) -> Result<(Module, Store)> {
+        Self::insert_with_metadata(module_hash, module, version, debug, None)
+    }
+
+    /// Like `insert`, but tags the item with opaque metadata for the operator's own bookkeeping.
+    /// Metadata doesn't affect keying or eviction. Passing `None` keeps any the item already has.
+    pub fn insert_with_metadata(
+        module_hash: Bytes32,
+        module: &[u8],
+        version: u16,
+        debug: bool,
+        metadata: Option<&[u8]>,
     ) -> Result<(Module, Store)> {
         let key = CacheKey::new(module_hash, version, debug);
+        let metadata: Option<Arc<[u8]>> = metadata.map(Into::into);
 
-        // if in LRU, move to ArbOS
+        // the lock is held throughout, so that nothing is cached between checking and inserting
         let mut cache = cache!();
-        if let Some(item) = cache.lru.pop(&key) {
-            cache.arbos.insert(key, item.clone());
-            return Ok(item.data());
+        if cache.disabled || cache.max_module_size.is_some_and(|max| module.len() > max) {
+            drop(cache);
+            return Self::uncached(module, version, debug);
         }
-        drop(cache);
+        let digest = crypto::keccak(module).into();
+        cache.check_collision(&key, digest)?;
 
-        let engine = CompileConfig::version(version, debug).engine();
-        let module = unsafe { Module::deserialize_unchecked(&engine, module)? };
+        // if in LRU, move to ArbOS
+        let mut existing = None;
+        if let Some(mut item) = cache.lru.pop(&key) {
+            if item.digest == digest {
+                item.metadata = metadata.or(item.metadata);
+                cache.arbos.insert(key, item.clone());
+                return Ok(item.data());
+            }
+            existing = item.metadata;
+        }
+        if let Some(item) = cache.arbos.get(&key) {
+            existing = item.metadata.clone();
+        }
+
+        let size = module.len();
+        let (module, engine) = Self::deserialize(module, version, debug)?;
 
-        let item = CacheItem::new(module, engine);
+        let mut item = CacheItem::new(module, engine, size, digest);
+        item.metadata = metadata.or(existing);
         let data = item.data();
-        cache!().arbos.insert(key, item);
+        cache.arbos.insert(key, item);
         Ok(data)
     }
 
@@ -116,27 +495,368 @@ impl InitCache {
         version: u16,
         debug: bool,
     ) -> Result<(Module, Store)> {
-        let engine = CompileConfig::version(version, debug).engine();
-        let module = unsafe { Module::deserialize_unchecked(&engine, module)? };
-
         let key = CacheKey::new(module_hash, version, debug);
-        let item = CacheItem::new(module, engine);
-        cache!().lru.put(key, item.clone());
+
+        let mut cache = cache!();
+        if cache.disabled || cache.max_module_size.is_some_and(|max| module.len() > max) {
+            drop(cache);
+            return Self::uncached(module, version, debug);
+        }
+        let digest = crypto::keccak(module).into();
+        cache.check_collision(&key, digest)?;
+
+        let size = module.len();
+        let (module, engine) = Self::deserialize(module, version, debug)?;
+
+        let item = CacheItem::new(module, engine, size, digest);
+        cache.put_lru(key, item.clone(), EvictionReason::Lru);
+        Self::unlock(cache);
         Ok(item.data())
     }
 
     /// Evicts an item in the long-term cache.
     pub fn evict(module_hash: Bytes32, version: u16, debug: bool) {
         let key = CacheKey::new(module_hash, version, debug);
-        cache!().arbos.remove(&key);
+        let mut cache = cache!();
+        if cache.arbos.remove(&key).is_some() {
+            cache.evicted(key, EvictionReason::Explicit);
+        }
+        Self::unlock(cache);
+    }
+
+    /// Removes an item from both caches, so that the next use deserializes the module afresh.
+    /// Unlike `evict`, this includes the LRU cache, which is useful when cached modules may be
+    /// stale, such as in tests that swap out the compiler.
+    pub fn invalidate(module_hash: Bytes32, version: u16, debug: bool) {
+        let key = CacheKey::new(module_hash, version, debug);
+        let mut cache = cache!();
+        let arbos = cache.arbos.remove(&key).is_some();
+        let lru = cache.lru.pop(&key).is_some();
+        if arbos || lru {
+            cache.evicted(key, EvictionReason::Explicit);
+        }
+        Self::unlock(cache);
+    }
+
+    /// Evicts many items in the long-term cache, acquiring the lock only once.
+    pub fn evict_many(module_hashes: impl IntoIterator<Item = Bytes32>, version: u16, debug: bool) {
+        let mut cache = cache!();
+        for module_hash in module_hashes {
+            let key = CacheKey::new(module_hash, version, debug);
+            if cache.arbos.remove(&key).is_some() {
+                cache.evicted(key, EvictionReason::Explicit);
+            }
+        }
+        Self::unlock(cache);
+    }
+
+    /// Serializes the cache's contents into a versioned archive, for debugging validator divergence.
+    /// Items are sorted by key, so equivalent caches produce identical archives.
+    pub fn export() -> Result<Vec<u8>> {
+        Self::export_with(None)
+    }
+
+    /// Like `export`, but optionally brotli-compresses each module at the given level.
+    pub fn export_with(compression: Option<u32>) -> Result<Vec<u8>> {
+        let cache = cache!();
+        let arbos = cache
+            .arbos
+            .iter()
+            .map(|(key, item)| (*key, true, item.module.clone()));
+        let lru = cache
+            .lru
+            .iter()
+            .map(|(key, item)| (*key, false, item.module.clone()));
+        let mut items: Vec<_> = arbos.chain(lru).collect();
+        drop(cache);
+
+        items.sort_by_key(|(key, arbos, _)| (key.module_hash, key.version, key.debug, *arbos));
+
+        let mut archive = vec![ARCHIVE_VERSION];
+        archive.extend((items.len() as u32).to_be_bytes());
+        for (key, arbos, module) in items {
+            let module = module.serialize()?;
+            let module = match compression {
+                Some(level) => {
+                    brotli::compress(&module, level, DEFAULT_WINDOW_SIZE, Dictionary::Empty)
+                        .map_err(|status| eyre!("failed to compress module: {status:?}"))?
+                }
+                None => module.to_vec(),
+            };
+            archive.extend(key.module_hash);
+            archive.extend(key.version.to_be_bytes());
+            archive.push(key.debug as u8);
+            archive.push(arbos as u8);
+            archive.push(compression.is_some() as u8);
+            archive.extend((module.len() as u32).to_be_bytes());
+            archive.extend(&*module);
+        }
+        Ok(archive)
+    }
+
+    /// Adds the items of an archive produced by `export`, replacing any with the same keys.
+    /// Nothing is added unless the whole archive is valid, nor when caching is disabled.
+    pub fn import(mut archive: &[u8]) -> Result<()> {
+        fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+            if data.len() < len {
+                bail!("truncated init cache archive");
+            }
+            let (head, tail) = data.split_at(len);
+            *data = tail;
+            Ok(head)
+        }
+        macro_rules! take {
+            ($ty:ty) => {
+                <$ty>::from_be_bytes(take(&mut archive, std::mem::size_of::<$ty>())?.try_into()?)
+            };
+        }
+
+        let archive_version = take!(u8);
+        if !(1..=ARCHIVE_VERSION).contains(&archive_version) {
+            bail!("unsupported init cache archive version {archive_version}");
+        }
+        // each item takes at least 40 bytes, which bounds what an untrusted count may reserve
+        let count = take!(u32);
+        let mut items = Vec::with_capacity((count as usize).min(archive.len() / 40));
+        for _ in 0..count {
+            let module_hash = Bytes32::try_from(take(&mut archive, 32)?)?;
+            let version = take!(u16);
+            let debug = take!(u8) != 0;
+            let arbos = take!(u8) != 0;
+            let compressed = archive_version > 1 && take!(u8) != 0;
+            let len = take!(u32) as usize;
+            let module = take(&mut archive, len)?;
+            let module = match compressed {
+                true => brotli::decompress(module, Dictionary::Empty)
+                    .map_err(|status| eyre!("failed to decompress module: {status:?}"))?,
+                false => module.to_vec(),
+            };
+
+            let size = module.len();
+            let digest = crypto::keccak(&module).into();
+            let compile = CompileConfig::version(version, debug);
+            let engine = compile.engine();
+            let module = unsafe { Module::deserialize(&engine, module)? };
+            check_memory_bound(&module, &compile)?;
+            let key = CacheKey::new(module_hash, version, debug);
+            items.push((key, arbos, CacheItem::new(module, engine, size, digest)));
+        }
+        if !archive.is_empty() {
+            bail!("init cache archive has trailing data");
+        }
+
+        let mut cache = cache!();
+        if cache.disabled {
+            return Ok(());
+        }
+        for (key, arbos, item) in items {
+            match arbos {
+                true => {
+                    cache.arbos.insert(key, item);
+                }
+                false => cache.put_lru(key, item, EvictionReason::Lru),
+            }
+        }
+        Self::unlock(cache);
+        Ok(())
+    }
+
+    /// Writes the cache's contents to disk, optionally brotli-compressing each module.
+    pub fn persist(path: impl AsRef<Path>, compression: Option<u32>) -> Result<()> {
+        fs::write(path, Self::export_with(compression)?)?;
+        Ok(())
+    }
+
+    /// Adds the items persisted to disk via `persist`, decompressing as needed.
+    pub fn restore(path: impl AsRef<Path>) -> Result<()> {
+        Self::import(&fs::read(path)?)
     }
 
     /// Modifies the cache for reorg, dropping the long-term cache.
     pub fn reorg(_block: u64) {
         let mut cache = cache!();
-        let cache = &mut *cache;
-        for (key, item) in cache.arbos.drain() {
-            cache.lru.put(key, item); // not all will fit, just a heuristic
-        }
+        cache.demote_arbos(); // not all will fit, just a heuristic
+        Self::unlock(cache);
+    }
+}
+
+#[test]
+fn test_resident_bytes() -> Result<()> {
+    let engine = CompileConfig::version(1, true).engine();
+    let store = Store::new(engine.clone());
+    let module = Module::new(&store, "(module)")?;
+    let item = |size| CacheItem::new(module.clone(), engine.clone(), size, Bytes32::default());
+    let key = |byte| CacheKey::new(Bytes32([byte; 32]), 1, true);
+
+    let mut cache = InitCache::new(2);
+    assert_eq!(cache.resident_bytes(), 0);
+
+    cache.arbos.insert(key(0), item(1000));
+    cache.lru.put(key(1), item(200));
+    cache.lru.put(key(2), item(30));
+    assert_eq!(cache.resident_bytes(), 1230);
+
+    // evicted LRU items no longer count
+    cache.lru.put(key(3), item(4));
+    assert_eq!(cache.resident_bytes(), 1034);
+    Ok(())
+}
+
+#[test]
+fn test_metadata() -> Result<()> {
+    let engine = CompileConfig::version(1, true).engine();
+    let module = Module::new(&Store::new(engine), "(module)")?.serialize()?;
+    let hash = Bytes32([0xee; 32]);
+
+    // metadata round-trips, and reinserting without any keeps it
+    assert_eq!(InitCache::metadata(hash, 1, true), None);
+    InitCache::insert_with_metadata(hash, &module, 1, true, Some(&b"block 7"[..]))?;
+    assert_eq!(
+        InitCache::metadata(hash, 1, true).as_deref(),
+        Some(&b"block 7"[..])
+    );
+    InitCache::insert(hash, &module, 1, true)?;
+    assert_eq!(
+        InitCache::metadata(hash, 1, true).as_deref(),
+        Some(&b"block 7"[..])
+    );
+
+    // metadata is keyed like the item itself
+    assert_eq!(InitCache::metadata(hash, 1, false), None);
+
+    // eviction drops the metadata too
+    InitCache::evict(hash, 1, true);
+    assert_eq!(InitCache::metadata(hash, 1, true), None);
+    InitCache::insert(hash, &module, 1, true)?;
+    assert_eq!(InitCache::metadata(hash, 1, true), None);
+    InitCache::evict(hash, 1, true);
+    Ok(())
+}
+
+#[test]
+fn test_invalidate() -> Result<()> {
+    let engine = CompileConfig::version(1, true).engine();
+    let module = Module::new(&Store::new(engine), "(module)")?.serialize()?;
+    let (arbos, lru) = (Bytes32([0xaa; 32]), Bytes32([0xbb; 32]));
+
+    InitCache::insert(arbos, &module, 1, true)?;
+    InitCache::insert_lru(lru, &module, 1, true)?;
+    assert!(InitCache::contains(arbos, 1, true));
+    assert!(InitCache::contains(lru, 1, true));
+
+    // invalidation removes items from either cache, unlike evict
+    InitCache::evict(lru, 1, true);
+    assert!(InitCache::contains(lru, 1, true));
+    InitCache::invalidate(arbos, 1, true);
+    InitCache::invalidate(lru, 1, true);
+    assert!(!InitCache::contains(arbos, 1, true));
+    assert!(!InitCache::contains(lru, 1, true));
+
+    // the next insertion deserializes the module again
+    InitCache::insert(arbos, &module, 1, true)?;
+    assert!(InitCache::contains(arbos, 1, true));
+    InitCache::invalidate(arbos, 1, true);
+    Ok(())
+}
+
+#[test]
+fn test_collision_policy() -> Result<()> {
+    let engine = CompileConfig::version(1, true).engine();
+    let store = Store::new(engine);
+    let first = Module::new(&store, "(module)")?.serialize()?;
+    let second = Module::new(&store, "(module (memory 1))")?.serialize()?;
+    let hash = Bytes32([0xcc; 32]);
+
+    InitCache::set_collision_policy(CollisionPolicy::Reject);
+    InitCache::insert(hash, &first, 1, true)?;
+
+    // reinserting the same bytes is fine, but different ones are rejected from either cache
+    InitCache::insert(hash, &first, 1, true)?;
+    let err = InitCache::insert(hash, &second, 1, true).unwrap_err();
+    assert!(err.to_string().contains("collides"), "{err}");
+    assert!(InitCache::insert_lru(hash, &second, 1, true).is_err());
+    InitCache::evict(hash, 1, true);
+
+    InitCache::insert_lru(hash, &first, 1, true)?;
+    assert!(InitCache::insert(hash, &second, 1, true).is_err());
+
+    // the cached module is the original one
+    let (module, _) = InitCache::get(hash, 1, true).unwrap();
+    assert_eq!(module.info().memories.len(), 0);
+    InitCache::invalidate(hash, 1, true);
+
+    // overwriting replaces the stale bytes
+    InitCache::set_collision_policy(CollisionPolicy::Overwrite);
+    InitCache::insert(hash, &first, 1, true)?;
+    let (module, _) = InitCache::insert(hash, &second, 1, true)?;
+    assert_eq!(module.info().memories.len(), 1);
+    InitCache::evict(hash, 1, true);
+    Ok(())
+}
+
+#[test]
+fn test_eviction_hook() -> Result<()> {
+    use EvictionReason::*;
+
+    let engine = CompileConfig::version(1, true).engine();
+    let store = Store::new(engine.clone());
+    let module = Module::new(&store, "(module)")?;
+    let item = || CacheItem::new(module.clone(), engine.clone(), 0, Bytes32::default());
+    let key = |byte| CacheKey::new(Bytes32([byte; 32]), 1, true);
+
+    // without a hook, nothing is recorded
+    let mut cache = InitCache::new(2);
+    for byte in 0..4 {
+        cache.put_lru(key(byte), item(), Lru);
+    }
+    assert!(cache.evictions.is_empty());
+
+    // overflowing the LRU cache evicts the oldest items
+    cache.eviction_hook = Some(Arc::new(
+        |_: Bytes32, _: u16, _: bool, _: EvictionReason| {},
+    ));
+    for byte in 4..7 {
+        cache.put_lru(key(byte), item(), Lru);
     }
+    let evictions = mem::take(&mut cache.evictions);
+    assert!(evictions == [(key(2), Lru), (key(3), Lru), (key(4), Lru)]);
+
+    // a reorg pushes out whatever the LRU cache can't hold
+    cache.arbos.insert(key(7), item());
+    cache.arbos.insert(key(8), item());
+    cache.demote_arbos();
+    let mut evictions = mem::take(&mut cache.evictions);
+    evictions.sort_by_key(|(key, _)| key.module_hash);
+    assert!(evictions == [(key(5), Reorg), (key(6), Reorg)]);
+
+    // the global hook receives explicit evictions once the lock is released
+    let seen: Arc<Mutex<Vec<_>>> = Arc::default();
+    let record = seen.clone();
+    let hook = move |hash: Bytes32, version: u16, debug: bool, reason: EvictionReason| {
+        // the lock is released, so the hook may use the cache
+        let cached = InitCache::contains(hash, version, debug);
+        record.lock().push((hash, version, debug, reason, cached));
+    };
+    InitCache::set_eviction_hook(Some(Arc::new(hook)));
+
+    let serialized = module.serialize()?;
+    let (arbos, lru) = (Bytes32([0xdd; 32]), Bytes32([0xde; 32]));
+    InitCache::insert(arbos, &serialized, 1, true)?;
+    InitCache::insert_lru(lru, &serialized, 1, true)?;
+    InitCache::evict(arbos, 1, true);
+    InitCache::evict(arbos, 1, true);
+    InitCache::invalidate(lru, 1, true);
+    InitCache::set_eviction_hook(None);
+
+    let seen: Vec<_> = seen
+        .lock()
+        .drain(..)
+        .filter(|x| [arbos, lru].contains(&x.0))
+        .collect();
+    let expected = [
+        (arbos, 1, true, Explicit, false),
+        (lru, 1, true, Explicit, false),
+    ];
+    assert_eq!(seen, expected);
+    Ok(())
 }
@@ -1,6 +1,7 @@
 // Copyright 2022-2024, Offchain Labs, Inc.
 // For license information, see https://github.com/nitro/blob/master/LICENSE
 
+use crate::{profile::HostProfile, OutputSink};
 use arbutil::{
     evm::{
         api::{DataReader, EvmApi},
@@ -48,6 +49,20 @@ pub struct WasmEnv<D: DataReader, E: EvmApi<D>> {
     pub compile: CompileConfig,
     /// The runtime config
     pub config: Option<StylusConfig>,
+    /// Ink spent in hostios, when profiling
+    pub profile: Option<HostProfile>,
+    /// The number of logs emitted during the current call
+    pub logs_emitted: u32,
+    /// The gas charged for memory expansion during the current call
+    pub memory_gas: u64,
+    /// The number of successful, nonzero memory grows during the current call
+    pub memory_grows: u32,
+    /// The tagged values recorded via `debug_trace` during the current call
+    pub debug_traces: Vec<(String, u64)>,
+    /// The memory grows of the current call, when recording
+    pub memory_growths: Option<Vec<MemoryGrowth>>,
+    /// Consumes the return data in place of `outs`, when streaming
+    pub sink: Option<OutputSink>,
     // Using the unused generic parameter D in a PhantomData field
     _data_reader_marker: PhantomData<D>,
 }
@@ -68,6 +83,13 @@ impl<D: DataReader, E: EvmApi<D>> WasmEnv<D, E> {
             outs: vec![],
             memory: None,
             meter: None,
+            profile: None,
+            logs_emitted: 0,
+            memory_gas: 0,
+            memory_grows: 0,
+            debug_traces: vec![],
+            memory_growths: None,
+            sink: None,
             _data_reader_marker: PhantomData,
         }
     }
@@ -96,6 +118,12 @@ impl<D: DataReader, E: EvmApi<D>> WasmEnv<D, E> {
         Ok(info)
     }
 
+    /// A view of the current call's calldata, for tracers and handlers inspecting it during
+    /// hostios. Unlike `read_args`, this neither copies nor charges ink.
+    pub fn args(&self) -> &[u8] {
+        &self.args
+    }
+
     pub fn meter_mut(&mut self) -> &mut MeterData {
         self.meter.as_mut().expect("not metered")
     }
@@ -105,12 +133,23 @@ impl<D: DataReader, E: EvmApi<D>> WasmEnv<D, E> {
     }
 }
 
+/// A `memory.grow` of at least one page, as recorded in debug mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryGrowth {
+    /// The number of pages added
+    pub delta: u16,
+    /// The size of the memory once grown, in pages
+    pub total: u32,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct MeterData {
     /// The amount of ink left
     pub ink_left: NonNull<VMGlobalDefinition>,
     /// Whether the instance has run out of ink
     pub ink_status: NonNull<VMGlobalDefinition>,
+    /// The amount of stack space left
+    pub stack_left: NonNull<VMGlobalDefinition>,
 }
 
 impl MeterData {
@@ -122,6 +161,10 @@ impl MeterData {
         unsafe { self.ink_status.as_ref().val.u32 }
     }
 
+    pub fn stack(&self) -> u32 {
+        unsafe { self.stack_left.as_ref().val.u32 }
+    }
+
     pub fn set_ink(&mut self, ink: u64) {
         unsafe { self.ink_left.as_mut().val = RawValue { u64: ink } }
     }
@@ -160,8 +203,30 @@ impl<'a, D: DataReader, E: EvmApi<D>> HostioInfo<'a, D, E> {
         self.memory.ty(&self.store).minimum
     }
 
+    /// Ensures `len` bytes starting at `ptr` lie within the program's current memory.
+    pub fn check_bounds(&self, ptr: GuestPtr, len: u64) -> Result<(), MemoryAccessError> {
+        let end = u64::from(ptr.0).saturating_add(len);
+        if end > self.view().data_size() {
+            return Err(MemoryAccessError::HeapOutOfBounds);
+        }
+        Ok(())
+    }
+
+    /// Ensures a fixed-size value at `ptr` meets [`CompileDebugParams::hostio_alignment`], if set.
+    ///
+    /// [`CompileDebugParams::hostio_alignment`]: prover::programs::config::CompileDebugParams
+    pub fn check_alignment(&self, ptr: GuestPtr) -> Result<(), Escape> {
+        let align = self.env.compile.debug.hostio_alignment;
+        if align > 1 && ptr.0 % align != 0 {
+            return Err(Escape::Unaligned(ptr.0, align));
+        }
+        Ok(())
+    }
+
     // TODO: use the unstable array_assum_init
-    pub fn read_fixed<const N: usize>(&self, ptr: GuestPtr) -> Result<[u8; N], MemoryAccessError> {
+    pub fn read_fixed<const N: usize>(&self, ptr: GuestPtr) -> Result<[u8; N], Escape> {
+        self.check_alignment(ptr)?;
+        self.check_bounds(ptr, N as u64)?;
         let mut data = [MaybeUninit::uninit(); N];
         self.view().read_uninit(ptr.into(), &mut data)?;
         Ok(data.map(|x| unsafe { x.assume_init() }))
@@ -182,6 +247,14 @@ impl<'a, D: DataReader, E: EvmApi<D>> MeteredMachine for HostioInfo<'a, D, E> {
         vm.set_ink(meter.ink());
         vm.set_status(meter.status());
     }
+
+    fn set_ink_left(&mut self, ink: u64) {
+        self.env.meter_mut().set_ink(ink);
+    }
+
+    fn set_ink_status(&mut self, status: u32) {
+        self.env.meter_mut().set_status(status);
+    }
 }
 
 impl<'a, D: DataReader, E: EvmApi<D>> GasMeteredMachine for HostioInfo<'a, D, E> {
@@ -214,6 +287,8 @@ pub enum Escape {
     Internal(ErrReport),
     #[error("logic error: `{0}`")]
     Logical(ErrReport),
+    #[error("unaligned pointer: `{0:#x}` is not a multiple of {1}")]
+    Unaligned(u32, u32),
     #[error("out of ink")]
     OutOfInk,
     #[error("exit early: `{0}`")]
@@ -3,23 +3,23 @@
 
 #![allow(clippy::too_many_arguments)]
 
-use crate::env::{Escape, HostioInfo, MaybeEscape, WasmEnv, WasmEnvMut};
+use crate::env::{Escape, HostioInfo, MaybeEscape, MemoryGrowth, WasmEnv, WasmEnvMut};
 use arbutil::{
     evm::{
         api::{DataReader, EvmApi},
         EvmData,
     },
-    Color,
+    pricing, Bytes20, Bytes32, Color,
 };
 use caller_env::GuestPtr;
 use eyre::Result;
-use prover::value::Value;
+use prover::{programs::config::StylusConfig, value::Value};
 use std::{
     fmt::Display,
     mem::{self, MaybeUninit},
 };
 use user_host_trait::UserHost;
-use wasmer::{MemoryAccessError, WasmPtr};
+use wasmer::{Pages, WasmPtr};
 
 impl<'a, DR, A> UserHost<DR> for HostioInfo<'a, DR, A>
 where
@@ -27,7 +27,7 @@ where
     A: EvmApi<DR>,
 {
     type Err = Escape;
-    type MemoryErr = MemoryAccessError;
+    type MemoryErr = Escape;
     type A = A;
 
     fn args(&self) -> &[u8] {
@@ -50,6 +50,18 @@ where
         &mut self.evm_data.return_data_len
     }
 
+    fn logs_emitted(&mut self) -> &mut u32 {
+        &mut self.logs_emitted
+    }
+
+    fn memory_gas(&mut self) -> &mut u64 {
+        &mut self.memory_gas
+    }
+
+    fn config(&self) -> StylusConfig {
+        HostioInfo::config(self)
+    }
+
     fn read_fixed<const N: usize>(
         &self,
         ptr: GuestPtr,
@@ -58,6 +70,7 @@ where
     }
 
     fn read_slice(&self, ptr: GuestPtr, len: u32) -> Result<Vec<u8>, Self::MemoryErr> {
+        self.check_bounds(ptr, len.into())?;
         let len = len as usize;
         let mut data: Vec<MaybeUninit<u8>> = Vec::with_capacity(len);
         // SAFETY: read_uninit fills all available space
@@ -68,14 +81,69 @@ where
         }
     }
 
+    fn read_slice_into(
+        &self,
+        ptr: GuestPtr,
+        len: u32,
+        dest: &mut Vec<u8>,
+    ) -> Result<(), Self::MemoryErr> {
+        self.check_bounds(ptr, len.into())?;
+        dest.clear();
+        dest.resize(len as usize, 0);
+        Ok(self.view().read(ptr.into(), dest)?)
+    }
+
+    fn read_outs(&mut self, ptr: GuestPtr, len: u32) -> Result<(), Self::Err> {
+        let Some(sink) = self.sink else {
+            let mut outs = mem::take(&mut self.outs);
+            let result = self.read_slice_into(ptr, len, &mut outs);
+            self.outs = outs;
+            return Ok(result?);
+        };
+
+        // stream straight from memory, so the data is never buffered in full
+        self.check_bounds(ptr, len.into())?;
+        let chunk_size = sink.chunk_size.clamp(1, u32::MAX as usize) as u32;
+        let mut chunk = vec![];
+        let mut offset = 0;
+        loop {
+            let size = (len - offset).min(chunk_size);
+            self.read_slice_into(ptr + offset, size, &mut chunk)?;
+            if !unsafe { sink.write(&chunk, offset == 0) } {
+                return Escape::logical("output sink rejected a chunk");
+            }
+            offset += size;
+            if offset == len {
+                return Ok(());
+            }
+        }
+    }
+
     fn write_u32(&mut self, ptr: GuestPtr, x: u32) -> Result<(), Self::MemoryErr> {
+        self.check_alignment(ptr)?;
+        self.check_bounds(ptr, 4)?;
         let ptr: WasmPtr<u32> = WasmPtr::new(ptr.into());
         ptr.deref(&self.view()).write(x)?;
         Ok(())
     }
 
     fn write_slice(&self, ptr: GuestPtr, src: &[u8]) -> Result<(), Self::MemoryErr> {
-        self.view().write(ptr.into(), src)
+        self.check_bounds(ptr, src.len() as u64)?;
+        Ok(self.view().write(ptr.into(), src)?)
+    }
+
+    fn check_memory_access(&self, ptr: GuestPtr, bytes: u32) -> Result<(), Self::MemoryErr> {
+        Ok(self.check_bounds(ptr, bytes.into())?)
+    }
+
+    fn write_bytes20(&self, ptr: GuestPtr, src: Bytes20) -> Result<(), Self::MemoryErr> {
+        self.check_alignment(ptr)?;
+        self.write_slice(ptr, &src.0)
+    }
+
+    fn write_bytes32(&self, ptr: GuestPtr, src: Bytes32) -> Result<(), Self::MemoryErr> {
+        self.check_alignment(ptr)?;
+        self.write_slice(ptr, &src.0)
     }
 
     fn say<D: Display>(&self, text: D) {
@@ -90,9 +158,18 @@ where
 }
 
 macro_rules! hostio {
-    ($env:expr, $($func:tt)*) => {
-        WasmEnv::program(&mut $env)?.$($func)*
-    };
+    ($env:expr, $func:ident($($arg:expr),* $(,)?)) => {{
+        let mut info = WasmEnv::program(&mut $env)?;
+        let start_ink = info.profile.is_some().then(|| info.meter().ink());
+        let result = info.$func($($arg),*);
+        if let Some(start_ink) = start_ink {
+            let used = start_ink.saturating_sub(info.meter().ink());
+            if let Some(profile) = info.profile.as_mut() {
+                profile.charge(stringify!($func), used);
+            }
+        }
+        result
+    }};
 }
 
 pub(crate) fn read_args<D: DataReader, E: EvmApi<D>>(
@@ -118,6 +195,24 @@ pub(crate) fn exit_early<D: DataReader, E: EvmApi<D>>(
     Err(Escape::Exit(status))
 }
 
+pub(crate) fn finish_return<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    ptr: GuestPtr,
+    len: u32,
+) -> MaybeEscape {
+    hostio!(env, finish_return(ptr, len))?;
+    Err(Escape::Exit(0))
+}
+
+pub(crate) fn finish_revert<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    ptr: GuestPtr,
+    len: u32,
+) -> MaybeEscape {
+    hostio!(env, finish_revert(ptr, len))?;
+    Err(Escape::Exit(1))
+}
+
 pub(crate) fn storage_load_bytes32<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
     key: GuestPtr,
@@ -126,6 +221,15 @@ pub(crate) fn storage_load_bytes32<D: DataReader, E: EvmApi<D>>(
     hostio!(env, storage_load_bytes32(key, dest))
 }
 
+pub(crate) fn storage_load_bytes<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    key: GuestPtr,
+    dest: GuestPtr,
+    len: u32,
+) -> MaybeEscape {
+    hostio!(env, storage_load_bytes(key, dest, len))
+}
+
 pub(crate) fn storage_cache_bytes32<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
     key: GuestPtr,
@@ -157,6 +261,15 @@ pub(crate) fn transient_store_bytes32<D: DataReader, E: EvmApi<D>>(
     hostio!(env, transient_store_bytes32(key, value))
 }
 
+pub(crate) fn transient_compare_and_set<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    key: GuestPtr,
+    expected: GuestPtr,
+    value: GuestPtr,
+) -> Result<u32, Escape> {
+    hostio!(env, transient_compare_and_set(key, expected, value))
+}
+
 pub(crate) fn call_contract<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
     contract: GuestPtr,
@@ -253,6 +366,20 @@ pub(crate) fn emit_log<D: DataReader, E: EvmApi<D>>(
     hostio!(env, emit_log(data, len, topics))
 }
 
+pub(crate) fn emit_log_with_signature<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    signature: GuestPtr,
+    signature_len: u32,
+    data: GuestPtr,
+    len: u32,
+    topics: u32,
+) -> MaybeEscape {
+    hostio!(
+        env,
+        emit_log_with_signature(signature, signature_len, data, len, topics)
+    )
+}
+
 pub(crate) fn account_balance<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
     address: GuestPtr,
@@ -286,6 +413,13 @@ pub(crate) fn account_codehash<D: DataReader, E: EvmApi<D>>(
     hostio!(env, account_codehash(address, ptr))
 }
 
+pub(crate) fn account_is_contract<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    address: GuestPtr,
+) -> Result<u32, Escape> {
+    hostio!(env, account_is_contract(address))
+}
+
 pub(crate) fn block_basefee<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
     ptr: GuestPtr,
@@ -293,6 +427,19 @@ pub(crate) fn block_basefee<D: DataReader, E: EvmApi<D>>(
     hostio!(env, block_basefee(ptr))
 }
 
+pub(crate) fn block_blob_basefee<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    ptr: GuestPtr,
+) -> MaybeEscape {
+    hostio!(env, block_blob_basefee(ptr))
+}
+
+pub(crate) fn block_excess_blob_gas<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+) -> Result<u64, Escape> {
+    hostio!(env, block_excess_blob_gas())
+}
+
 pub(crate) fn block_coinbase<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
     ptr: GuestPtr,
@@ -300,6 +447,13 @@ pub(crate) fn block_coinbase<D: DataReader, E: EvmApi<D>>(
     hostio!(env, block_coinbase(ptr))
 }
 
+pub(crate) fn block_prevrandao<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    ptr: GuestPtr,
+) -> MaybeEscape {
+    hostio!(env, block_prevrandao(ptr))
+}
+
 pub(crate) fn block_gas_limit<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
 ) -> Result<u64, Escape> {
@@ -312,6 +466,18 @@ pub(crate) fn block_number<D: DataReader, E: EvmApi<D>>(
     hostio!(env, block_number())
 }
 
+pub(crate) fn arb_l1_block_number<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+) -> Result<u64, Escape> {
+    hostio!(env, arb_l1_block_number())
+}
+
+pub(crate) fn arb_l2_block_number<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+) -> Result<u64, Escape> {
+    hostio!(env, arb_l2_block_number())
+}
+
 pub(crate) fn block_timestamp<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
 ) -> Result<u64, Escape> {
@@ -324,6 +490,13 @@ pub(crate) fn chainid<D: DataReader, E: EvmApi<D>>(
     hostio!(env, chainid())
 }
 
+pub(crate) fn arb_chain_param<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    param: u32,
+) -> Result<u64, Escape> {
+    hostio!(env, arb_chain_param(param))
+}
+
 pub(crate) fn contract_address<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
     ptr: GuestPtr,
@@ -331,6 +504,21 @@ pub(crate) fn contract_address<D: DataReader, E: EvmApi<D>>(
     hostio!(env, contract_address(ptr))
 }
 
+pub(crate) fn contract_code_copy<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    offset: u32,
+    size: u32,
+    dest: GuestPtr,
+) -> Result<u32, Escape> {
+    hostio!(env, contract_code_copy(offset, size, dest))
+}
+
+pub(crate) fn contract_code_size<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+) -> Result<u32, Escape> {
+    hostio!(env, contract_code_size())
+}
+
 pub(crate) fn evm_gas_left<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
 ) -> Result<u64, Escape> {
@@ -359,6 +547,22 @@ pub(crate) fn math_mod<D: DataReader, E: EvmApi<D>>(
     hostio!(env, math_mod(value, modulus))
 }
 
+pub(crate) fn math_add<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    value: GuestPtr,
+    addend: GuestPtr,
+) -> MaybeEscape {
+    hostio!(env, math_add(value, addend))
+}
+
+pub(crate) fn math_mul<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    value: GuestPtr,
+    multiplier: GuestPtr,
+) -> MaybeEscape {
+    hostio!(env, math_mul(value, multiplier))
+}
+
 pub(crate) fn math_pow<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
     value: GuestPtr,
@@ -391,6 +595,12 @@ pub(crate) fn msg_reentrant<D: DataReader, E: EvmApi<D>>(
     hostio!(env, msg_reentrant())
 }
 
+pub(crate) fn is_static<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+) -> Result<u32, Escape> {
+    hostio!(env, is_static())
+}
+
 pub(crate) fn msg_sender<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
     ptr: GuestPtr,
@@ -414,6 +624,65 @@ pub(crate) fn native_keccak256<D: DataReader, E: EvmApi<D>>(
     hostio!(env, native_keccak256(input, len, output))
 }
 
+pub(crate) fn keccak256_pair<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    left: GuestPtr,
+    right: GuestPtr,
+    output: GuestPtr,
+) -> MaybeEscape {
+    hostio!(env, keccak256_pair(left, right, output))
+}
+
+pub(crate) fn native_sha256<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    input: GuestPtr,
+    len: u32,
+    output: GuestPtr,
+) -> MaybeEscape {
+    hostio!(env, native_sha256(input, len, output))
+}
+
+pub(crate) fn native_ripemd160<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    input: GuestPtr,
+    len: u32,
+    output: GuestPtr,
+) -> MaybeEscape {
+    hostio!(env, native_ripemd160(input, len, output))
+}
+
+pub(crate) fn native_modexp<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    base: GuestPtr,
+    base_len: u32,
+    exponent: GuestPtr,
+    exp_len: u32,
+    modulus: GuestPtr,
+    mod_len: u32,
+    output: GuestPtr,
+) -> MaybeEscape {
+    hostio!(
+        env,
+        native_modexp(base, base_len, exponent, exp_len, modulus, mod_len, output)
+    )
+}
+
+pub(crate) fn native_ecpairing<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    input: GuestPtr,
+    len: u32,
+) -> Result<u32, Escape> {
+    hostio!(env, native_ecpairing(input, len))
+}
+
+pub(crate) fn native_ecrecover<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    input: GuestPtr,
+    output: GuestPtr,
+) -> MaybeEscape {
+    hostio!(env, native_ecrecover(input, output))
+}
+
 pub(crate) fn tx_gas_price<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
     ptr: GuestPtr,
@@ -421,6 +690,13 @@ pub(crate) fn tx_gas_price<D: DataReader, E: EvmApi<D>>(
     hostio!(env, tx_gas_price(ptr))
 }
 
+pub(crate) fn effective_gas_price<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    ptr: GuestPtr,
+) -> MaybeEscape {
+    hostio!(env, effective_gas_price(ptr))
+}
+
 pub(crate) fn tx_ink_price<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
 ) -> Result<u32, Escape> {
@@ -434,11 +710,50 @@ pub(crate) fn tx_origin<D: DataReader, E: EvmApi<D>>(
     hostio!(env, tx_origin(ptr))
 }
 
+pub(crate) fn arb_l1_sender<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    ptr: GuestPtr,
+) -> MaybeEscape {
+    hostio!(env, arb_l1_sender(ptr))
+}
+
 pub(crate) fn pay_for_memory_grow<D: DataReader, E: EvmApi<D>>(
     mut env: WasmEnvMut<D, E>,
     pages: u16,
 ) -> MaybeEscape {
-    hostio!(env, pay_for_memory_grow(pages))
+    hostio!(env, pay_for_memory_grow(pages))?;
+
+    // payment precedes the grow itself, so the memory hasn't grown yet
+    if pages > 0 {
+        let mut info = WasmEnv::program(&mut env)?;
+        let total = info.memory_size().0 + u32::from(pages);
+
+        // the grow fails when it would exceed the max, leaving nothing to count or record
+        let max = info.memory.ty(&info.store).maximum;
+        if total > max.unwrap_or(Pages::max_value()).0 {
+            return Ok(());
+        }
+
+        // grows of zero pages don't reallocate, so only the others count toward the limit
+        let limit = info.config().max_memory_grows;
+        info.env.memory_grows = info.env.memory_grows.saturating_add(1);
+        if limit != 0 && info.env.memory_grows > limit {
+            return Escape::logical("too many memory grows");
+        }
+        if let Some(growths) = info.env.memory_growths.as_mut() {
+            growths.push(MemoryGrowth {
+                delta: pages,
+                total,
+            });
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn memory_gas_charged<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+) -> Result<u64, Escape> {
+    hostio!(env, memory_gas_charged())
 }
 
 pub(crate) fn console_log_text<D: DataReader, E: EvmApi<D>>(
@@ -464,3 +779,50 @@ pub(crate) fn console_tee<D: DataReader, E: EvmApi<D>, T: Into<Value> + Copy>(
 }
 
 pub(crate) fn null_host<D: DataReader, E: EvmApi<D>>(_: WasmEnvMut<D, E>) {}
+
+pub(crate) fn debug_print_i64<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    value: u64,
+) -> MaybeEscape {
+    hostio!(env, debug_print_i64(value))
+}
+
+pub(crate) fn debug_dump_memory<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    ptr: GuestPtr,
+    len: u32,
+) -> MaybeEscape {
+    hostio!(env, debug_dump_memory(ptr, len))
+}
+
+pub(crate) fn debug_breakpoint<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+) -> MaybeEscape {
+    hostio!(env, debug_breakpoint())
+}
+
+/// Records a tagged value in the env's trace buffer, which is read back after the run.
+/// Unlike the console functions, nothing is printed, so tests can assert on the entries.
+pub(crate) fn debug_trace<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    tag_ptr: GuestPtr,
+    tag_len: u32,
+    value: u64,
+) -> MaybeEscape {
+    let mut info = WasmEnv::program(&mut env)?;
+    let tag = info.read_slice(tag_ptr, tag_len)?;
+    let tag = String::from_utf8_lossy(&tag).into_owned();
+    info.env.debug_traces.push((tag, value));
+    Ok(())
+}
+
+/// Writes the remaining stack space, which lives in a global the trait can't see.
+pub(crate) fn debug_stack_left<D: DataReader, E: EvmApi<D>>(
+    mut env: WasmEnvMut<D, E>,
+    ptr: GuestPtr,
+) -> MaybeEscape {
+    let mut info = WasmEnv::start(&mut env, pricing::PTR_INK)?;
+    let stack_left = info.meter().stack();
+    info.write_u32(ptr, stack_left)?;
+    Ok(())
+}
@@ -4,20 +4,34 @@
 use arbutil::{
     evm::{
         api::DataReader,
+        replay::Recorder,
         req::EvmApiRequestor,
         user::{UserOutcome, UserOutcomeKind},
-        EvmData,
+        EvmData, StorageOp,
     },
     format::DebugBytes,
-    Bytes32,
+    pricing, Bytes32,
 };
-use cache::InitCache;
+use cache::{CompileCache, InitCache, StorePoolConfig};
 use evm_api::NativeRequestHandler;
 use eyre::ErrReport;
-use native::NativeInstance;
+use lazy_static::lazy_static;
+use lru::LruCache;
+use native::{ArtifactSizes, NativeInstance};
+use parking_lot::{Mutex, RwLock};
+use profile::{CallTimings, GasBreakdown, HostProfile};
 use prover::programs::{prelude::*, StylusData};
 use run::RunProgram;
-use std::{marker::PhantomData, mem, ptr};
+use std::{
+    alloc::{self, Layout},
+    collections::HashMap,
+    marker::PhantomData,
+    mem,
+    num::NonZeroUsize,
+    ptr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
 
 pub use brotli;
 pub use prover;
@@ -25,6 +39,7 @@ pub use prover;
 pub mod env;
 pub mod host;
 pub mod native;
+pub mod profile;
 pub mod run;
 
 mod cache;
@@ -98,12 +113,84 @@ pub struct RustBytes {
     cap: usize,
 }
 
+/// Allocates and reclaims the buffers behind `RustBytes` outputs on behalf of the host.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct BytesAllocator {
+    /// Returns a buffer of at least `len` bytes. Returning null aborts the process, as running out
+    /// of memory would with the global allocator.
+    pub alloc: unsafe extern "C" fn(ctx: usize, len: usize) -> *mut u8,
+    /// Reclaims a buffer previously returned by `alloc` for the same `len`.
+    pub free: unsafe extern "C" fn(ctx: usize, ptr: *mut u8, len: usize),
+    /// Opaque host state passed to each call.
+    pub ctx: usize,
+}
+
+/// Receives the return data of `stylus_call_streaming` in chunks, in order, as the program sets it.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct OutputSink {
+    /// Consumes a chunk, which is only valid during the call. Returning `false` fails the program.
+    /// The `restart` flag marks the first chunk of new return data, which replaces any streamed
+    /// before, and is set even when the new data is empty.
+    pub write: unsafe extern "C" fn(ctx: usize, chunk: RustSlice, restart: bool) -> bool,
+    /// Opaque host state passed to each call.
+    pub ctx: usize,
+    /// The most bytes in each chunk. Zero is treated as one.
+    pub chunk_size: usize,
+}
+
+impl OutputSink {
+    /// Feeds a chunk to the sink, returning whether it was accepted.
+    pub(crate) unsafe fn write(&self, chunk: &[u8], restart: bool) -> bool {
+        (self.write)(self.ctx, RustSlice::new(chunk), restart)
+    }
+}
+
+lazy_static! {
+    /// The host's allocator, if any. Outputs use the global allocator otherwise.
+    static ref ALLOCATOR: RwLock<Option<BytesAllocator>> = RwLock::new(None);
+
+    /// The outputs written by a host allocator, keyed by address, so each returns to its own.
+    static ref HOST_OUTPUTS: Mutex<HashMap<usize, BytesAllocator>> = Mutex::new(HashMap::default());
+
+    /// The most recent distinct module hashes activated, so that re-activations aren't counted.
+    static ref ACTIVATED: Mutex<LruCache<Bytes32, ()>> =
+        Mutex::new(LruCache::new(NonZeroUsize::new(MAX_ACTIVATED).unwrap()));
+}
+
+/// The number of recently activated module hashes remembered to deduplicate the activated count.
+const MAX_ACTIVATED: usize = 1 << 16;
+
+/// The distinct programs activated during this process's lifetime, for telemetry.
+static ACTIVATED_COUNT: AtomicU64 = AtomicU64::new(0);
+
 impl RustBytes {
     unsafe fn into_vec(self) -> Vec<u8> {
         Vec::from_raw_parts(self.ptr, self.len, self.cap)
     }
 
     unsafe fn write(&mut self, mut vec: Vec<u8>) {
+        if let Some(allocator) = *ALLOCATOR.read() {
+            let len = vec.len();
+            if len == 0 {
+                // empty outputs aren't allocated, and drop as a no-op
+                self.ptr = ptr::null_mut();
+                self.len = 0;
+                self.cap = 0;
+                return;
+            }
+            self.ptr = (allocator.alloc)(allocator.ctx, len);
+            if self.ptr.is_null() {
+                // an empty output would be mistaken for a real one, so there's no safe way back
+                alloc::handle_alloc_error(Layout::array::<u8>(len).unwrap());
+            }
+            ptr::copy_nonoverlapping(vec.as_ptr(), self.ptr, len);
+            self.len = len;
+            self.cap = len;
+            HOST_OUTPUTS.lock().insert(self.ptr as usize, allocator);
+            return;
+        }
         self.ptr = vec.as_mut_ptr();
         self.len = vec.len();
         self.cap = vec.capacity();
@@ -144,6 +231,41 @@ pub unsafe extern "C" fn stylus_activate(
     module_hash: *mut Bytes32,
     stylus_data: *mut StylusData,
     gas: *mut u64,
+) -> UserOutcomeKind {
+    stylus_activate_compressed(
+        wasm,
+        page_limit,
+        version,
+        debug,
+        false,
+        output,
+        asm_len,
+        module_hash,
+        stylus_data,
+        gas,
+    )
+}
+
+/// Like `stylus_activate`, but optionally brotli-compresses the asm portion of the `output`.
+/// The module portion is always compressed, since `Module::into_bytes` does so already.
+///
+/// Compressed asm must be passed to `stylus_call_compressed`, which inflates it when not cached.
+///
+/// # Safety
+///
+/// `output`, `asm_len`, `module_hash`, `footprint`, and `gas` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_activate_compressed(
+    wasm: GoSliceData,
+    page_limit: u16,
+    version: u16,
+    debug: bool,
+    compress_asm: bool,
+    output: *mut RustBytes,
+    asm_len: *mut usize,
+    module_hash: *mut Bytes32,
+    stylus_data: *mut StylusData,
+    gas: *mut u64,
 ) -> UserOutcomeKind {
     let wasm = wasm.slice();
     let output = &mut *output;
@@ -154,9 +276,19 @@ pub unsafe extern "C" fn stylus_activate(
         Ok(val) => val,
         Err(err) => return output.write_err(err),
     };
+    let asm = match compress_asm {
+        true => match native::compress_asm(&asm) {
+            Ok(asm) => asm,
+            Err(err) => return output.write_err(err),
+        },
+        false => asm,
+    };
     *asm_len = asm.len();
     *module_hash = module.hash();
     *stylus_data = info;
+    if ACTIVATED.lock().put(*module_hash, ()).is_none() {
+        ACTIVATED_COUNT.fetch_add(1, Ordering::Relaxed);
+    }
 
     let mut data = asm;
     data.extend(&*module.into_bytes());
@@ -164,7 +296,101 @@ pub unsafe extern "C" fn stylus_activate(
     UserOutcomeKind::Success
 }
 
+/// Compiles a user wasm ahead of time, so that activating it later skips compilation.
+/// Unlike activation, this charges no gas and has no consensus semantics.
+/// On failure, the `output` holds the error.
+///
+/// # Safety
+///
+/// `output` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_precompile(
+    wasm: GoSliceData,
+    version: u16,
+    debug: bool,
+    output: *mut RustBytes,
+) -> UserOutcomeKind {
+    util::ffi_guard(&mut *output, |output| {
+        if let Err(err) = native::precompile(wasm.slice(), version, debug) {
+            return output.write_err(err.wrap_err("failed to precompile"));
+        }
+        output.write(vec![]);
+        UserOutcomeKind::Success
+    })
+}
+
+/// Activates a user wasm like `stylus_activate`, then inserts the asm into the long term cache.
+/// This saves passing the asm back across the FFI boundary to `stylus_cache_module`.
+///
+/// # Safety
+///
+/// `output`, `asm_len`, `module_hash`, `footprint`, and `gas` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_activate_and_cache(
+    wasm: GoSliceData,
+    page_limit: u16,
+    version: u16,
+    debug: bool,
+    output: *mut RustBytes,
+    asm_len: *mut usize,
+    module_hash: *mut Bytes32,
+    stylus_data: *mut StylusData,
+    gas: *mut u64,
+) -> UserOutcomeKind {
+    let status = stylus_activate(
+        wasm,
+        page_limit,
+        version,
+        debug,
+        output,
+        asm_len,
+        module_hash,
+        stylus_data,
+        gas,
+    );
+    if status != UserOutcomeKind::Success {
+        return status;
+    }
+
+    // the asm prefixes the output
+    let asm = std::slice::from_raw_parts((*output).ptr, *asm_len);
+    if let Err(error) = InitCache::insert(*module_hash, asm, version, debug) {
+        panic!("tried to cache invalid asm!: {error}");
+    }
+    status
+}
+
+/// Activates a user wasm like `stylus_activate`, but reports only the size of the resulting asm.
+/// This is useful for fee estimation and size policies, being cheaper to transmit than the asm.
+///
+/// The `output` is written only on failure, to hold the error string.
+///
+/// # Safety
+///
+/// `output`, `asm_size`, and `gas` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_asm_size(
+    wasm: GoSliceData,
+    page_limit: u16,
+    version: u16,
+    debug: bool,
+    output: *mut RustBytes,
+    asm_size: *mut usize,
+    gas: *mut u64,
+) -> UserOutcomeKind {
+    let wasm = wasm.slice();
+    let gas = &mut *gas;
+
+    // size estimates are outside consensus, so even compiler panics become errors
+    match native::activate_guarded(wasm, version, page_limit, debug, gas) {
+        Ok((asm, ..)) => *asm_size = asm.len(),
+        Err(err) => return (*output).write_err(err),
+    }
+    UserOutcomeKind::Success
+}
+
 /// Calls an activated user program.
+/// When `debug_chain` is set, running out of ink still writes any return data set beforehand.
 ///
 /// # Safety
 ///
@@ -181,35 +407,397 @@ pub unsafe extern "C" fn stylus_call(
     output: *mut RustBytes,
     gas: *mut u64,
 ) -> UserOutcomeKind {
+    call_program(
+        module,
+        calldata,
+        config,
+        req_handler,
+        evm_data,
+        debug_chain,
+        false,
+        false,
+        &mut *output,
+        &mut *gas,
+        None,
+        None,
+        None,
+        0,
+        None,
+    )
+}
+
+/// Calls an activated user program, pre-reserving `output_hint` bytes for its return data.
+/// The hint only avoids reallocations, so a wrong hint never changes the outcome.
+///
+/// # Safety
+///
+/// `module` must represent a valid module produced from `stylus_activate`.
+/// `output` and `gas` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_call_sized(
+    module: GoSliceData,
+    calldata: GoSliceData,
+    config: StylusConfig,
+    req_handler: NativeRequestHandler,
+    evm_data: EvmData,
+    debug_chain: bool,
+    output: *mut RustBytes,
+    gas: *mut u64,
+    output_hint: usize,
+) -> UserOutcomeKind {
+    call_program(
+        module,
+        calldata,
+        config,
+        req_handler,
+        evm_data,
+        debug_chain,
+        false,
+        false,
+        &mut *output,
+        &mut *gas,
+        None,
+        None,
+        None,
+        output_hint,
+        None,
+    )
+}
+
+/// Calls an activated user program, feeding its return data to `sink` in chunks rather than
+/// writing it to `output`, which then only holds errors. The data is streamed straight from the
+/// program's memory each time it's set, so it's never buffered in full. If the sink rejects a
+/// chunk, the program fails at that point, as if it had trapped.
+///
+/// # Safety
+///
+/// `module` must represent a valid module produced from `stylus_activate`.
+/// `output` and `gas` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_call_streaming(
+    module: GoSliceData,
+    calldata: GoSliceData,
+    config: StylusConfig,
+    req_handler: NativeRequestHandler,
+    evm_data: EvmData,
+    debug_chain: bool,
+    sink: OutputSink,
+    output: *mut RustBytes,
+    gas: *mut u64,
+) -> UserOutcomeKind {
+    call_program(
+        module,
+        calldata,
+        config,
+        req_handler,
+        evm_data,
+        debug_chain,
+        false,
+        false,
+        &mut *output,
+        &mut *gas,
+        None,
+        None,
+        None,
+        0,
+        Some(sink),
+    )
+}
+
+/// Calls an activated user program whose asm was compressed by `stylus_activate_compressed`.
+///
+/// # Safety
+///
+/// `module` must represent a valid module produced from `stylus_activate_compressed`.
+/// `output` and `gas` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_call_compressed(
+    module: GoSliceData,
+    calldata: GoSliceData,
+    config: StylusConfig,
+    req_handler: NativeRequestHandler,
+    evm_data: EvmData,
+    debug_chain: bool,
+    output: *mut RustBytes,
+    gas: *mut u64,
+) -> UserOutcomeKind {
+    call_program(
+        module,
+        calldata,
+        config,
+        req_handler,
+        evm_data,
+        debug_chain,
+        true,
+        false,
+        &mut *output,
+        &mut *gas,
+        None,
+        None,
+        None,
+        0,
+        None,
+    )
+}
+
+/// Calls an activated user program, then discards all of its state changes, like `eth_call`.
+/// The output and `gas` reflect the full execution, including the cost of any writes.
+///
+/// # Safety
+///
+/// `module` must represent a valid module produced from `stylus_activate`.
+/// `output` and `gas` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_call_dry(
+    module: GoSliceData,
+    calldata: GoSliceData,
+    config: StylusConfig,
+    req_handler: NativeRequestHandler,
+    evm_data: EvmData,
+    debug_chain: bool,
+    output: *mut RustBytes,
+    gas: *mut u64,
+) -> UserOutcomeKind {
+    call_program(
+        module,
+        calldata,
+        config,
+        req_handler,
+        evm_data,
+        debug_chain,
+        false,
+        true,
+        &mut *output,
+        &mut *gas,
+        None,
+        None,
+        None,
+        0,
+        None,
+    )
+}
+
+/// Calls an activated user program, breaking down the gas used by category.
+/// The categories in `breakdown` always sum to the total gas consumed.
+///
+/// # Safety
+///
+/// `module` must represent a valid module produced from `stylus_activate`.
+/// `output`, `gas`, and `breakdown` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_call_verbose(
+    module: GoSliceData,
+    calldata: GoSliceData,
+    config: StylusConfig,
+    req_handler: NativeRequestHandler,
+    evm_data: EvmData,
+    debug_chain: bool,
+    output: *mut RustBytes,
+    gas: *mut u64,
+    breakdown: *mut GasBreakdown,
+) -> UserOutcomeKind {
+    call_program(
+        module,
+        calldata,
+        config,
+        req_handler,
+        evm_data,
+        debug_chain,
+        false,
+        false,
+        &mut *output,
+        &mut *gas,
+        Some(&mut *breakdown),
+        None,
+        None,
+        0,
+        None,
+    )
+}
+
+/// Calls an activated user program, measuring the wall-clock time spent in each phase.
+/// Timing is only enabled on debug chains, otherwise `timings` is left untouched.
+///
+/// # Safety
+///
+/// `module` must represent a valid module produced from `stylus_activate`.
+/// `output`, `gas`, and `timings` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_call_timed(
+    module: GoSliceData,
+    calldata: GoSliceData,
+    config: StylusConfig,
+    req_handler: NativeRequestHandler,
+    evm_data: EvmData,
+    debug_chain: bool,
+    output: *mut RustBytes,
+    gas: *mut u64,
+    timings: *mut CallTimings,
+) -> UserOutcomeKind {
+    call_program(
+        module,
+        calldata,
+        config,
+        req_handler,
+        evm_data,
+        debug_chain,
+        false,
+        false,
+        &mut *output,
+        &mut *gas,
+        None,
+        Some(&mut *timings),
+        None,
+        0,
+        None,
+    )
+}
+
+/// Calls an activated user program, recording each request made of Go into a replayable trace.
+/// The trace can be fed to a `Replayer` to reproduce the call offline, without any EVM state.
+/// Recording is only enabled on debug chains, otherwise `trace` is left untouched.
+///
+/// # Safety
+///
+/// `module` must represent a valid module produced from `stylus_activate`.
+/// `output`, `gas`, and `trace` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_call_recorded(
+    module: GoSliceData,
+    calldata: GoSliceData,
+    config: StylusConfig,
+    req_handler: NativeRequestHandler,
+    evm_data: EvmData,
+    debug_chain: bool,
+    output: *mut RustBytes,
+    gas: *mut u64,
+    trace: *mut RustBytes,
+) -> UserOutcomeKind {
+    call_program(
+        module,
+        calldata,
+        config,
+        req_handler,
+        evm_data,
+        debug_chain,
+        false,
+        false,
+        &mut *output,
+        &mut *gas,
+        None,
+        None,
+        Some(&mut *trace),
+        0,
+        None,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe fn call_program(
+    module: GoSliceData,
+    calldata: GoSliceData,
+    config: StylusConfig,
+    req_handler: NativeRequestHandler,
+    evm_data: EvmData,
+    debug_chain: bool,
+    compressed: bool,
+    dry_run: bool,
+    output: &mut RustBytes,
+    gas: &mut u64,
+    breakdown: Option<&mut GasBreakdown>,
+    timings: Option<&mut CallTimings>,
+    trace: Option<&mut RustBytes>,
+    output_hint: usize,
+    sink: Option<OutputSink>,
+) -> UserOutcomeKind {
+    if let Err(error) = StylusConfig::check_version(config.version) {
+        return output.write_err(error.wrap_err("call failed"));
+    }
     let module = module.slice();
     let calldata = calldata.slice().to_vec();
-    let evm_api = EvmApiRequestor::new(req_handler);
+    let trace = trace.filter(|_| debug_chain);
+    let evm_api = EvmApiRequestor::new(Recorder::new(req_handler, trace.is_some()));
     let pricing = config.pricing;
-    let output = &mut *output;
     let ink = pricing.gas_to_ink(*gas);
+    let mut timings = timings.filter(|_| debug_chain);
 
     // Safety: module came from compile_user_wasm and we've paid for memory expansion
-    let instance = unsafe {
-        NativeInstance::deserialize_cached(module, config.version, evm_api, evm_data, debug_chain)
-    };
+    let instance = CallTimings::record(timings.as_mut().map(|x| &mut x.instantiation), || unsafe {
+        NativeInstance::deserialize_cached(
+            module,
+            config.version,
+            evm_api,
+            evm_data,
+            debug_chain,
+            compressed,
+        )
+    });
     let mut instance = match instance {
         Ok(instance) => instance,
         Err(error) => util::panic_with_wasm(module, error.wrap_err("init failed")),
     };
+    if breakdown.is_some() {
+        instance.env_mut().profile = Some(HostProfile::default());
+    }
+    // the hint comes from Go unchecked, so one that can't be reserved is simply ignored
+    let _ = instance.env_mut().outs.try_reserve(output_hint);
+    instance.env_mut().sink = sink;
 
-    let status = match instance.run_main(&calldata, config, ink) {
+    let outcome = CallTimings::record(
+        timings.as_mut().map(|x| &mut x.execution),
+        || match dry_run {
+            true => instance.run_dry(&calldata, config, ink),
+            false => instance.run_main(&calldata, config, ink),
+        },
+    );
+    let status = match outcome {
         Err(e) | Ok(UserOutcome::Failure(e)) => output.write_err(e.wrap_err("call failed")),
+        Ok(UserOutcome::OutOfInk) if debug_chain => {
+            // surface any return data set before exhaustion to aid debugging
+            output.write(instance.env().outs.clone());
+            UserOutcomeKind::OutOfInk
+        }
         Ok(outcome) => output.write_outcome(outcome),
     };
     let ink_left = match status {
         UserOutcomeKind::OutOfStack => 0, // take all gas when out of stack
         _ => instance.ink_left().into(),
     };
-    *gas = pricing.ink_to_gas(ink_left);
+    let gas_left = pricing.ink_to_gas(ink_left);
+    if let Some(breakdown) = breakdown {
+        let profile = instance.env().profile.unwrap_or_default();
+        *breakdown = profile.breakdown(gas.saturating_sub(gas_left), pricing);
+    }
+    if let Some(trace) = trace {
+        let requests = instance.env_mut().evm_api.request_handler().take_trace();
+        trace.write(requests.unwrap_or_default().to_bytes());
+    }
+    instance.recycle();
+    *gas = gas_left;
     status
 }
 
-/// Caches an activated user program.
+/// The earliest Stylus version this runtime supports.
+#[no_mangle]
+pub extern "C" fn stylus_min_version() -> u16 {
+    StylusConfig::MIN_VERSION
+}
+
+/// The latest Stylus version this runtime supports.
+/// Activating or calling programs of other versions fails with an error.
+#[no_mangle]
+pub extern "C" fn stylus_max_version() -> u16 {
+    StylusConfig::MAX_VERSION
+}
+
+/// Whether an activated user program is in the init cache.
+#[no_mangle]
+pub extern "C" fn stylus_cache_contains(module_hash: Bytes32, version: u16, debug: bool) -> bool {
+    InitCache::contains(module_hash, version, debug)
+}
+
+/// Caches an activated user program, unless the init cache is disabled.
+/// Modules exceeding the max cached module size are rejected without being read.
 ///
 /// # Safety
 ///
@@ -221,17 +809,246 @@ pub unsafe extern "C" fn stylus_cache_module(
     version: u16,
     debug: bool,
 ) {
+    if InitCache::oversized(module.len) {
+        return;
+    }
     if let Err(error) = InitCache::insert(module_hash, module.slice(), version, debug) {
         panic!("tried to cache invalid asm!: {error}");
     }
 }
 
+/// Checks a `StylusConfig` for internal consistency before it's used to call a program.
+/// On failure, the first problem found is written to `output`.
+///
+/// # Safety
+///
+/// `output` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_config_validate(
+    config: StylusConfig,
+    output: *mut RustBytes,
+) -> UserOutcomeKind {
+    let output = &mut *output;
+    match config.validate() {
+        Ok(()) => UserOutcomeKind::Success,
+        Err(error) => output.write_err(error.wrap_err("invalid config")),
+    }
+}
+
+/// Writes the fixed cost of each hostio as text, one `name ink gas` line per hostio, where gas is
+/// priced according to `config`. Costs that depend on arguments or on the EVM aren't included.
+///
+/// # Safety
+///
+/// `output` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_host_cost_table(config: StylusConfig, output: *mut RustBytes) {
+    let output = &mut *output;
+    let mut table = String::new();
+    for (hostio, ink) in pricing::HOSTIO_BASE_INK {
+        let gas = config.pricing.ink_to_gas(ink);
+        table.push_str(&format!("{hostio} {ink} {gas}\n"));
+    }
+    output.write(table.into_bytes());
+}
+
+/// Computes the gas cost of a storage operation given the slot's state, as Geth charges it.
+/// See [`arbutil::evm::storage_op_cost`] for details.
+///
+/// The `op` is a [`StorageOp`] discriminant. Unknown ops cost `u64::MAX`, which no call can afford.
+#[no_mangle]
+pub extern "C" fn stylus_storage_op_cost(
+    op: u8,
+    cold: bool,
+    original: Bytes32,
+    current: Bytes32,
+    new: Bytes32,
+) -> u64 {
+    let Ok(op) = StorageOp::try_from(op) else {
+        return u64::MAX;
+    };
+    arbutil::evm::storage_op_cost(op, cold, original, current, new)
+}
+
+/// Reports whether a module produced by `stylus_activate` can be deserialized by this runtime.
+/// Unlike `stylus_cache_module`, invalid asm is reported rather than panicking.
+#[no_mangle]
+pub extern "C" fn stylus_module_valid(module: GoSliceData, version: u16) -> bool {
+    native::module_valid(module.slice(), version)
+}
+
+/// Reports whether two modules produced by `stylus_activate` describe the same program.
+/// Modules are compared by their consensus-relevant metadata rather than byte for byte.
+#[no_mangle]
+pub extern "C" fn stylus_modules_equal(a: GoSliceData, b: GoSliceData, version: u16) -> bool {
+    native::modules_equal(a.slice(), b.slice(), version)
+}
+
+/// Breaks down the size of a module produced by `stylus_activate` by the components of its
+/// compiled artifact. Returns `false`, leaving `sizes` untouched, if the asm can't be read.
+///
+/// # Safety
+///
+/// `sizes` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_artifact_sizes(
+    module: GoSliceData,
+    sizes: *mut ArtifactSizes,
+) -> bool {
+    match native::artifact_sizes(module.slice()) {
+        Ok(breakdown) => *sizes = breakdown,
+        Err(_) => return false,
+    }
+    true
+}
+
+/// Reads back the page limit and Stylus version a program was activated under.
+///
+/// # Safety
+///
+/// `stylus_data` must point to data produced by `stylus_activate`, and the outputs must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_activation_params(
+    stylus_data: *const StylusData,
+    page_limit: *mut u16,
+    version: *mut u16,
+) {
+    let stylus_data = &*stylus_data;
+    *page_limit = stylus_data.page_limit;
+    *version = stylus_data.version;
+}
+
 /// Evicts an activated user program from the init cache.
 #[no_mangle]
 pub extern "C" fn stylus_evict_module(module_hash: Bytes32, version: u16, debug: bool) {
     InitCache::evict(module_hash, version, debug);
 }
 
+/// Drops an activated user program from every cache, so that it's next compiled and deserialized
+/// afresh. Unlike `stylus_evict_module`, this includes the init cache's LRU and the compile cache.
+#[no_mangle]
+pub extern "C" fn stylus_invalidate_module(
+    wasm: GoSliceData,
+    module_hash: Bytes32,
+    version: u16,
+    debug: bool,
+) {
+    InitCache::invalidate(module_hash, version, debug);
+    CompileCache::invalidate(wasm.slice(), version, debug);
+}
+
+/// Evicts many activated user programs from the init cache at once.
+/// The `module_hashes` are a packed list of 32-byte hashes, with any trailing bytes ignored.
+#[no_mangle]
+pub extern "C" fn stylus_evict_modules(module_hashes: GoSliceData, version: u16, debug: bool) {
+    let module_hashes = module_hashes.slice().chunks_exact(32);
+    let module_hashes = module_hashes.map(|hash| Bytes32::try_from(hash).unwrap());
+    InitCache::evict_many(module_hashes, version, debug);
+}
+
+/// Returns how many distinct programs `stylus_activate` has activated during this process's
+/// lifetime. Re-activating the same program doesn't count again, unless more than `MAX_ACTIVATED`
+/// other programs have been activated since.
+#[no_mangle]
+pub extern "C" fn stylus_activated_count() -> u64 {
+    ACTIVATED_COUNT.load(Ordering::Relaxed)
+}
+
+/// Enables pooling the stores of cached programs, for tooling making many calls.
+/// Each program keeps up to `capacity` idle stores, each reused for at most `max_uses` calls.
+/// A `capacity` of 0 disables pooling, which is the default.
+#[no_mangle]
+pub extern "C" fn stylus_set_store_pool(capacity: u32, max_uses: u32) {
+    let config = (capacity > 0).then_some(StorePoolConfig {
+        capacity: capacity as usize,
+        max_uses,
+    });
+    InitCache::set_store_pool(config);
+}
+
+/// Instantiates a module produced by `stylus_activate` against stub imports, failing if that takes
+/// longer than `millis` milliseconds. For vetting programs outside of consensus, since timing
+/// isn't deterministic. On failure, the `output` holds the error.
+///
+/// # Safety
+///
+/// `output` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_check_instantiation(
+    module: GoSliceData,
+    version: u16,
+    debug: bool,
+    millis: u64,
+    output: *mut RustBytes,
+) -> UserOutcomeKind {
+    util::ffi_guard(&mut *output, |output| {
+        let compile = CompileConfig::version(version, debug);
+        let timeout = Duration::from_millis(millis);
+        if let Err(err) = native::check_instantiation(module.slice(), &compile, timeout) {
+            return output.write_err(err.wrap_err("failed to instantiate"));
+        }
+        output.write(vec![]);
+        UserOutcomeKind::Success
+    })
+}
+
+/// Disables the init cache for the rest of the process, dropping everything already cached.
+/// Afterward, caching a program does nothing and every call deserializes its module afresh.
+#[no_mangle]
+pub extern "C" fn stylus_disable_init_cache() {
+    InitCache::disable();
+}
+
+/// Bounds the size of the serialized modules the init cache accepts, in bytes, protecting its
+/// memory. Larger modules aren't cached, though calls may still use them. A `size` of 0 removes
+/// the bound, which is the default.
+#[no_mangle]
+pub extern "C" fn stylus_set_max_cached_module_size(size: u64) {
+    InitCache::set_max_module_size((size > 0).then_some(size as usize));
+}
+
+/// Returns the summed sizes of the serialized modules resident in the init cache, in bytes.
+#[no_mangle]
+pub extern "C" fn stylus_cache_size_bytes() -> u64 {
+    InitCache::size_bytes()
+}
+
+/// Exports the init cache's contents into a versioned archive, for debugging.
+///
+/// # Safety
+///
+/// `output` must not be null.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_export_init_cache(output: *mut RustBytes) -> UserOutcomeKind {
+    util::ffi_guard(&mut *output, |output| {
+        match InitCache::export() {
+            Ok(archive) => output.write(archive),
+            Err(err) => return output.write_err(err),
+        }
+        UserOutcomeKind::Success
+    })
+}
+
+/// Imports an archive produced by `stylus_export_init_cache`. On failure, the `output`
+/// holds the error and the cache is unchanged.
+///
+/// # Safety
+///
+/// `output` must not be null, and the archive must come from a trusted source.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_import_init_cache(
+    archive: GoSliceData,
+    output: *mut RustBytes,
+) -> UserOutcomeKind {
+    util::ffi_guard(&mut *output, |output| {
+        if let Err(err) = InitCache::import(archive.slice()) {
+            return output.write_err(err);
+        }
+        output.write(vec![]);
+        UserOutcomeKind::Success
+    })
+}
+
 /// Reorgs the init cache. This will likely never happen.
 #[no_mangle]
 pub extern "C" fn stylus_reorg_vm(block: u64) {
@@ -240,12 +1057,31 @@ pub extern "C" fn stylus_reorg_vm(block: u64) {
 
 /// Frees the vector. Does nothing when the vector is null.
 ///
+/// Vectors written by a host allocator are returned to it, even if another has since been installed.
+///
 /// # Safety
 ///
 /// Must only be called once per vec.
 #[no_mangle]
 pub unsafe extern "C" fn stylus_drop_vec(vec: RustBytes) {
-    if !vec.ptr.is_null() {
-        mem::drop(vec.into_vec())
+    if vec.ptr.is_null() {
+        return;
     }
+    let allocator = HOST_OUTPUTS.lock().remove(&(vec.ptr as usize));
+    match allocator {
+        Some(allocator) => (allocator.free)(allocator.ctx, vec.ptr, vec.cap),
+        None => mem::drop(vec.into_vec()),
+    }
+}
+
+/// Routes the allocation of future outputs through the host's allocator.
+/// Passing null restores the global allocator.
+///
+/// # Safety
+///
+/// `allocator` must be null or point to a valid `BytesAllocator`, which must remain able to free
+/// its outputs until they're dropped.
+#[no_mangle]
+pub unsafe extern "C" fn stylus_set_allocator(allocator: *const BytesAllocator) {
+    *ALLOCATOR.write() = allocator.as_ref().copied();
 }
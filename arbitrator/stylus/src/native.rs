@@ -2,9 +2,11 @@
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
 use crate::{
-    cache::InitCache,
+    cache::{CompileCache, InitCache, PooledStore},
     env::{MeterData, WasmEnv},
-    host, util,
+    host,
+    profile::{CallTimings, InstantiationCost},
+    util,
 };
 use arbutil::{
     evm::{
@@ -14,13 +16,16 @@ use arbutil::{
     operator::OperatorCode,
     Color,
 };
-use eyre::{bail, eyre, ErrReport, Result};
+use brotli::{Dictionary, DEFAULT_WINDOW_SIZE};
+use eyre::{bail, eyre, ErrReport, Result, WrapErr};
+use parking_lot::Mutex;
 use prover::{
+    binary::{self, DataSegment},
     machine::Module as ProverModule,
     programs::{
         config::PricingParams,
         counter::{Counter, CountingMachine, OP_OFFSETS},
-        depth::STYLUS_STACK_LEFT,
+        depth::{STYLUS_DEPTH_DEBT, STYLUS_DEPTH_POLLS, STYLUS_STACK_LEFT},
         meter::{STYLUS_INK_LEFT, STYLUS_INK_STATUS},
         prelude::*,
         start::STYLUS_START,
@@ -31,18 +36,34 @@ use std::{
     collections::BTreeMap,
     fmt::Debug,
     ops::{Deref, DerefMut},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        mpsc,
+    },
+    thread,
+    time::Duration,
 };
 use wasmer::{
-    imports, AsStoreMut, Function, FunctionEnv, Instance, Memory, Module, Pages, Store,
-    TypedFunction, Value, WasmTypeList,
+    imports, AsStoreMut, Exports, ExternType, Function, FunctionEnv, Imports, Instance, Memory,
+    Module, Pages, Store, TypedFunction, Value, WasmTypeList,
 };
+use wasmer_types::{MetadataHeader, SerializableModule};
 use wasmer_vm::VMExtern;
 
+/// The most helper threads instantiation checks may occupy at once, including those abandoned
+/// on timeout that are still running.
+const MAX_INSTANTIATION_THREADS: usize = 16;
+
+/// The helper threads instantiation checks currently occupy.
+static INSTANTIATION_THREADS: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Debug)]
 pub struct NativeInstance<D: DataReader, E: EvmApi<D>> {
     pub instance: Instance,
     pub store: Store,
     pub env: FunctionEnv<WasmEnv<D, E>>,
+    pooled: Option<PooledStore>,
 }
 
 impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
@@ -51,6 +72,7 @@ impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
             instance,
             store,
             env,
+            pooled: None,
         };
         if let Some(config) = native.env().config {
             native.set_stack(config.max_depth);
@@ -78,6 +100,16 @@ impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
         self.memory().ty(&self.store).minimum
     }
 
+    /// Records each `memory.grow` of at least one page during later calls, for memory profiling.
+    /// The current call's grows are then in the env's `memory_growths`. Only available in debug mode.
+    pub fn record_memory_growths(&mut self) -> Result<()> {
+        if !self.env().compile.debug.debug_funcs {
+            bail!("recording memory growth requires debug mode");
+        }
+        self.env_mut().memory_growths = Some(vec![]);
+        Ok(())
+    }
+
     pub fn read_slice(&self, mem: &str, ptr: usize, len: usize) -> Result<Vec<u8>> {
         let memory = self.exports.get_memory(mem)?;
         let memory = memory.view(&self.store);
@@ -100,10 +132,12 @@ impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
         let env = WasmEnv::new(compile, None, evm, evm_data);
         let store = env.compile.store();
         let module = unsafe { Module::deserialize_unchecked(&store, module)? };
+        check_memory_bound(&module, &env.compile)?;
         Self::from_module(module, store, env)
     }
 
     /// Creates a `NativeInstance` from a serialized module, or from a cached one if known.
+    /// When `compressed` is set, the module is inflated only if it's not already cached.
     ///
     /// # Safety
     ///
@@ -114,14 +148,26 @@ impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
         evm: E,
         evm_data: EvmData,
         debug: bool,
+        compressed: bool,
     ) -> Result<Self> {
         let compile = CompileConfig::version(version, debug);
         let env = WasmEnv::new(compile, None, evm, evm_data);
         let module_hash = env.evm_data.module_hash;
 
-        if let Some((module, store)) = InitCache::get(module_hash, version, debug) {
-            return Self::from_module(module, store, env);
+        if let Some((module, store, pooled)) = InitCache::get_pooled(module_hash, version, debug) {
+            check_memory_bound(&module, &env.compile)?;
+            let mut native = Self::from_module(module, store, env)?;
+            native.pooled = pooled;
+            return Ok(native);
         }
+        let inflated;
+        let module = match compressed {
+            true => {
+                inflated = decompress_asm(module)?;
+                &inflated
+            }
+            false => module,
+        };
         let (module, store) = match env.evm_data.cached {
             true => InitCache::insert(module_hash, module, version, debug)?,
             false => InitCache::insert_lru(module_hash, module, version, debug)?,
@@ -129,6 +175,43 @@ impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
         Self::from_module(module, store, env)
     }
 
+    /// Like `deserialize_cached`, but also reports the cost of instantiation alone, so that tests
+    /// can assert it stays within budget. Since timing isn't deterministic, this is for tooling.
+    ///
+    /// # Safety
+    ///
+    /// `module` must represent a valid module.
+    pub unsafe fn deserialize_cached_measured(
+        module: &[u8],
+        version: u16,
+        evm: E,
+        evm_data: EvmData,
+        debug: bool,
+        compressed: bool,
+    ) -> Result<(Self, InstantiationCost)> {
+        let cached = InitCache::contains(evm_data.module_hash, version, debug);
+        let mut nanos = 0;
+        let native = CallTimings::record(Some(&mut nanos), || unsafe {
+            Self::deserialize_cached(module, version, evm, evm_data, debug, compressed)
+        })?;
+        Ok((native, InstantiationCost::new(nanos, cached)))
+    }
+
+    /// Drops the instance, returning its store to the init cache's pool if drawn from one.
+    /// Taking `self` ensures a store is only reused once nothing refers to its old instance.
+    pub fn recycle(self) {
+        let Self {
+            instance,
+            store,
+            env,
+            pooled,
+        } = self;
+        drop((instance, env));
+        if let Some(pooled) = pooled {
+            pooled.restore(store);
+        }
+    }
+
     pub fn from_path(
         path: &str,
         evm_api: E,
@@ -143,8 +226,53 @@ impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
         Self::from_module(module, store, env)
     }
 
-    fn from_module(module: Module, mut store: Store, env: WasmEnv<D, E>) -> Result<Self> {
+    /// Like `from_path`, but first instantiates the `libraries`, given as pairs of namespaces
+    /// and paths, so that the primary module may import their exports. This is meant for
+    /// tooling rather than consensus: libraries can't import hostios and run unmetered.
+    pub fn from_path_linked(
+        path: &str,
+        libraries: &[(&str, &str)],
+        evm_api: E,
+        evm_data: EvmData,
+        compile: &CompileConfig,
+        config: StylusConfig,
+    ) -> Result<Self> {
+        let env = WasmEnv::new(compile.clone(), Some(config), evm_api, evm_data);
+        let mut store = env.compile.store();
+
+        let mut linked = vec![];
+        for (namespace, path) in libraries {
+            let module = Module::new(&store, std::fs::read(path)?)?;
+            let library = Instance::new(&mut store, &module, &Imports::new())
+                .wrap_err_with(|| format!("failed to instantiate library {}", path.red()))?;
+
+            // the library's instrumentation would otherwise trap immediately
+            let exports = &library.exports;
+            let ink = Value::I64(u64::MAX as i64);
+            let stack = Value::I32(u32::MAX as i32);
+            exports.get_global(STYLUS_INK_LEFT)?.set(&mut store, ink)?;
+            exports
+                .get_global(STYLUS_STACK_LEFT)?
+                .set(&mut store, stack)?;
+            linked.push((namespace.to_string(), library.exports));
+        }
+
+        let module = Module::new(&store, std::fs::read(path)?)?;
+        Self::from_module_linked(module, store, env, linked)
+    }
+
+    fn from_module(module: Module, store: Store, env: WasmEnv<D, E>) -> Result<Self> {
+        Self::from_module_linked(module, store, env, vec![])
+    }
+
+    fn from_module_linked(
+        module: Module,
+        mut store: Store,
+        env: WasmEnv<D, E>,
+        libraries: Vec<(String, Exports)>,
+    ) -> Result<Self> {
         let debug_funcs = env.compile.debug.debug_funcs;
+        let extra_funcs = env.compile.debug.extra_funcs;
         let func_env = FunctionEnv::new(&mut store, env);
         macro_rules! func {
             ($func:expr) => {
@@ -156,11 +284,15 @@ impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
                 "read_args" => func!(host::read_args),
                 "write_result" => func!(host::write_result),
                 "exit_early" => func!(host::exit_early),
+                "finish_return" => func!(host::finish_return),
+                "finish_revert" => func!(host::finish_revert),
                 "storage_load_bytes32" => func!(host::storage_load_bytes32),
+                "storage_load_bytes" => func!(host::storage_load_bytes),
                 "storage_cache_bytes32" => func!(host::storage_cache_bytes32),
                 "storage_flush_cache" => func!(host::storage_flush_cache),
                 "transient_load_bytes32" => func!(host::transient_load_bytes32),
                 "transient_store_bytes32" => func!(host::transient_store_bytes32),
+                "transient_compare_and_set" => func!(host::transient_compare_and_set),
                 "call_contract" => func!(host::call_contract),
                 "delegate_call_contract" => func!(host::delegate_call_contract),
                 "static_call_contract" => func!(host::static_call_contract),
@@ -169,32 +301,54 @@ impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
                 "read_return_data" => func!(host::read_return_data),
                 "return_data_size" => func!(host::return_data_size),
                 "emit_log" => func!(host::emit_log),
+                "emit_log_with_signature" => func!(host::emit_log_with_signature),
                 "account_balance" => func!(host::account_balance),
                 "account_code" => func!(host::account_code),
                 "account_codehash" => func!(host::account_codehash),
+                "account_is_contract" => func!(host::account_is_contract),
                 "account_code_size" => func!(host::account_code_size),
                 "evm_gas_left" => func!(host::evm_gas_left),
                 "evm_ink_left" => func!(host::evm_ink_left),
                 "block_basefee" => func!(host::block_basefee),
+                "block_blob_basefee" => func!(host::block_blob_basefee),
+                "block_excess_blob_gas" => func!(host::block_excess_blob_gas),
                 "chainid" => func!(host::chainid),
+                "arb_chain_param" => func!(host::arb_chain_param),
                 "block_coinbase" => func!(host::block_coinbase),
+                "block_prevrandao" => func!(host::block_prevrandao),
                 "block_gas_limit" => func!(host::block_gas_limit),
                 "block_number" => func!(host::block_number),
+                "arb_l1_block_number" => func!(host::arb_l1_block_number),
+                "arb_l2_block_number" => func!(host::arb_l2_block_number),
                 "block_timestamp" => func!(host::block_timestamp),
                 "contract_address" => func!(host::contract_address),
+                "contract_code_copy" => func!(host::contract_code_copy),
+                "contract_code_size" => func!(host::contract_code_size),
                 "math_div" => func!(host::math_div),
                 "math_mod" => func!(host::math_mod),
+                "math_add" => func!(host::math_add),
+                "math_mul" => func!(host::math_mul),
                 "math_pow" => func!(host::math_pow),
                 "math_add_mod" => func!(host::math_add_mod),
                 "math_mul_mod" => func!(host::math_mul_mod),
                 "msg_reentrant" => func!(host::msg_reentrant),
+                "is_static" => func!(host::is_static),
                 "msg_sender" => func!(host::msg_sender),
                 "msg_value" => func!(host::msg_value),
                 "tx_gas_price" => func!(host::tx_gas_price),
+                "effective_gas_price" => func!(host::effective_gas_price),
                 "tx_ink_price" => func!(host::tx_ink_price),
                 "tx_origin" => func!(host::tx_origin),
+                "arb_l1_sender" => func!(host::arb_l1_sender),
                 "pay_for_memory_grow" => func!(host::pay_for_memory_grow),
+                "memory_gas_charged" => func!(host::memory_gas_charged),
                 "native_keccak256" => func!(host::native_keccak256),
+                "keccak256_pair" => func!(host::keccak256_pair),
+                "native_sha256" => func!(host::native_sha256),
+                "native_ripemd160" => func!(host::native_ripemd160),
+                "native_modexp" => func!(host::native_modexp),
+                "native_ecpairing" => func!(host::native_ecpairing),
+                "native_ecrecover" => func!(host::native_ecrecover),
             },
         };
         if debug_funcs {
@@ -208,6 +362,18 @@ impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
             imports.define("console", "tee_f32", func!(host::console_tee::<D, E, f32>));
             imports.define("console", "tee_f64", func!(host::console_tee::<D, E, f64>));
             imports.define("debug", "null_host", func!(host::null_host));
+            imports.define("debug", "trace", func!(host::debug_trace));
+            if extra_funcs {
+                imports.define("debug", "print_i64", func!(host::debug_print_i64));
+                imports.define("debug", "dump_memory", func!(host::debug_dump_memory));
+                imports.define("debug", "breakpoint", func!(host::debug_breakpoint));
+                imports.define("debug", "stack_left", func!(host::debug_stack_left));
+            }
+        }
+        for (namespace, exports) in libraries {
+            for (name, export) in exports.iter() {
+                imports.define(&namespace, name, export.clone());
+            }
         }
         let instance = Instance::new(&mut store, &module, &imports)?;
         let exports = &instance.exports;
@@ -233,10 +399,12 @@ impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
         };
         let ink_left = expect_global(STYLUS_INK_LEFT);
         let ink_status = expect_global(STYLUS_INK_STATUS);
+        let stack_left = expect_global(STYLUS_STACK_LEFT);
 
         self.env_mut().meter = Some(MeterData {
             ink_left,
             ink_status,
+            stack_left,
         });
     }
 
@@ -273,6 +441,29 @@ impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
         self.set_ink(ink);
         Ok(func.call(&mut self.store)?)
     }
+
+    /// Calls an arbitrary export by name, bypassing the entrypoint's calling convention.
+    /// The call is metered and depth-checked just like `run_main`. This is meant for tooling.
+    pub fn run_export(&mut self, name: &str, args: &[Value], ink: u64) -> Result<Vec<Value>> {
+        self.set_ink(ink);
+        if let Some(config) = self.env().config {
+            self.set_stack(config.max_depth);
+        }
+
+        let func = self.instance.exports.get_function(name)?.clone();
+        match func.call(&mut self.store, args) {
+            Ok(values) => Ok(values.into_vec()),
+            Err(error) => {
+                if self.stack_left() == 0 {
+                    bail!("out of stack calling {}", name.red());
+                }
+                if self.ink_left() == MachineMeter::Exhausted {
+                    bail!("out of ink calling {}", name.red());
+                }
+                Err(eyre!(error).wrap_err(format!("failed to call {}", name.red())))
+            }
+        }
+    }
 }
 
 impl<D: DataReader, E: EvmApi<D>> Deref for NativeInstance<D, E> {
@@ -303,6 +494,14 @@ impl<D: DataReader, E: EvmApi<D>> MeteredMachine for NativeInstance<D, E> {
         vm.set_ink(meter.ink());
         vm.set_status(meter.status());
     }
+
+    fn set_ink_left(&mut self, ink: u64) {
+        self.env_mut().meter_mut().set_ink(ink);
+    }
+
+    fn set_ink_status(&mut self, status: u32) {
+        self.env_mut().meter_mut().set_status(status);
+    }
 }
 
 impl<D: DataReader, E: EvmApi<D>> GasMeteredMachine for NativeInstance<D, E> {
@@ -323,6 +522,13 @@ impl<D: DataReader, E: EvmApi<D>> CountingMachine for NativeInstance<D, E> {
         }
         Ok(counts)
     }
+
+    fn operator_count(&mut self, op: OperatorCode) -> Result<u64> {
+        let Some(&offset) = OP_OFFSETS.lock().get(&op) else {
+            return Ok(0);
+        };
+        self.get_global(&Counter::global_name(offset))
+    }
 }
 
 impl<D: DataReader, E: EvmApi<D>> DepthCheckedMachine for NativeInstance<D, E> {
@@ -331,7 +537,12 @@ impl<D: DataReader, E: EvmApi<D>> DepthCheckedMachine for NativeInstance<D, E> {
     }
 
     fn set_stack(&mut self, size: u32) {
-        self.set_global(STYLUS_STACK_LEFT, size).unwrap()
+        self.set_global(STYLUS_STACK_LEFT, size).unwrap();
+
+        // sparse checks restart their countdown, with nothing owed
+        if self.set_global(STYLUS_DEPTH_POLLS, 1_u32).is_ok() {
+            self.set_global(STYLUS_DEPTH_DEBT, 0_u32).unwrap();
+        }
     }
 }
 
@@ -346,26 +557,48 @@ impl<D: DataReader, E: EvmApi<D>> StartlessMachine for NativeInstance<D, E> {
 }
 
 pub fn module(wasm: &[u8], compile: CompileConfig) -> Result<Vec<u8>> {
+    let module = compile_module(wasm, compile)?.serialize()?;
+    Ok(module.to_vec())
+}
+
+/// Compiles a user wasm, returning the module prior to serialization so that tooling may inspect
+/// the globals and exports injected by instrumentation. Since wasmer instruments the module while
+/// compiling it, there's no intermediate wasm to return. Requires a debug config.
+pub fn instrumented(wasm: &[u8], compile: CompileConfig) -> Result<Module> {
+    if !compile.debug.debug_funcs {
+        bail!("inspecting instrumentation requires a debug config");
+    }
+    compile_module(wasm, compile)
+}
+
+fn compile_module(wasm: &[u8], compile: CompileConfig) -> Result<Module> {
     let mut store = compile.store();
     let module = Module::new(&store, wasm)?;
+    let imports = stub_imports(&mut store, &compile);
+    Instance::new(&mut store, &module, &imports)?;
+    Ok(module)
+}
+
+/// Imports every hostio the config allows as a stub that panics if called.
+fn stub_imports(store: &mut Store, compile: &CompileConfig) -> Imports {
     macro_rules! stub {
         (u8 <- $($types:tt)+) => {
-            Function::new_typed(&mut store, $($types)+ -> u8 { panic!("incomplete import") })
+            Function::new_typed(&mut *store, $($types)+ -> u8 { panic!("incomplete import") })
         };
         (u32 <- $($types:tt)+) => {
-            Function::new_typed(&mut store, $($types)+ -> u32 { panic!("incomplete import") })
+            Function::new_typed(&mut *store, $($types)+ -> u32 { panic!("incomplete import") })
         };
         (u64 <- $($types:tt)+) => {
-            Function::new_typed(&mut store, $($types)+ -> u64 { panic!("incomplete import") })
+            Function::new_typed(&mut *store, $($types)+ -> u64 { panic!("incomplete import") })
         };
         (f32 <- $($types:tt)+) => {
-            Function::new_typed(&mut store, $($types)+ -> f32 { panic!("incomplete import") })
+            Function::new_typed(&mut *store, $($types)+ -> f32 { panic!("incomplete import") })
         };
         (f64 <- $($types:tt)+) => {
-            Function::new_typed(&mut store, $($types)+ -> f64 { panic!("incomplete import") })
+            Function::new_typed(&mut *store, $($types)+ -> f64 { panic!("incomplete import") })
         };
         ($($types:tt)+) => {
-            Function::new_typed(&mut store, $($types)+ panic!("incomplete import"))
+            Function::new_typed(&mut *store, $($types)+ panic!("incomplete import"))
         };
     }
     let mut imports = imports! {
@@ -373,11 +606,15 @@ pub fn module(wasm: &[u8], compile: CompileConfig) -> Result<Vec<u8>> {
             "read_args" => stub!(|_: u32|),
             "write_result" => stub!(|_: u32, _: u32|),
             "exit_early" => stub!(|_: u32|),
+            "finish_return" => stub!(|_: u32, _: u32|),
+            "finish_revert" => stub!(|_: u32, _: u32|),
             "storage_load_bytes32" => stub!(|_: u32, _: u32|),
+            "storage_load_bytes" => stub!(|_: u32, _: u32, _: u32|),
             "storage_cache_bytes32" => stub!(|_: u32, _: u32|),
             "storage_flush_cache" => stub!(|_: u32|),
             "transient_load_bytes32" => stub!(|_: u32, _: u32|),
             "transient_store_bytes32" => stub!(|_: u32, _: u32|),
+            "transient_compare_and_set" => stub!(u32 <- |_: u32, _: u32, _: u32|),
             "call_contract" => stub!(u8 <- |_: u32, _: u32, _: u32, _: u32, _: u64, _: u32|),
             "delegate_call_contract" => stub!(u8 <- |_: u32, _: u32, _: u32, _: u64, _: u32|),
             "static_call_contract" => stub!(u8 <- |_: u32, _: u32, _: u32, _: u64, _: u32|),
@@ -386,32 +623,54 @@ pub fn module(wasm: &[u8], compile: CompileConfig) -> Result<Vec<u8>> {
             "read_return_data" => stub!(u32 <- |_: u32, _: u32, _: u32|),
             "return_data_size" => stub!(u32 <- ||),
             "emit_log" => stub!(|_: u32, _: u32, _: u32|),
+            "emit_log_with_signature" => stub!(|_: u32, _: u32, _: u32, _: u32, _: u32|),
             "account_balance" => stub!(|_: u32, _: u32|),
             "account_code" => stub!(u32 <- |_: u32, _: u32, _: u32, _: u32|),
             "account_codehash" => stub!(|_: u32, _: u32|),
+            "account_is_contract" => stub!(u32 <- |_: u32|),
             "account_code_size" => stub!(u32 <- |_: u32|),
             "evm_gas_left" => stub!(u64 <- ||),
             "evm_ink_left" => stub!(u64 <- ||),
             "block_basefee" => stub!(|_: u32|),
+            "block_blob_basefee" => stub!(|_: u32|),
+            "block_excess_blob_gas" => stub!(u64 <- ||),
             "chainid" => stub!(u64 <- ||),
+            "arb_chain_param" => stub!(u64 <- |_: u32|),
             "block_coinbase" => stub!(|_: u32|),
+            "block_prevrandao" => stub!(|_: u32|),
             "block_gas_limit" => stub!(u64 <- ||),
             "block_number" => stub!(u64 <- ||),
+            "arb_l1_block_number" => stub!(u64 <- ||),
+            "arb_l2_block_number" => stub!(u64 <- ||),
             "block_timestamp" => stub!(u64 <- ||),
             "contract_address" => stub!(|_: u32|),
+            "contract_code_copy" => stub!(u32 <- |_: u32, _: u32, _: u32|),
+            "contract_code_size" => stub!(u32 <- ||),
             "math_div" => stub!(|_: u32, _: u32|),
             "math_mod" => stub!(|_: u32, _: u32|),
+            "math_add" => stub!(|_: u32, _: u32|),
+            "math_mul" => stub!(|_: u32, _: u32|),
             "math_pow" => stub!(|_: u32, _: u32|),
             "math_add_mod" => stub!(|_: u32, _: u32, _: u32|),
             "math_mul_mod" => stub!(|_: u32, _: u32, _: u32|),
             "msg_reentrant" => stub!(u32 <- ||),
+            "is_static" => stub!(u32 <- ||),
             "msg_sender" => stub!(|_: u32|),
             "msg_value" => stub!(|_: u32|),
             "tx_gas_price" => stub!(|_: u32|),
+            "effective_gas_price" => stub!(|_: u32|),
             "tx_ink_price" => stub!(u32 <- ||),
             "tx_origin" => stub!(|_: u32|),
+            "arb_l1_sender" => stub!(|_: u32|),
             "pay_for_memory_grow" => stub!(|_: u16|),
+            "memory_gas_charged" => stub!(u64 <- ||),
             "native_keccak256" => stub!(|_: u32, _: u32, _: u32|),
+            "keccak256_pair" => stub!(|_: u32, _: u32, _: u32|),
+            "native_sha256" => stub!(|_: u32, _: u32, _: u32|),
+            "native_ripemd160" => stub!(|_: u32, _: u32, _: u32|),
+            "native_modexp" => stub!(|_: u32, _: u32, _: u32, _: u32, _: u32, _: u32, _: u32|),
+            "native_ecpairing" => stub!(u32 <- |_: u32, _: u32|),
+            "native_ecrecover" => stub!(|_: u32, _: u32|),
         },
     };
     if compile.debug.debug_funcs {
@@ -425,11 +684,63 @@ pub fn module(wasm: &[u8], compile: CompileConfig) -> Result<Vec<u8>> {
         imports.define("console", "tee_f32", stub!(f32 <- |_: f32|));
         imports.define("console", "tee_f64", stub!(f64 <- |_: f64|));
         imports.define("debug", "null_host", stub!(||));
+        imports.define("debug", "trace", stub!(|_: u32, _: u32, _: u64|));
+        if compile.debug.extra_funcs {
+            imports.define("debug", "print_i64", stub!(|_: u64|));
+            imports.define("debug", "dump_memory", stub!(|_: u32, _: u32|));
+            imports.define("debug", "breakpoint", stub!(||));
+            imports.define("debug", "stack_left", stub!(|_: u32|));
+        }
     }
-    Instance::new(&mut store, &module, &imports)?;
+    imports
+}
 
-    let module = module.serialize()?;
-    Ok(module.to_vec())
+/// Checks that a deserialized module's memory fits within the config's heap bound, which may be
+/// tighter than the one it was compiled with. Otherwise, instantiation would fail less clearly.
+pub(crate) fn check_memory_bound(module: &Module, compile: &CompileConfig) -> Result<()> {
+    let limit = compile.bounds.heap_bound;
+    for memory in module.info().memories.values() {
+        if memory.minimum > limit {
+            let pages = memory.minimum.0;
+            bail!(
+                "module's memory of {} pages exceeds the {}-page limit",
+                pages.red(),
+                limit.0.red()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Instantiates serialized asm against stub imports, failing if that takes longer than `timeout`.
+/// This vets programs outside of consensus, protecting validators from modules that pass
+/// validation yet are slow to instantiate. Since timing isn't deterministic, calls never use it.
+///
+/// Instantiation can't be interrupted, so it happens on a helper thread that's abandoned on
+/// timeout. That thread holds only its own store and stubs, never a program's env or EVM API.
+/// At most `MAX_INSTANTIATION_THREADS` run at once, so stalled instantiations can't pile up:
+/// once they're all busy, checks fail immediately.
+pub fn check_instantiation(asm: &[u8], compile: &CompileConfig, timeout: Duration) -> Result<()> {
+    let mut store = compile.store();
+    let module = unsafe { Module::deserialize(&store, asm)? };
+    let imports = stub_imports(&mut store, compile);
+
+    let threads = &INSTANTIATION_THREADS;
+    let reserve = |busy| (busy < MAX_INSTANTIATION_THREADS).then_some(busy + 1);
+    if threads.fetch_update(SeqCst, SeqCst, reserve).is_err() {
+        bail!("too many instantiations in progress");
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let result = Instance::new(&mut store, &module, &imports).map(|_| ());
+        threads.fetch_sub(1, SeqCst);
+        let _ = sender.send(result);
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => Ok(result?),
+        Err(_) => bail!("instantiation exceeded its {timeout:?} timeout"),
+    }
 }
 
 pub fn activate(
@@ -439,12 +750,250 @@ pub fn activate(
     debug: bool,
     gas: &mut u64,
 ) -> Result<(Vec<u8>, ProverModule, StylusData)> {
+    StylusConfig::check_version(version)?;
     let compile = CompileConfig::version(version, debug);
     let (module, stylus_data) = ProverModule::activate(wasm, version, page_limit, debug, gas)?;
 
+    if let Some(asm) = CompileCache::get(wasm, version, debug) {
+        return Ok((asm, module, stylus_data));
+    }
     let asm = match self::module(wasm, compile) {
         Ok(asm) => asm,
         Err(err) => util::panic_with_wasm(wasm, err),
     };
     Ok((asm, module, stylus_data))
 }
+
+/// Compiles a wasm ahead of time, caching the asm so that activating the same wasm later skips
+/// compilation. Unlike activation, this is a maintenance operation without consensus semantics:
+/// no gas is charged and nothing is produced, and activation's output is the same either way.
+pub fn precompile(wasm: &[u8], version: u16, debug: bool) -> Result<()> {
+    StylusConfig::check_version(version)?;
+    if CompileCache::contains(wasm, version, debug) {
+        return Ok(());
+    }
+    let compile = CompileConfig::version(version, debug);
+    let asm = util::catch_panic("compilation", || self::module(wasm, compile))?;
+    CompileCache::insert(wasm, &asm, version, debug);
+    Ok(())
+}
+
+/// Like `activate`, but converts panics into errors, which is useful when activating untrusted
+/// wasm outside of consensus. Note that `stylus_activate` deliberately lets compiler panics
+/// happen, since a program the prover accepts but the native compiler rejects is fatal.
+pub fn activate_guarded(
+    wasm: &[u8],
+    version: u16,
+    page_limit: u16,
+    debug: bool,
+    gas: &mut u64,
+) -> Result<(Vec<u8>, ProverModule, StylusData)> {
+    util::catch_panic("activation", || {
+        activate(wasm, version, page_limit, debug, gas)
+    })
+}
+
+/// Like `activate`, but first rejects programs that provably cost more than the supplied gas,
+/// before doing the expensive validation and instrumentation.
+/// Since the check is conservative, a program that would activate is never rejected.
+/// Gas is only charged if activation proceeds.
+pub fn activate_bounded(
+    wasm: &[u8],
+    version: u16,
+    page_limit: u16,
+    debug: bool,
+    gas: &mut u64,
+) -> Result<(Vec<u8>, ProverModule, StylusData)> {
+    let floor = ProverModule::activation_gas_floor(wasm).wrap_err("failed to parse wasm")?;
+    if floor > *gas {
+        bail!(
+            "activation requires at least {} gas but only {} was supplied",
+            floor.red(),
+            gas.red()
+        );
+    }
+    activate(wasm, version, page_limit, debug, gas)
+}
+
+/// Activates many programs across up to `threads` threads, for bulk onboarding outside of consensus.
+/// Each program is charged against its own entry in `gas`, exactly as if activated via `activate`,
+/// and results are returned in the order of `wasms`.
+pub fn batch_activate(
+    wasms: &[&[u8]],
+    version: u16,
+    page_limit: u16,
+    debug: bool,
+    gas: &mut [u64],
+    threads: usize,
+) -> Vec<Result<(Vec<u8>, ProverModule, StylusData)>> {
+    assert_eq!(wasms.len(), gas.len(), "each wasm needs its own gas");
+    let threads = threads.clamp(1, wasms.len().max(1));
+    let jobs = Mutex::new(wasms.iter().zip(gas.iter_mut()).enumerate());
+
+    let mut results: Vec<_> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = vec![];
+                    loop {
+                        let job = jobs.lock().next();
+                        let Some((index, (wasm, gas))) = job else {
+                            return done;
+                        };
+                        done.push((index, activate(wasm, version, page_limit, debug, gas)));
+                    }
+                })
+            })
+            .collect();
+
+        let mut results = vec![];
+        for worker in workers {
+            match worker.join() {
+                Ok(done) => results.extend(done),
+                Err(panic) => std::panic::resume_unwind(panic),
+            }
+        }
+        results
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Reads a program's data segments without instrumenting or running it, for static analysis.
+pub fn data_segments(wasm: &[u8]) -> Result<Vec<DataSegment>> {
+    let bin = binary::parse(wasm, Path::new("user"))?;
+    bin.data_segments()
+}
+
+/// Brotli-compresses an activated program's asm for storage or transport.
+pub fn compress_asm(asm: &[u8]) -> Result<Vec<u8>> {
+    brotli::compress(asm, 0, DEFAULT_WINDOW_SIZE, Dictionary::Empty)
+        .map_err(|status| eyre!("failed to compress asm: {status:?}"))
+}
+
+/// Checks whether serialized asm can be deserialized by this runtime, without caching it.
+pub fn module_valid(module: &[u8], version: u16) -> bool {
+    let engine = CompileConfig::version(version, false).engine();
+    unsafe { Module::deserialize(&engine, module).is_ok() }
+}
+
+/// Checks whether two serialized asms describe the same program by comparing what affects
+/// execution: the memory footprint, the instrumented globals, the module's interface, and the
+/// machine code of its functions. Unlike a byte comparison, this ignores non-semantic details
+/// of serialization. Asm that can't be deserialized by this runtime is never equal to anything.
+pub fn modules_equal(a: &[u8], b: &[u8], version: u16) -> bool {
+    let engine = CompileConfig::version(version, false).engine();
+    let deserialize = |asm| unsafe { Module::deserialize(&engine, asm) };
+    let (Ok(info_a), Ok(info_b)) = (deserialize(a), deserialize(b)) else {
+        return false;
+    };
+    let (info_a, info_b) = (info_a.info(), info_b.info());
+    let info_equal = info_a.memories == info_b.memories
+        && info_a.globals == info_b.globals
+        && info_a.global_initializers == info_b.global_initializers
+        && info_a.exports == info_b.exports
+        && info_a.imports == info_b.imports
+        && info_a.signatures == info_b.signatures
+        && info_a.functions == info_b.functions
+        && info_a.start_function == info_b.start_function;
+
+    let (Ok(a), Ok(b)) = (serializable_module(a), serializable_module(b)) else {
+        return false;
+    };
+    let (a, b) = (
+        &a.compilation.function_bodies,
+        &b.compilation.function_bodies,
+    );
+    info_equal && a.values().map(|x| &x.body).eq(b.values().map(|x| &x.body))
+}
+
+/// The sizes of the components of an activated module's compiled artifact, in bytes.
+/// Each component is measured on its own, so the fields sum to a little less than the size of the
+/// serialized module, which also frames and pads them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct ArtifactSizes {
+    /// Machine code, including trampolines and custom sections like those for libcalls.
+    pub code: u64,
+    /// Relocations applied to the code when the module is loaded, as serialized.
+    pub relocations: u64,
+    /// Everything else, such as the module's info, frame info, and data initializers, as serialized.
+    pub metadata: u64,
+}
+
+impl ArtifactSizes {
+    pub fn total(&self) -> u64 {
+        self.code + self.relocations + self.metadata
+    }
+}
+
+/// Breaks down the size of an activated module by the components of its compiled artifact,
+/// which helps operators understand the memory footprint of each module.
+pub fn artifact_sizes(asm: &[u8]) -> Result<ArtifactSizes> {
+    let mut module = serializable_module(asm)?;
+    let compilation = &mut module.compilation;
+
+    let bodies = compilation.function_bodies.values();
+    let trampolines = compilation.function_call_trampolines.values();
+    let dynamic = compilation.dynamic_function_trampolines.values();
+    let bodies = bodies.chain(trampolines).chain(dynamic);
+    let sections = compilation.custom_sections.values();
+    let code = bodies.map(|x| x.body.len()).sum::<usize>()
+        + sections.map(|x| x.bytes.len()).sum::<usize>();
+
+    // serialize the module again without its code, then without its relocations too
+    compilation.function_bodies.clear();
+    compilation.function_call_trampolines.clear();
+    compilation.dynamic_function_trampolines.clear();
+    compilation.custom_sections.clear();
+    let without_code = module.serialize()?.len();
+
+    let compilation = &mut module.compilation;
+    compilation.function_relocations.clear();
+    compilation.custom_section_relocations.clear();
+    let metadata = module.serialize()?.len();
+
+    Ok(ArtifactSizes {
+        code: code as u64,
+        relocations: (without_code - metadata) as u64,
+        metadata: metadata as u64,
+    })
+}
+
+/// Reads the compilation artifacts of an activated module without loading it into an engine.
+fn serializable_module(asm: &[u8]) -> Result<SerializableModule> {
+    // the header of a serialized artifact (see wasmer's ArtifactBuild::MAGIC_HEADER)
+    const MAGIC_HEADER: &[u8] = b"wasmer-universal";
+
+    let Some(data) = asm.strip_prefix(MAGIC_HEADER) else {
+        bail!("asm isn't a serialized artifact");
+    };
+    let len = MetadataHeader::parse(data)?;
+    let Some(data) = data[MetadataHeader::LEN..].get(..len) else {
+        bail!("truncated artifact");
+    };
+    Ok(unsafe { SerializableModule::deserialize(data)? })
+}
+
+/// Lists the names of the globals instrumentation injected into an activated module, sorted.
+/// Consensus depends on these, like `STYLUS_INK_LEFT` and `STYLUS_STACK_LEFT`, so verifiers can
+/// use this to confirm none are missing. Injected globals are exactly the exported ones with a
+/// `stylus_` prefix, since parsing reserves it and rejects user exports that use it.
+pub fn injected_globals(module: &[u8], version: u16) -> Result<Vec<String>> {
+    let engine = CompileConfig::version(version, false).engine();
+    let module = unsafe { Module::deserialize(&engine, module)? };
+    let mut names: Vec<_> = module
+        .exports()
+        .filter(|export| matches!(export.ty(), ExternType::Global(_)))
+        .map(|export| export.name().to_owned())
+        .filter(|name| name.starts_with("stylus_"))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Inflates asm compressed via `compress_asm`.
+pub fn decompress_asm(asm: &[u8]) -> Result<Vec<u8>> {
+    brotli::decompress(asm, Dictionary::Empty)
+        .map_err(|status| eyre!("failed to decompress asm: {status:?}"))
+}
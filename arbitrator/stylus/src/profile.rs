@@ -0,0 +1,211 @@
+// Copyright 2024, Offchain Labs, Inc.
+// For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+use prover::programs::config::PricingParams;
+use std::time::Instant;
+
+/// The kinds of work a hostio's ink may be attributed to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HostCategory {
+    Storage,
+    Calls,
+    Logs,
+    Other,
+}
+
+impl HostCategory {
+    /// Classifies a hostio by name, attributing unknown ones, like the debug imports, to `Other`.
+    pub fn of(hostio: &str) -> Self {
+        Self::classify(hostio).unwrap_or(Self::Other)
+    }
+
+    /// Classifies a hostio by name, or returns `None` if it isn't one of the `vm_hooks`.
+    /// Every hostio is listed, so that adding one without categorizing it fails the tests.
+    pub fn classify(hostio: &str) -> Option<Self> {
+        Some(match hostio {
+            "storage_load_bytes32"
+            | "storage_load_bytes"
+            | "storage_cache_bytes32"
+            | "storage_flush_cache"
+            | "transient_load_bytes32"
+            | "transient_store_bytes32"
+            | "transient_compare_and_set" => Self::Storage,
+            "call_contract"
+            | "delegate_call_contract"
+            | "static_call_contract"
+            | "create1"
+            | "create2" => Self::Calls,
+            "emit_log" | "emit_log_with_signature" => Self::Logs,
+            "read_args"
+            | "write_result"
+            | "exit_early"
+            | "finish_return"
+            | "finish_revert"
+            | "read_return_data"
+            | "return_data_size"
+            | "account_balance"
+            | "account_code"
+            | "account_codehash"
+            | "account_is_contract"
+            | "account_code_size"
+            | "evm_gas_left"
+            | "evm_ink_left"
+            | "block_basefee"
+            | "block_blob_basefee"
+            | "block_excess_blob_gas"
+            | "chainid"
+            | "arb_chain_param"
+            | "block_coinbase"
+            | "block_prevrandao"
+            | "block_gas_limit"
+            | "block_number"
+            | "arb_l1_block_number"
+            | "arb_l2_block_number"
+            | "block_timestamp"
+            | "contract_address"
+            | "contract_code_copy"
+            | "contract_code_size"
+            | "math_div"
+            | "math_mod"
+            | "math_add"
+            | "math_mul"
+            | "math_pow"
+            | "math_add_mod"
+            | "math_mul_mod"
+            | "msg_reentrant"
+            | "is_static"
+            | "msg_sender"
+            | "msg_value"
+            | "tx_gas_price"
+            | "effective_gas_price"
+            | "tx_ink_price"
+            | "tx_origin"
+            | "arb_l1_sender"
+            | "pay_for_memory_grow"
+            | "memory_gas_charged"
+            | "native_keccak256"
+            | "keccak256_pair"
+            | "native_sha256"
+            | "native_ripemd160"
+            | "native_modexp"
+            | "native_ecpairing"
+            | "native_ecrecover" => Self::Other,
+            _ => return None,
+        })
+    }
+}
+
+/// Ink spent inside hostios during a call, by category.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HostProfile {
+    pub storage: u64,
+    pub calls: u64,
+    pub logs: u64,
+    pub other: u64,
+}
+
+impl HostProfile {
+    /// Attributes the ink spent by the named hostio.
+    pub fn charge(&mut self, hostio: &str, ink: u64) {
+        let total = match HostCategory::of(hostio) {
+            HostCategory::Storage => &mut self.storage,
+            HostCategory::Calls => &mut self.calls,
+            HostCategory::Logs => &mut self.logs,
+            HostCategory::Other => &mut self.other,
+        };
+        *total = total.saturating_add(ink);
+    }
+
+    /// Splits the gas used by a call into categories.
+    /// Whatever wasn't spent in a hostio is attributed to compute, so the parts always sum to `gas_used`.
+    pub fn breakdown(&self, gas_used: u64, pricing: PricingParams) -> GasBreakdown {
+        let mut left = gas_used;
+        let mut take = |ink: u64| {
+            let gas = pricing.ink_to_gas(ink).min(left);
+            left -= gas;
+            gas
+        };
+        let storage = take(self.storage);
+        let calls = take(self.calls);
+        let logs = take(self.logs);
+        let other = take(self.other);
+
+        GasBreakdown {
+            compute: left,
+            storage,
+            calls,
+            logs,
+            other,
+        }
+    }
+}
+
+/// Gas used by a call, by category. The fields sum to the total gas consumed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct GasBreakdown {
+    /// Gas spent executing opcodes.
+    pub compute: u64,
+    /// Gas spent in storage hostios, both permanent and transient.
+    pub storage: u64,
+    /// Gas spent calling and creating contracts.
+    pub calls: u64,
+    /// Gas spent emitting logs.
+    pub logs: u64,
+    /// Gas spent in all other hostios.
+    pub other: u64,
+}
+
+impl GasBreakdown {
+    pub fn total(&self) -> u64 {
+        self.compute + self.storage + self.calls + self.logs + self.other
+    }
+}
+
+/// Wall-clock time spent in each phase of a call, in nanoseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CallTimings {
+    /// Time spent deserializing, or fetching from the cache, and instantiating the module.
+    pub instantiation: u64,
+    /// Time spent executing the program.
+    pub execution: u64,
+}
+
+impl CallTimings {
+    /// Runs `f`, recording its duration in `phase` if timing is enabled.
+    pub fn record<T>(phase: Option<&mut u64>, f: impl FnOnce() -> T) -> T {
+        let Some(phase) = phase else {
+            return f();
+        };
+        let start = Instant::now();
+        let result = f();
+        *phase = start.elapsed().as_nanos().try_into().unwrap_or(u64::MAX);
+        result
+    }
+
+    pub fn total(&self) -> u64 {
+        self.instantiation.saturating_add(self.execution)
+    }
+}
+
+/// The cost of instantiating a module, isolated from its execution.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InstantiationCost {
+    /// Wall-clock time spent deserializing, or fetching from the cache, and instantiating.
+    pub nanos: u64,
+    /// The gas equivalent of that time, had it been spent at the chain's speed limit.
+    pub gas: u64,
+    /// Whether the init cache already held the module.
+    pub cached: bool,
+}
+
+impl InstantiationCost {
+    /// The chain's speed limit, in gas per second.
+    pub const GAS_PER_SECOND: u64 = 7_000_000;
+
+    pub fn new(nanos: u64, cached: bool) -> Self {
+        let gas = (nanos as u128 * Self::GAS_PER_SECOND as u128 / 1_000_000_000) as u64;
+        Self { nanos, gas, cached }
+    }
+}
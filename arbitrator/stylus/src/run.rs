@@ -6,9 +6,17 @@
 use crate::{env::Escape, native::NativeInstance};
 use arbutil::evm::api::{DataReader, EvmApi};
 use arbutil::evm::user::UserOutcome;
-use eyre::{eyre, Result};
+use eyre::{bail, eyre, Result, WrapErr};
 use prover::machine::Machine;
+use prover::programs::counter::{OP_LIMIT_ERROR, STYLUS_OPS_LEFT};
 use prover::programs::{prelude::*, STYLUS_ENTRY_POINT};
+use prover::value::Value;
+use std::mem;
+use wasmer::RuntimeError;
+use wasmer_types::TrapCode;
+
+/// The error of any `Failure` caused by the wasm `unreachable` instruction, wherever it's hit.
+pub const UNREACHABLE_ERROR: &str = "unreachable instruction executed";
 
 pub trait RunProgram {
     fn run_main(&mut self, args: &[u8], config: StylusConfig, ink: u64) -> Result<UserOutcome>;
@@ -43,6 +51,14 @@ impl RunProgram for Machine {
         self.set_ink(ink);
         self.set_stack(config.max_depth);
 
+        let limited = self.get_global(STYLUS_OPS_LEFT).is_ok();
+        match (config.max_ops, limited) {
+            (0, false) => {}
+            (0, true) => self.set_global(STYLUS_OPS_LEFT, Value::I64(u64::MAX))?,
+            (_, false) => bail!("operator limits require op_limit instrumentation"),
+            (ops, true) => self.set_global(STYLUS_OPS_LEFT, Value::I64(ops))?,
+        }
+
         let status: u32 = call!("user", STYLUS_ENTRY_POINT, vec![args_len], |error| {
             if self.stack_left() == 0 {
                 return UserOutcome::OutOfStack;
@@ -50,6 +66,9 @@ impl RunProgram for Machine {
             if self.ink_left() == MachineMeter::Exhausted {
                 return UserOutcome::OutOfInk;
             }
+            if self.get_global(STYLUS_OPS_LEFT).ok() == Some(Value::I64(0)) {
+                return UserOutcome::Failure(eyre!(OP_LIMIT_ERROR));
+            }
             UserOutcome::Failure(error)
         });
 
@@ -66,16 +85,57 @@ impl RunProgram for Machine {
 
 impl<D: DataReader, E: EvmApi<D>> RunProgram for NativeInstance<D, E> {
     fn run_main(&mut self, args: &[u8], config: StylusConfig, ink: u64) -> Result<UserOutcome> {
+        // the EVM reverts the state of failed calls, just as it does for the other runtimes
+        self.run_entrypoint(args, config, ink)
+    }
+}
+
+impl<D: DataReader, E: EvmApi<D>> NativeInstance<D, E> {
+    /// Runs the program like `run_main`, but rolls back all state changes regardless of the
+    /// outcome. Gas is charged as usual, making this suitable for estimation.
+    pub fn run_dry(&mut self, args: &[u8], config: StylusConfig, ink: u64) -> Result<UserOutcome> {
+        self.env_mut().evm_api.checkpoint();
+        let outcome = self.run_entrypoint(args, config, ink);
+        self.env_mut().evm_api.rollback();
+        outcome
+    }
+
+    /// Runs the program without an ink limit, so that even programs which would run out of ink
+    /// run to completion and the operator counts reflect the entire execution. The meter still
+    /// runs, yet can never trap. This is for profiling only and must never be used in consensus,
+    /// so it requires a debug config that counts operators.
+    pub fn run_count_only(&mut self, args: &[u8], config: StylusConfig) -> Result<UserOutcome> {
+        let debug = &self.env().compile.debug;
+        if !debug.debug_funcs || !debug.count_ops {
+            bail!("count-only mode requires a debug config that counts operators");
+        }
+        self.run_main(args, config, u64::MAX)
+    }
+
+    fn run_entrypoint(
+        &mut self,
+        args: &[u8],
+        config: StylusConfig,
+        ink: u64,
+    ) -> Result<UserOutcome> {
         use UserOutcome::*;
 
         self.set_ink(ink);
         self.set_stack(config.max_depth);
+        self.set_ops_left(config.max_ops)?;
 
         let store = &mut self.store;
         let env = self.env.as_mut(store);
         env.args = args.to_owned();
         env.outs.clear();
         env.config = Some(config);
+        env.logs_emitted = 0;
+        env.memory_gas = 0;
+        env.memory_grows = 0;
+        env.debug_traces.clear();
+        if let Some(growths) = env.memory_growths.as_mut() {
+            growths.clear();
+        }
 
         if env.evm_data.tracing {
             let args_len = args.len() as u32;
@@ -87,25 +147,10 @@ impl<D: DataReader, E: EvmApi<D>> RunProgram for NativeInstance<D, E> {
         let main = exports.get_typed_function::<u32, u32>(store, STYLUS_ENTRY_POINT)?;
         let status = match main.call(store, args.len() as u32) {
             Ok(status) => status,
-            Err(outcome) => {
-                if self.stack_left() == 0 {
-                    return Ok(OutOfStack);
-                }
-                if self.ink_left() == MachineMeter::Exhausted {
-                    return Ok(OutOfInk);
-                }
-
-                let escape: Escape = match outcome.downcast() {
-                    Ok(escape) => escape,
-                    Err(error) => return Ok(Failure(eyre!(error).wrap_err("hard user error"))),
-                };
-                match escape {
-                    Escape::OutOfInk => return Ok(OutOfInk),
-                    Escape::Memory(error) => return Ok(Failure(error.into())),
-                    Escape::Internal(error) | Escape::Logical(error) => return Ok(Failure(error)),
-                    Escape::Exit(status) => status,
-                }
-            }
+            Err(error) => match self.classify_trap(error) {
+                Ok(status) => status,
+                Err(outcome) => return Ok(outcome),
+            },
         };
 
         let env = self.env_mut();
@@ -114,10 +159,78 @@ impl<D: DataReader, E: EvmApi<D>> RunProgram for NativeInstance<D, E> {
                 .capture_hostio("user_returned", &[], &status.to_be_bytes(), ink, ink);
         }
 
-        let outs = env.outs.clone();
+        let outs = mem::take(&mut env.outs);
         Ok(match status {
             0 => UserOutcome::Success(outs),
             _ => UserOutcome::Revert(outs),
         })
     }
+
+    /// Runs the start function that instrumentation moved out of instantiation, if any.
+    /// Traps are classified exactly like those of `run_main`.
+    pub fn run_start(&mut self, ink: u64) -> Result<UserOutcome> {
+        let start = self.get_start()?;
+        self.set_ink(ink);
+        if let Some(config) = self.env().config {
+            self.set_stack(config.max_depth);
+        }
+
+        let status = match start.call(&mut self.store) {
+            Ok(()) => 0,
+            Err(error) => match self.classify_trap(error) {
+                Ok(status) => status,
+                Err(outcome) => return Ok(outcome),
+            },
+        };
+        let outs = self.env().outs.clone();
+        Ok(match status {
+            0 => UserOutcome::Success(outs),
+            _ => UserOutcome::Revert(outs),
+        })
+    }
+
+    /// Applies the operator limit, resetting any left over from a previous call.
+    /// A limit of 0 means no limit, which programs without `op_limit` instrumentation require.
+    fn set_ops_left(&mut self, max_ops: u64) -> Result<()> {
+        let limited = self.instance.exports.get_global(STYLUS_OPS_LEFT).is_ok();
+        if max_ops == 0 && !limited {
+            return Ok(());
+        }
+        let ops = if max_ops == 0 { u64::MAX } else { max_ops };
+        self.set_global(STYLUS_OPS_LEFT, ops as i64)
+            .wrap_err("operator limits require op_limit instrumentation")
+    }
+
+    /// Determines the outcome of a trap, or the exit status if the program exited early.
+    /// Note that instantiation failures never get here, being errors rather than outcomes.
+    fn classify_trap(&mut self, error: RuntimeError) -> Result<u32, UserOutcome> {
+        use UserOutcome::*;
+
+        if self.stack_left() == 0 {
+            return Err(OutOfStack);
+        }
+        if self.ink_left() == MachineMeter::Exhausted {
+            return Err(OutOfInk);
+        }
+        if self.get_global::<u64>(STYLUS_OPS_LEFT).ok() == Some(0) {
+            return Err(Failure(eyre!(OP_LIMIT_ERROR)));
+        }
+
+        let escape: Escape = match error.downcast() {
+            Ok(escape) => escape,
+            Err(error) => {
+                if error.clone().to_trap() == Some(TrapCode::UnreachableCodeReached) {
+                    return Err(Failure(eyre!(UNREACHABLE_ERROR)));
+                }
+                return Err(Failure(eyre!(error).wrap_err("hard user error")));
+            }
+        };
+        match escape {
+            Escape::OutOfInk => Err(OutOfInk),
+            Escape::Memory(error) => Err(Failure(error.into())),
+            escape @ Escape::Unaligned(..) => Err(Failure(escape.into())),
+            Escape::Internal(error) | Escape::Logical(error) => Err(Failure(error)),
+            Escape::Exit(status) => Ok(status),
+        }
+    }
 }
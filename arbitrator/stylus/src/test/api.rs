@@ -3,16 +3,18 @@
 
 use crate::{native, run::RunProgram};
 use arbutil::{
+    crypto,
     evm::{
         api::{EvmApi, VecReader},
         user::UserOutcomeKind,
-        EvmData,
+        ChainParam, EvmData,
     },
     Bytes20, Bytes32,
 };
-use eyre::Result;
+use eyre::{bail, Result};
 use parking_lot::Mutex;
 use prover::programs::{memory::MemoryModel, prelude::*};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
 use std::{collections::HashMap, sync::Arc};
 
 use super::TestInstance;
@@ -20,13 +22,18 @@ use super::TestInstance;
 #[derive(Clone, Debug)]
 pub(crate) struct TestEvmApi {
     contracts: Arc<Mutex<HashMap<Bytes20, Vec<u8>>>>,
+    codes: Arc<Mutex<HashMap<Bytes20, Vec<u8>>>>,
     storage: Arc<Mutex<HashMap<Bytes20, HashMap<Bytes32, Bytes32>>>>,
+    checkpoints: Arc<Mutex<Vec<HashMap<Bytes20, HashMap<Bytes32, Bytes32>>>>>,
     program: Bytes20,
     write_result: Arc<Mutex<Vec<u8>>>,
     compile: CompileConfig,
     configs: Arc<Mutex<HashMap<Bytes20, StylusConfig>>>,
     evm_data: EvmData,
     pages: Arc<Mutex<(u16, u16)>>,
+    logs: Arc<Mutex<Vec<(Vec<u8>, u32)>>>,
+    transient: Arc<Mutex<HashMap<Bytes32, Bytes32>>>,
+    chain_params: Arc<Mutex<HashMap<u32, u64>>>,
 }
 
 impl TestEvmApi {
@@ -39,31 +46,113 @@ impl TestEvmApi {
 
         let api = TestEvmApi {
             contracts: Arc::new(Mutex::new(HashMap::new())),
+            codes: Arc::new(Mutex::new(HashMap::new())),
             storage: Arc::new(Mutex::new(storage)),
+            checkpoints: Arc::new(Mutex::new(vec![])),
             program,
             write_result: Arc::new(Mutex::new(vec![])),
             compile,
             configs: Arc::new(Mutex::new(HashMap::new())),
             evm_data,
             pages: Arc::new(Mutex::new((0, 0))),
+            logs: Arc::new(Mutex::new(vec![])),
+            transient: Arc::new(Mutex::new(HashMap::new())),
+            chain_params: Arc::new(Mutex::new(HashMap::new())),
         };
         (api, evm_data)
     }
 
+    /// Like `new`, but deterministically derives the `EvmData`'s otherwise arbitrary values from
+    /// `seed`, so that tests depending on randomness or salts are repeatable.
+    pub fn new_seeded(compile: CompileConfig, seed: u64) -> (TestEvmApi, EvmData) {
+        let (mut api, mut evm_data) = Self::new(compile);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut bytes = |data: &mut [u8]| rng.fill_bytes(data);
+
+        bytes(&mut evm_data.block_prevrandao.0);
+        bytes(&mut evm_data.block_coinbase.0);
+        bytes(&mut evm_data.msg_sender.0);
+        bytes(&mut evm_data.tx_origin.0);
+        api.evm_data = evm_data;
+        (api, evm_data)
+    }
+
     pub fn deploy(&mut self, address: Bytes20, config: StylusConfig, name: &str) -> Result<()> {
         let file = format!("tests/{name}/target/wasm32-unknown-unknown/release/{name}.wasm");
         let wasm = std::fs::read(file)?;
         let module = native::module(&wasm, self.compile.clone())?;
         self.contracts.lock().insert(address, module);
+        self.codes.lock().insert(address, wasm);
         self.configs.lock().insert(address, config);
         Ok(())
     }
 
+    pub fn set_code(&mut self, address: Bytes20, code: Vec<u8>) {
+        self.codes.lock().insert(address, code);
+    }
+
+    /// The logs emitted so far, as pairs of data and topic counts.
+    pub fn logs(&self) -> Vec<(Vec<u8>, u32)> {
+        self.logs.lock().clone()
+    }
+
+    pub fn set_chain_param(&mut self, param: ChainParam, value: u64) {
+        self.chain_params.lock().insert(param.into(), value);
+    }
+
+    /// The number of checkpoints not yet rolled back.
+    pub fn checkpoints(&self) -> usize {
+        self.checkpoints.lock().len()
+    }
+
+    /// Replaces the `EvmData` the top frame runs with, which nested calls inherit.
+    pub fn set_evm_data(&mut self, evm_data: EvmData) {
+        self.evm_data = evm_data;
+    }
+
     pub fn set_pages(&mut self, open: u16) {
         let mut pages = self.pages.lock();
         pages.0 = open;
         pages.1 = open.max(pages.1);
     }
+
+    /// Runs a contract in a new frame, which is static if either it or its caller is.
+    fn call(
+        &mut self,
+        contract: Bytes20,
+        calldata: &[u8],
+        gas: u64,
+        is_static: bool,
+    ) -> (u32, u64, UserOutcomeKind) {
+        let compile = self.compile.clone();
+        let config = *self.configs.lock().get(&contract).unwrap();
+
+        let mut evm_data = self.evm_data;
+        evm_data.is_static |= is_static;
+        let mut api = self.clone();
+        api.evm_data = evm_data;
+
+        let mut native = unsafe {
+            let contracts = self.contracts.lock();
+            let module = contracts.get(&contract).unwrap();
+            TestInstance::deserialize(module, compile, api, evm_data).unwrap()
+        };
+
+        // like the EVM, revert the state of failed calls
+        let snapshot = self.storage.lock().clone();
+        let ink = config.pricing.gas_to_ink(gas);
+        let outcome = native.run_main(calldata, config, ink).unwrap();
+        let (status, outs) = outcome.into_data();
+        if status != UserOutcomeKind::Success {
+            *self.storage.lock() = snapshot;
+        }
+        let outs_len = outs.len() as u32;
+
+        let ink_left: u64 = native.ink_left().into();
+        let gas_left = config.pricing.ink_to_gas(ink_left);
+        *self.write_result.lock() = outs;
+        (outs_len, gas - gas_left, status)
+    }
 }
 
 impl EvmApi<VecReader> for TestEvmApi {
@@ -82,17 +171,24 @@ impl EvmApi<VecReader> for TestEvmApi {
     }
 
     fn flush_storage_cache(&mut self, _clear: bool, _gas_left: u64) -> Result<u64> {
+        if self.evm_data.is_static {
+            bail!("write protection");
+        }
         let storage = &mut self.storage.lock();
         let storage = storage.get_mut(&self.program).unwrap();
         Ok(22100 * storage.len() as u64) // pretend worst case
     }
 
-    fn get_transient_bytes32(&mut self, _key: Bytes32) -> Bytes32 {
-        unimplemented!("tload not supported")
+    fn get_transient_bytes32(&mut self, key: Bytes32) -> Bytes32 {
+        self.transient.lock().get(&key).cloned().unwrap_or_default()
     }
 
-    fn set_transient_bytes32(&mut self, _key: Bytes32, _value: Bytes32) -> Result<()> {
-        unimplemented!("tstore not supported")
+    fn set_transient_bytes32(&mut self, key: Bytes32, value: Bytes32) -> Result<()> {
+        if self.evm_data.is_static {
+            bail!("write protection");
+        }
+        self.transient.lock().insert(key, value);
+        Ok(())
     }
 
     /// Simulates a contract call.
@@ -104,34 +200,18 @@ impl EvmApi<VecReader> for TestEvmApi {
         gas: u64,
         _value: Bytes32,
     ) -> (u32, u64, UserOutcomeKind) {
-        let compile = self.compile.clone();
-        let evm_data = self.evm_data;
-        let config = *self.configs.lock().get(&contract).unwrap();
-
-        let mut native = unsafe {
-            let contracts = self.contracts.lock();
-            let module = contracts.get(&contract).unwrap();
-            TestInstance::deserialize(module, compile, self.clone(), evm_data).unwrap()
-        };
-
-        let ink = config.pricing.gas_to_ink(gas);
-        let outcome = native.run_main(calldata, config, ink).unwrap();
-        let (status, outs) = outcome.into_data();
-        let outs_len = outs.len() as u32;
-
-        let ink_left: u64 = native.ink_left().into();
-        let gas_left = config.pricing.ink_to_gas(ink_left);
-        *self.write_result.lock() = outs;
-        (outs_len, gas - gas_left, status)
+        self.call(contract, calldata, gas, false)
     }
 
+    /// Simulates a delegate call, which preserves the caller's static context.
+    /// Note: since storage isn't separated by address, this otherwise behaves like a normal call.
     fn delegate_call(
         &mut self,
-        _contract: Bytes20,
-        _calldata: &[u8],
-        _gas: u64,
+        contract: Bytes20,
+        calldata: &[u8],
+        gas: u64,
     ) -> (u32, u64, UserOutcomeKind) {
-        todo!("delegate call not yet supported")
+        self.call(contract, calldata, gas, false)
     }
 
     fn static_call(
@@ -140,8 +220,7 @@ impl EvmApi<VecReader> for TestEvmApi {
         calldata: &[u8],
         gas: u64,
     ) -> (u32, u64, UserOutcomeKind) {
-        println!("note: overriding static call with call");
-        self.contract_call(contract, calldata, gas, Bytes32::default())
+        self.call(contract, calldata, gas, true)
     }
 
     fn create1(
@@ -167,20 +246,26 @@ impl EvmApi<VecReader> for TestEvmApi {
         VecReader::new(self.write_result.lock().clone())
     }
 
-    fn emit_log(&mut self, _data: Vec<u8>, _topics: u32) -> Result<()> {
-        Ok(()) // pretend a log was emitted
+    fn emit_log(&mut self, data: Vec<u8>, topics: u32) -> Result<()> {
+        self.logs.lock().push((data, topics));
+        Ok(())
     }
 
     fn account_balance(&mut self, _address: Bytes20) -> (Bytes32, u64) {
         unimplemented!()
     }
 
-    fn account_code(&mut self, _address: Bytes20, _gas_left: u64) -> (VecReader, u64) {
-        unimplemented!()
+    fn account_code(&mut self, address: Bytes20, _gas_left: u64) -> (VecReader, u64) {
+        let code = self.codes.lock().get(&address).cloned().unwrap_or_default();
+        (VecReader::new(code), 0)
     }
 
-    fn account_codehash(&mut self, _address: Bytes20) -> (Bytes32, u64) {
-        unimplemented!()
+    fn account_codehash(&mut self, address: Bytes20) -> (Bytes32, u64) {
+        let hash = match self.codes.lock().get(&address) {
+            Some(code) => crypto::keccak(code).into(),
+            None => Bytes32::default(),
+        };
+        (hash, 0)
     }
 
     fn add_pages(&mut self, new: u16) -> u64 {
@@ -203,4 +288,24 @@ impl EvmApi<VecReader> for TestEvmApi {
     ) {
         unimplemented!()
     }
+
+    fn checkpoint(&mut self) {
+        let storage = self.storage.lock().clone();
+        self.checkpoints.lock().push(storage);
+    }
+
+    fn rollback(&mut self) {
+        if let Some(storage) = self.checkpoints.lock().pop() {
+            *self.storage.lock() = storage;
+        }
+    }
+
+    fn chain_param(&mut self, param: ChainParam) -> u64 {
+        let param: u32 = param.into();
+        self.chain_params
+            .lock()
+            .get(&param)
+            .cloned()
+            .unwrap_or_default()
+    }
 }
@@ -0,0 +1,199 @@
+// Copyright 2024, Offchain Labs, Inc.
+// For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+use crate::test::api::TestEvmApi;
+use arbutil::{
+    evm::{api::VecReader, EvmData},
+    pricing,
+};
+use caller_env::GuestPtr;
+use eyre::ErrReport;
+use prover::programs::{config::PricingParams, meter::OutOfInkError, prelude::*};
+use std::{collections::HashMap, fmt::Display};
+use user_host_trait::UserHost;
+
+/// A host whose every charge fails, recording the first.
+/// Since buying ink is the first thing a hostio does, this measures its fixed cost.
+#[derive(Default)]
+struct FirstCharge {
+    charged: Option<u64>,
+    evm_data: EvmData,
+}
+
+impl MeteredMachine for FirstCharge {
+    fn ink_left(&self) -> MachineMeter {
+        MachineMeter::Exhausted
+    }
+
+    fn set_meter(&mut self, _meter: MachineMeter) {}
+
+    fn set_ink_left(&mut self, _ink: u64) {}
+
+    fn set_ink_status(&mut self, _status: u32) {}
+
+    fn buy_ink(&mut self, ink: u64) -> Result<(), OutOfInkError> {
+        self.charged.get_or_insert(ink);
+        Err(OutOfInkError)
+    }
+}
+
+impl GasMeteredMachine for FirstCharge {
+    fn pricing(&self) -> PricingParams {
+        unreachable!("hostios buy ink before gas")
+    }
+}
+
+impl UserHost<VecReader> for FirstCharge {
+    type Err = ErrReport;
+    type MemoryErr = ErrReport;
+    type A = TestEvmApi;
+
+    fn args(&self) -> &[u8] {
+        unreachable!("hostios charge before reading args")
+    }
+
+    fn outs(&mut self) -> &mut Vec<u8> {
+        unreachable!("hostios charge before writing outs")
+    }
+
+    fn evm_api(&mut self) -> &mut Self::A {
+        unreachable!("hostios charge before calling the EVM")
+    }
+
+    fn evm_data(&self) -> &EvmData {
+        &self.evm_data
+    }
+
+    fn evm_return_data_len(&mut self) -> &mut u32 {
+        unreachable!("hostios charge before reading return data")
+    }
+
+    fn logs_emitted(&mut self) -> &mut u32 {
+        unreachable!("hostios charge before emitting logs")
+    }
+
+    fn memory_gas(&mut self) -> &mut u64 {
+        unreachable!("hostios charge before reading memory gas")
+    }
+
+    fn config(&self) -> StylusConfig {
+        unreachable!("hostios charge before reading the config")
+    }
+
+    fn read_slice(&self, _ptr: GuestPtr, _len: u32) -> Result<Vec<u8>, ErrReport> {
+        unreachable!("hostios charge before reading memory")
+    }
+
+    fn read_fixed<const N: usize>(&self, _ptr: GuestPtr) -> Result<[u8; N], ErrReport> {
+        unreachable!("hostios charge before reading memory")
+    }
+
+    fn write_u32(&mut self, _ptr: GuestPtr, _x: u32) -> Result<(), ErrReport> {
+        unreachable!("hostios charge before writing memory")
+    }
+
+    fn write_slice(&self, _ptr: GuestPtr, _src: &[u8]) -> Result<(), ErrReport> {
+        unreachable!("hostios charge before writing memory")
+    }
+
+    fn check_memory_access(&self, _ptr: GuestPtr, _bytes: u32) -> Result<(), ErrReport> {
+        unreachable!("hostios charge before accessing memory")
+    }
+
+    fn say<D: Display>(&self, _text: D) {}
+
+    fn trace(&mut self, _name: &str, _args: &[u8], _outs: &[u8], _end_ink: u64) {}
+}
+
+/// Runs a hostio on a fresh [`FirstCharge`], returning its name and the fixed ink it charged.
+macro_rules! charge {
+    ($hostio:ident($($arg:expr),* $(,)?)) => {{
+        let mut host = FirstCharge::default();
+        let _ = host.$hostio($($arg),*);
+        (stringify!($hostio), host.charged.unwrap_or_default())
+    }};
+}
+
+#[test]
+fn test_hostio_base_ink() {
+    let p = GuestPtr(0);
+
+    // lengths are zero, so that no hostio charges for its arguments
+    let charges = [
+        charge!(read_args(p)),
+        charge!(write_result(p, 0)),
+        charge!(exit_early(0)),
+        charge!(finish_return(p, 0)),
+        charge!(finish_revert(p, 0)),
+        charge!(storage_load_bytes32(p, p)),
+        charge!(storage_load_bytes(p, p, 0)),
+        charge!(storage_cache_bytes32(p, p)),
+        charge!(storage_flush_cache(false)),
+        charge!(transient_load_bytes32(p, p)),
+        charge!(transient_store_bytes32(p, p)),
+        charge!(transient_compare_and_set(p, p, p)),
+        charge!(call_contract(p, p, 0, p, 0, p)),
+        charge!(delegate_call_contract(p, p, 0, 0, p)),
+        charge!(static_call_contract(p, p, 0, 0, p)),
+        charge!(create1(p, 0, p, p, p)),
+        charge!(create2(p, 0, p, p, p, p)),
+        charge!(read_return_data(p, 0, 0)),
+        charge!(return_data_size()),
+        charge!(emit_log(p, 0, 0)),
+        charge!(emit_log_with_signature(p, 0, p, 0, 0)),
+        charge!(account_balance(p, p)),
+        charge!(account_code(p, 0, 0, p)),
+        charge!(account_codehash(p, p)),
+        charge!(account_is_contract(p)),
+        charge!(account_code_size(p)),
+        charge!(evm_gas_left()),
+        charge!(evm_ink_left()),
+        charge!(block_basefee(p)),
+        charge!(block_blob_basefee(p)),
+        charge!(block_excess_blob_gas()),
+        charge!(chainid()),
+        charge!(arb_chain_param(0)),
+        charge!(block_coinbase(p)),
+        charge!(block_prevrandao(p)),
+        charge!(block_gas_limit()),
+        charge!(block_number()),
+        charge!(arb_l1_block_number()),
+        charge!(arb_l2_block_number()),
+        charge!(block_timestamp()),
+        charge!(contract_address(p)),
+        charge!(contract_code_copy(0, 0, p)),
+        charge!(contract_code_size()),
+        charge!(math_div(p, p)),
+        charge!(math_mod(p, p)),
+        charge!(math_add(p, p)),
+        charge!(math_mul(p, p)),
+        charge!(math_pow(p, p)),
+        charge!(math_add_mod(p, p, p)),
+        charge!(math_mul_mod(p, p, p)),
+        charge!(msg_reentrant()),
+        charge!(is_static()),
+        charge!(msg_sender(p)),
+        charge!(msg_value(p)),
+        charge!(tx_gas_price(p)),
+        charge!(effective_gas_price(p)),
+        charge!(tx_ink_price()),
+        charge!(tx_origin(p)),
+        charge!(arb_l1_sender(p)),
+        charge!(pay_for_memory_grow(0)),
+        charge!(memory_gas_charged()),
+        charge!(native_keccak256(p, 0, p)),
+        charge!(keccak256_pair(p, p, p)),
+        charge!(native_sha256(p, 0, p)),
+        charge!(native_ripemd160(p, 0, p)),
+        charge!(native_modexp(p, 0, p, 0, p, 0, p)),
+        charge!(native_ecpairing(p, 0)),
+        charge!(native_ecrecover(p, p)),
+    ];
+
+    // the table lists exactly the hostios above, each at the ink it actually charges
+    let table: HashMap<_, _> = pricing::HOSTIO_BASE_INK.into_iter().collect();
+    assert_eq!(table.len(), charges.len());
+    for (hostio, ink) in charges {
+        assert_eq!(table.get(hostio), Some(&ink), "{hostio}");
+    }
+}
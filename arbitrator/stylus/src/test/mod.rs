@@ -1,26 +1,33 @@
 // Copyright 2022-2023, Offchain Labs, Inc.
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
-use crate::{env::WasmEnv, native::NativeInstance, run::RunProgram, test::api::TestEvmApi};
+use crate::{
+    env::WasmEnv, native::NativeInstance, run::RunProgram, test::api::TestEvmApi, GoSliceData,
+    RustSlice,
+};
 use arbutil::{
-    evm::{api::VecReader, user::UserOutcome},
+    evm::{
+        api::{EvmApiMethod, VecReader, EVM_API_METHOD_REQ_OFFSET},
+        user::UserOutcome,
+    },
     Bytes20, Bytes32, Color,
 };
-use eyre::{bail, Result};
+use eyre::{bail, ensure, Result};
 use prover::{
     machine::GlobalState,
     programs::{config::SigMap, prelude::*},
     Machine,
 };
 use rand::prelude::*;
-use std::{collections::HashMap, path::Path, sync::Arc};
+use std::{collections::HashMap, path::Path, process::Command, sync::Arc};
 use wasmer::{
     imports, wasmparser::Operator, CompilerConfig, Function, FunctionEnv, Imports, Instance,
     Module, Store,
 };
 use wasmer_compiler_singlepass::Singlepass;
 
-mod api;
+pub(crate) mod api;
+mod charges;
 mod misc;
 mod native;
 mod sdk;
@@ -29,7 +36,7 @@ mod wavm;
 #[cfg(feature = "timings")]
 mod timings;
 
-type TestInstance = NativeInstance<VecReader, TestEvmApi>;
+pub(crate) type TestInstance = NativeInstance<VecReader, TestEvmApi>;
 
 impl TestInstance {
     fn new_test(path: &str, compile: CompileConfig) -> Result<Self> {
@@ -110,15 +117,59 @@ pub fn random_bytes20() -> Bytes20 {
     data.into()
 }
 
-fn random_bytes32() -> Bytes32 {
+pub(crate) fn random_bytes32() -> Bytes32 {
     let mut data = [0; 32];
     rand::thread_rng().fill_bytes(&mut data);
     data.into()
 }
 
+/// A request handler for FFI tests whose programs make no requests of their own.
+/// Checkpoints and rollbacks come from the runtime rather than the program, so they're acknowledged.
+pub(crate) unsafe extern "C" fn no_requests(
+    _: usize,
+    req_type: u32,
+    _: *mut RustSlice,
+    _: *mut u64,
+    _: *mut GoSliceData,
+    _: *mut GoSliceData,
+) {
+    let method = |method: EvmApiMethod| method as u32 + EVM_API_METHOD_REQ_OFFSET;
+    if req_type != method(EvmApiMethod::Checkpoint) && req_type != method(EvmApiMethod::Rollback) {
+        unreachable!("the program makes no requests");
+    }
+}
+
+/// Reruns the named test alone in a child process, for checks that change process-wide state.
+/// Returns whether the caller is that child, and so should run the checks itself.
+pub(crate) fn in_child_process(test: &str) -> Result<bool> {
+    in_child_process_expecting(test, true)
+}
+
+/// Like `in_child_process`, but for checks that end the process, which the child must not survive.
+pub(crate) fn in_dying_child_process(test: &str) -> Result<bool> {
+    in_child_process_expecting(test, false)
+}
+
+fn in_child_process_expecting(test: &str, success: bool) -> Result<bool> {
+    const CHILD: &str = "STYLUS_TEST_CHILD";
+    if std::env::var_os(CHILD).is_some() {
+        return Ok(true);
+    }
+    let status = Command::new(std::env::current_exe()?)
+        .args(["--exact", test, "--nocapture"])
+        .env(CHILD, "1")
+        .status()?;
+    match success {
+        true => ensure!(status.success(), "{test} failed in a child process"),
+        false => ensure!(!status.success(), "{test} survived in a child process"),
+    }
+    Ok(false)
+}
+
 fn test_compile_config() -> CompileConfig {
     let mut compile_config = CompileConfig::version(0, true);
     compile_config.debug.count_ops = true;
+    compile_config.debug.extra_funcs = true;
     compile_config
 }
 
@@ -128,7 +179,7 @@ fn uniform_cost_config() -> StylusConfig {
     stylus_config
 }
 
-fn test_configs() -> (CompileConfig, StylusConfig, u64) {
+pub(crate) fn test_configs() -> (CompileConfig, StylusConfig, u64) {
     (
         test_compile_config(),
         uniform_cost_config(),
@@ -7,34 +7,67 @@
 )]
 
 use crate::{
-    run::RunProgram,
+    cache::{CompileCache, InitCache, StorePoolConfig},
+    env::MemoryGrowth,
+    evm_api::NativeRequestHandler,
+    native::{self, NativeInstance},
+    profile::{CallTimings, HostCategory, HostProfile, InstantiationCost},
+    run::{RunProgram, UNREACHABLE_ERROR},
+    stylus_activate, stylus_activate_and_cache, stylus_activate_compressed, stylus_activated_count,
+    stylus_activation_params, stylus_asm_size, stylus_cache_contains, stylus_cache_module,
+    stylus_call, stylus_call_compressed, stylus_call_sized, stylus_call_streaming,
+    stylus_call_timed, stylus_check_instantiation, stylus_config_validate,
+    stylus_disable_init_cache, stylus_drop_vec, stylus_evict_modules, stylus_host_cost_table,
+    stylus_invalidate_module, stylus_max_version, stylus_min_version, stylus_modules_equal,
+    stylus_precompile, stylus_set_allocator, stylus_set_max_cached_module_size,
+    stylus_storage_op_cost,
     test::{
-        check_instrumentation, random_bytes20, random_bytes32, random_ink, run_machine, run_native,
+        api::TestEvmApi, check_instrumentation, in_child_process, in_dying_child_process,
+        no_requests, random_bytes20, random_bytes32, random_ink, run_machine, run_native,
         test_compile_config, test_configs, TestInstance,
     },
+    util, BytesAllocator, GoSliceData, OutputSink, RustBytes, RustSlice, ACTIVATED,
 };
 use arbutil::{
     crypto,
     evm::{
-        api::EvmApi,
+        self,
+        api::{EvmApi, EvmApiMethod, VecReader},
+        replay::{Recorder, Replayer, Trace},
+        req::{EvmApiRequestor, RequestHandler},
         user::{UserOutcome, UserOutcomeKind},
+        ChainParam, EvmData, StorageOp,
     },
-    format, Bytes20, Bytes32, Color,
+    format,
+    operator::OperatorCode,
+    pricing, Bytes20, Bytes32, Color,
 };
 use eyre::{bail, ensure, Result};
+use parking_lot::Mutex;
 use prover::{
-    binary,
+    binary::{self, DataSegment},
+    machine::Module as ProverModule,
     programs::{
-        counter::{Counter, CountingMachine},
+        counter::{CountDiff, Counter, CountingMachine, OP_LIMIT_ERROR},
+        depth::STYLUS_STACK_LEFT,
+        memory::MemoryModel,
+        meter::{STYLUS_INK_LEFT, STYLUS_INK_STATUS},
         prelude::*,
         start::StartMover,
         MiddlewareWrapper, ModuleMod,
     },
     Machine,
 };
-use std::{collections::HashMap, path::Path, sync::Arc, time::Instant};
+use std::{
+    alloc::{self, Layout},
+    collections::{HashMap, HashSet},
+    mem::MaybeUninit,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use wasmer::wasmparser::Operator;
-use wasmer::{CompilerConfig, ExportIndex, Imports, Pages, Store};
+use wasmer::{CompilerConfig, ExportIndex, ExternType, Imports, Module, Pages, Store, Value};
 use wasmer_compiler_singlepass::Singlepass;
 
 #[test]
@@ -71,6 +104,66 @@ fn test_ink() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_cost_overrides() -> Result<()> {
+    // in add-loop.wat
+    //    the `add_loop` function runs `count` iterations, each with a single i32.add
+
+    let mut compile = test_compile_config();
+    compile.pricing.costs = |_, _| 1;
+
+    let ink = 10_000;
+    let mut native = TestInstance::new_test("tests/add-loop.wat", compile.clone())?;
+    let outs = native.run_export("add_loop", &[Value::I32(100)], ink)?;
+    assert_eq!(outs, vec![Value::I32(100)]);
+
+    let add = OperatorCode::from(&Operator::I32Add);
+    compile.debug.cost_overrides.insert(add, 1_000);
+
+    let mut native = TestInstance::new_test("tests/add-loop.wat", compile)?;
+    let err = native
+        .run_export("add_loop", &[Value::I32(10)], ink)
+        .unwrap_err();
+    assert!(err.to_string().contains("out of ink"));
+    Ok(())
+}
+
+#[test]
+fn test_ink_status() -> Result<()> {
+    let (compile, config, _) = test_configs();
+    let mut native = TestInstance::new_linked("tests/add.wat", &compile, config)?;
+
+    // an exhausted meter stays exhausted, even when topped up
+    native.set_ink_left(100);
+    native.set_ink_status(1);
+    assert_eq!(native.ink_left(), MachineMeter::Exhausted);
+    assert!(native.gas_left().is_err());
+
+    native.set_ink_status(0);
+    assert_eq!(native.ink_left(), MachineMeter::Ready(100));
+    native.set_ink_left(50);
+    assert_eq!(native.ink_left(), MachineMeter::Ready(50));
+    Ok(())
+}
+
+#[test]
+fn test_run_export() -> Result<()> {
+    let mut compile = test_compile_config();
+    compile.pricing.costs = super::expensive_add;
+
+    let mut native = TestInstance::new_test("tests/add.wat", compile)?;
+    let outs = native.run_export("add_one", &[Value::I32(41)], 250)?;
+    assert_eq!(outs, vec![Value::I32(42)]);
+    assert_eq!(native.ink_left(), MachineMeter::Ready(150));
+
+    let err = native
+        .run_export("add_one", &[Value::I32(41)], 99)
+        .unwrap_err();
+    assert!(err.to_string().contains("out of ink"));
+    assert!(native.run_export("missing", &[], 250).is_err());
+    Ok(())
+}
+
 #[test]
 fn test_depth() -> Result<()> {
     // in depth.wat
@@ -113,6 +206,52 @@ fn test_depth() -> Result<()> {
     check(4 * frame_size + frame_size / 2, 4)
 }
 
+#[test]
+fn test_depth_interval() -> Result<()> {
+    // in depth.wat
+    //    the `depth` global equals the number of times `recurse` is called
+    //    each frame of `recurse` is 9 words, as shown in test_depth
+    //    `descend` calls itself the given number of times, with frames of 6 words
+
+    let mut compile = test_compile_config();
+    compile.debug.depth_check_interval = 4;
+
+    let mut native = TestInstance::new_test("tests/depth.wat", compile)?;
+    let exports = &native.exports;
+    let recurse = exports.get_typed_function::<i64, ()>(&native.store, "recurse")?;
+
+    let mut check = |space: u32, expected: u32| -> Result<()> {
+        native.set_global("depth", 0)?;
+        native.set_stack(space);
+
+        assert!(recurse.call(&mut native.store, 0).is_err());
+        assert_eq!(native.stack_left(), 0);
+
+        let program_depth: u32 = native.get_global("depth")?;
+        assert_eq!(program_depth, expected);
+        Ok(())
+    };
+
+    // overflows still trap, but only on the first and every 4th frame thereafter
+    let frame_size = 9;
+    check(frame_size, 0)?;
+    check(4 * frame_size, 4)?;
+    check(4 * frame_size + frame_size / 2, 4)?;
+
+    // setting the stack restarts the countdown, so results don't depend on earlier calls
+    check(4 * frame_size, 4)?;
+
+    // unchecked frames that don't fit repay what they owe, so returning restores the space exactly
+    let descend = native
+        .exports
+        .get_typed_function::<i32, ()>(&native.store, "descend")?;
+    let frame_size = 6;
+    native.set_stack(2 * frame_size);
+    descend.call(&mut native.store, 3)?;
+    assert_eq!(native.stack_left(), 2 * frame_size);
+    Ok(())
+}
+
 #[test]
 fn test_start() -> Result<()> {
     // in start.wat
@@ -143,6 +282,25 @@ fn test_start() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_looping_start() -> Result<()> {
+    // in start-loop.wat
+    //     the `start` function loops forever
+
+    let filename = "tests/start-loop.wat";
+    let wasm = wasmer::wat2wasm(&std::fs::read(filename)?)?;
+    let mut gas = u64::MAX;
+    let err = native::activate(&wasm, 1, 128, true, &mut gas).unwrap_err();
+    assert!(format!("{err:?}").contains("start functions not allowed"));
+
+    // instantiation never runs `start`, and calling it directly is metered
+    let mut native = TestInstance::new_test(filename, CompileConfig::version(1, true))?;
+    let starter = native.get_start()?;
+    assert!(native.call_func(starter, 1_000_000).is_err());
+    assert_eq!(native.ink_left(), MachineMeter::Exhausted);
+    Ok(())
+}
+
 #[test]
 fn test_count() -> Result<()> {
     let mut compiler = Singlepass::new();
@@ -173,6 +331,12 @@ fn test_count() -> Result<()> {
     assert_eq!(check(GlobalSet { global_index: 0 }), Some(&7));
     assert_eq!(check(I64Add), Some(&7));
     assert_eq!(check(I64Const { value: 0 }), Some(&7));
+
+    // single counts match the full set
+    for (&op, &count) in &counts {
+        assert_eq!(instance.operator_count(op)?, count);
+    }
+    assert_eq!(instance.operator_count(Unreachable.into())?, 0);
     Ok(())
 }
 
@@ -201,6 +365,35 @@ fn test_import_export_safety() -> Result<()> {
     check("tests/bad-import.wat", false)
 }
 
+#[test]
+fn test_hostio_versions() -> Result<()> {
+    let program = |hostio: &str| {
+        let wat = format!(
+            r#"(module
+                (import "vm_hooks" "{hostio}" (func))
+                (memory (export "memory") 0 0)
+                (func (export "user_entrypoint") (param i32) (result i32) i32.const 0))"#
+        );
+        wasmer::wat2wasm(wat.as_bytes()).map(|x| x.to_vec())
+    };
+    let mut gas = u64::MAX;
+
+    // programs of earlier versions can't import hostios added since
+    for &hostio in StylusConfig::V2_HOSTIOS {
+        let wasm = program(hostio)?;
+        let error = crate::native::activate(&wasm, 1, 128, true, &mut gas).unwrap_err();
+        let error = format!("{error:?}");
+        assert!(error.contains(hostio), "{error}");
+        assert!(error.contains("requires Stylus version 2"), "{error}");
+        crate::native::activate(&wasm, 2, 128, true, &mut gas)?;
+    }
+
+    // the rest are available to all versions
+    crate::native::activate(&program("read_args")?, 1, 128, true, &mut gas)?;
+    assert_eq!(StylusConfig::hostio_version("read_args"), 0);
+    Ok(())
+}
+
 #[test]
 fn test_module_mod() -> Result<()> {
     // in module-mod.wat
@@ -280,6 +473,42 @@ fn test_heap() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_deserialize_memory_bound() -> Result<()> {
+    // compiled without a tight bound, the module's memory exceeds version 1's 128 pages
+    let wat = r#"
+        (module
+            (func (export "user_entrypoint") (param i32) (result i32) i32.const 0)
+            (memory (export "memory") 129 129))"#;
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+    let mut compile = test_compile_config();
+    let asm = native::module(&wasm, compile.clone())?;
+
+    let deserialize = |compile: &CompileConfig| {
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        unsafe { TestInstance::deserialize(&asm, compile.clone(), evm, evm_data) }
+    };
+    deserialize(&compile)?;
+
+    compile.bounds.heap_bound = Pages(128);
+    let err = deserialize(&compile).unwrap_err();
+    let oversized = |err: &eyre::Report| err.to_string().contains("-page limit");
+    assert!(oversized(&err), "{err}");
+
+    // the cached path checks against the version's bound, caching nothing that fails
+    for cached in [false, true] {
+        let (evm, mut evm_data) = TestEvmApi::new(compile.clone());
+        let module_hash = random_bytes32();
+        evm_data.module_hash = module_hash;
+        evm_data.cached = cached;
+        let err = unsafe { TestInstance::deserialize_cached(&asm, 1, evm, evm_data, true, false) };
+        let err = err.err().expect("oversized memory deserialized");
+        assert!(oversized(&err), "{err}");
+        assert!(!InitCache::contains(module_hash, 1, true));
+    }
+    Ok(())
+}
+
 #[test]
 fn test_rust() -> Result<()> {
     // in keccak.rs
@@ -310,6 +539,39 @@ fn test_rust() -> Result<()> {
     check_instrumentation(native, machine)
 }
 
+#[test]
+fn test_count_only() -> Result<()> {
+    // in keccak.rs
+    //     the input is the # of hashings followed by a preimage
+    //     the output is the iterated hash of the preimage
+
+    let filename = "tests/keccak/target/wasm32-unknown-unknown/release/keccak.wasm";
+    let (_, config, _) = test_configs();
+    let mut compile = CompileConfig::version(1, true);
+    compile.debug.count_ops = true;
+    let args = [&[255][..], b"nyan"].concat();
+    let total =
+        |native: &mut TestInstance| -> Result<u64> { Ok(native.operator_counts()?.values().sum()) };
+
+    // with little ink, the program runs out partway through
+    let mut limited = TestInstance::new_linked(filename, &compile, config)?;
+    let ink = config.pricing.gas_to_ink(1_000);
+    let outcome = limited.run_main(&args, config, ink)?;
+    assert_eq!(outcome.kind(), UserOutcomeKind::OutOfInk);
+
+    let mut unlimited = TestInstance::new_linked(filename, &compile, config)?;
+    let outcome = unlimited.run_count_only(&args, config)?;
+    assert_eq!(outcome.kind(), UserOutcomeKind::Success);
+    assert!(total(&mut unlimited)? > total(&mut limited)?);
+
+    // the mode is debug-only
+    compile.debug.count_ops = false;
+    let mut native = TestInstance::new_linked(filename, &compile, config)?;
+    let err = native.run_count_only(&args, config).unwrap_err();
+    assert!(err.to_string().contains("count-only mode requires"));
+    Ok(())
+}
+
 #[test]
 fn test_fallible() -> Result<()> {
     // in fallible.rs
@@ -457,6 +719,54 @@ fn test_calls() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_delegate_call_static() -> Result<()> {
+    // in call.rs
+    //     a call of kind 1 is a delegate call
+    //
+    // in storage.rs
+    //     an input starting with 0x01 will induce a storage write
+
+    let calls = "tests/multicall/target/wasm32-unknown-unknown/release/multicall.wasm";
+    let store_addr = random_bytes20();
+    let (compile, config, ink) = test_configs();
+
+    let key = random_bytes32();
+    let value = random_bytes32();
+    let mut call = vec![0x01];
+    call.extend(store_addr);
+    call.push(0x01);
+    call.extend(key);
+    call.extend(value);
+
+    let mut args = vec![1];
+    args.extend(u32::to_be_bytes(call.len() as u32));
+    args.extend(call);
+
+    for is_static in [true, false] {
+        let (mut evm, mut evm_data) = TestEvmApi::new(compile.clone());
+        evm_data.is_static = is_static;
+        evm.set_evm_data(evm_data);
+        evm.deploy(store_addr, config, "storage")?;
+
+        let mut native = TestInstance::from_path(calls, evm.clone(), evm_data, &compile, config)?;
+        let outcome = native.run_main(&args, config, ink)?;
+
+        // the delegated write must fail when the top frame is static
+        match (is_static, outcome) {
+            (true, UserOutcome::Revert(_)) => {
+                assert_eq!(evm.get_bytes32(key).0, Bytes32::default())
+            }
+            (false, UserOutcome::Success(_)) => assert_eq!(evm.get_bytes32(key).0, value),
+            (_, outcome) => bail!(
+                "unexpected outcome when static={is_static}: {}",
+                outcome.red()
+            ),
+        }
+    }
+    Ok(())
+}
+
 #[test]
 fn test_exit_early() -> Result<()> {
     // in exit-early.wat
@@ -484,3 +794,2995 @@ fn test_exit_early() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_gas_breakdown() -> Result<()> {
+    // in mixed.wat
+    //     the args are hashed, with the digest stored, loaded, and logged
+    //     a loop of additions then burns compute
+
+    let (mut compile, config, ink) = test_configs();
+    compile.pricing.costs = super::expensive_add;
+
+    let (mut native, _) = TestInstance::new_with_evm("tests/mixed.wat", &compile, config)?;
+    native.env_mut().profile = Some(HostProfile::default());
+    run_native(&mut native, &[1; 32], ink)?;
+
+    let pricing = config.pricing;
+    let gas_used = pricing.ink_to_gas(ink) - pricing.ink_to_gas(native.ink_ready()?);
+    let breakdown = native.env().profile.unwrap().breakdown(gas_used, pricing);
+    assert_eq!(breakdown.total(), gas_used);
+    assert!(breakdown.compute > 0);
+    assert!(breakdown.storage > 0);
+    assert!(breakdown.logs > 0);
+    assert!(breakdown.other > 0);
+    assert_eq!(breakdown.calls, 0);
+    Ok(())
+}
+
+#[test]
+fn test_host_categories() {
+    use HostCategory::*;
+
+    // every hostio must be categorized on purpose rather than falling through to `Other`
+    for (hostio, _) in pricing::HOSTIO_BASE_INK {
+        assert!(HostCategory::classify(hostio).is_some(), "{hostio}");
+    }
+    assert_eq!(HostCategory::of("emit_log_with_signature"), Logs);
+    assert_eq!(HostCategory::of("storage_load_bytes"), Storage);
+    assert_eq!(HostCategory::of("transient_compare_and_set"), Storage);
+    assert_eq!(HostCategory::classify("print_i64"), None);
+}
+
+#[test]
+fn test_code_copy() -> Result<()> {
+    // in code-copy.wat
+    //     the program copies a window of its own code and returns it
+
+    let filename = "tests/code-copy.wat";
+    let (compile, config, ink) = test_configs();
+    let code = wasmer::wat2wasm(&std::fs::read(filename)?)?.to_vec();
+
+    let (mut native, mut evm) = TestInstance::new_with_evm(filename, &compile, config)?;
+    evm.set_code(Bytes20::default(), code.clone());
+
+    let mut copy = |offset: u32, size: u32| {
+        let args = [offset.to_le_bytes(), size.to_le_bytes()].concat();
+        run_native(&mut native, &args, ink)
+    };
+
+    let len = code.len() as u32;
+    assert_eq!(copy(0, 16)?, code[..16]);
+    assert_eq!(copy(8, 32)?, code[8..40]);
+    assert_eq!(copy(len - 4, 16)?, code[code.len() - 4..]);
+    assert!(copy(len, 16)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_compressed_asm() -> Result<()> {
+    // in exit-early.wat
+    //     the input is returned as the output
+
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let (_, mut config, _) = test_configs();
+    config.version = 1;
+    let args = vec![0x00; 32];
+    let slice = |data: &[u8]| GoSliceData {
+        ptr: data.as_ptr(),
+        len: data.len(),
+    };
+    let empty = || RustBytes {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+        cap: 0,
+    };
+
+    let activate = |compress: bool| -> Vec<u8> {
+        let mut output = empty();
+        let (mut asm_len, mut module_hash) = (0, Bytes32::default());
+        let mut stylus_data = MaybeUninit::uninit();
+        let mut gas = u64::MAX;
+        let status = unsafe {
+            stylus_activate_compressed(
+                slice(&wasm),
+                128,
+                1,
+                true,
+                compress,
+                &mut output,
+                &mut asm_len,
+                &mut module_hash,
+                stylus_data.as_mut_ptr(),
+                &mut gas,
+            )
+        };
+        assert_eq!(status, UserOutcomeKind::Success);
+        let asm = unsafe { std::slice::from_raw_parts(output.ptr, asm_len) }.to_vec();
+        unsafe { stylus_drop_vec(output) };
+        asm
+    };
+    let asm = activate(false);
+    let compressed = activate(true);
+    assert_ne!(asm, compressed);
+
+    let call = |asm: &[u8], module_hash: Bytes32, cached: bool, inflate: bool| {
+        let mut evm_data = EvmData::default();
+        evm_data.module_hash = module_hash;
+        evm_data.cached = cached;
+        let req_handler = NativeRequestHandler {
+            handle_request_fptr: no_requests,
+            id: 0,
+        };
+        let entry = match inflate {
+            true => stylus_call_compressed,
+            false => stylus_call,
+        };
+        let mut output = empty();
+        let mut gas = 1_000_000;
+        let status = unsafe {
+            entry(
+                slice(asm),
+                slice(&args),
+                config,
+                req_handler,
+                evm_data,
+                true,
+                &mut output,
+                &mut gas,
+            )
+        };
+        let outs = unsafe { std::slice::from_raw_parts(output.ptr, output.len) }.to_vec();
+        unsafe { stylus_drop_vec(output) };
+        (status, outs)
+    };
+
+    // compressed asm runs just like the original
+    let success = (UserOutcomeKind::Success, args.clone());
+    assert_eq!(call(&asm, random_bytes32(), false, false), success);
+    assert_eq!(call(&compressed, random_bytes32(), false, true), success);
+
+    // what's cached is the inflated module, so later calls needn't inflate anything
+    let module_hash = random_bytes32();
+    assert_eq!(call(&compressed, module_hash, true, true), success);
+    assert!(stylus_cache_contains(module_hash, 1, true));
+    assert_eq!(call(&compressed, module_hash, true, false), success);
+    Ok(())
+}
+
+#[test]
+fn test_call_timings() -> Result<()> {
+    // in write-results.wat
+    //     the return data is set twice, first to half the args and then to all of them
+
+    let wat = std::fs::read("tests/write-results.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let (_, config, _) = test_configs();
+    let mut gas = u64::MAX;
+    let (asm, ..) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    let calldata: Vec<u8> = (0..0x8000).map(|x| x as u8).collect();
+
+    let call = |debug_chain: bool, timings: &mut CallTimings| {
+        let mut evm_data = EvmData::default();
+        evm_data.module_hash = random_bytes32();
+        let req_handler = NativeRequestHandler {
+            handle_request_fptr: no_requests,
+            id: 0,
+        };
+        let mut output = RustBytes {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+            cap: 0,
+        };
+        let mut gas = 1_000_000;
+        let status = unsafe {
+            stylus_call_timed(
+                GoSliceData {
+                    ptr: asm.as_ptr(),
+                    len: asm.len(),
+                },
+                GoSliceData {
+                    ptr: calldata.as_ptr(),
+                    len: calldata.len(),
+                },
+                config,
+                req_handler,
+                evm_data,
+                debug_chain,
+                &mut output,
+                &mut gas,
+                timings,
+            )
+        };
+        let outs = unsafe { std::slice::from_raw_parts(output.ptr, output.len) }.to_vec();
+        unsafe { stylus_drop_vec(output) };
+        assert_eq!(status, UserOutcomeKind::Success);
+        assert_eq!(outs, calldata);
+    };
+
+    // each phase is timed, and together they fit within the call
+    let mut timings = CallTimings::default();
+    let start = Instant::now();
+    call(true, &mut timings);
+    let total = start.elapsed().as_nanos() as u64;
+    assert!(timings.instantiation > 0);
+    assert!(timings.execution > 0);
+    assert!(timings.total() <= total);
+
+    // timing is disabled off debug chains
+    let sentinel = CallTimings {
+        instantiation: u64::MAX,
+        execution: u64::MAX,
+    };
+    let mut timings = sentinel;
+    call(false, &mut timings);
+    assert_eq!(timings, sentinel);
+    Ok(())
+}
+
+#[test]
+fn test_sha256() -> Result<()> {
+    // in sha256.wat
+    //     the args are hashed and the digest returned
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/sha256.wat", &compile, config)?;
+
+    let mut check = |preimage: &[u8], digest: &str| -> Result<()> {
+        let output = run_native(&mut native, preimage, ink)?;
+        assert_eq!(hex::encode(output), digest);
+        Ok(())
+    };
+    check(
+        b"",
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    )?;
+    check(
+        b"abc",
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    )?;
+    check(
+        b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+    )
+}
+
+#[test]
+fn test_ripemd160() -> Result<()> {
+    // in ripemd160.wat
+    //     the args are hashed and the digest returned, left-padded to 32 bytes
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/ripemd160.wat", &compile, config)?;
+
+    let mut check = |preimage: &[u8], digest: &str| -> Result<()> {
+        let output = run_native(&mut native, preimage, ink)?;
+        assert_eq!(output.len(), 32);
+        assert_eq!(output[..12], [0; 12]);
+        assert_eq!(hex::encode(&output[12..]), digest);
+        Ok(())
+    };
+    check(b"", "9c1185a5c5e9fc54612808977ee8f548b2258d31")?;
+    check(b"abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc")?;
+    check(
+        b"message digest",
+        "5d0689ef49d2fae572b881b123a85ffa21595f36",
+    )
+}
+
+#[test]
+fn test_artifact_sizes() -> Result<()> {
+    // the entrypoint calls a local function the given number of times, each a call to relocate
+    let compile = |calls: usize| -> Result<Vec<u8>> {
+        let calls = "call $zero drop ".repeat(calls);
+        let wat = format!(
+            r#"(module
+                (memory (export "memory") 0 0)
+                (func $zero (result i32)
+                    i32.const 0)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    {calls}
+                    i32.const 0))"#
+        );
+        let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+        native::module(&wasm, CompileConfig::version(1, true))
+    };
+    let asm = compile(1)?;
+
+    // each component is measured on its own, rather than one being what's left of the others
+    let sizes = native::artifact_sizes(&asm)?;
+    assert!(sizes.code > 0);
+    assert!(sizes.relocations > 0);
+    assert!(sizes.metadata > 0);
+    assert!(sizes.total() < asm.len() as u64);
+
+    // every call adds the same relocation, both serialized at the same size
+    let (three, five) = (
+        native::artifact_sizes(&compile(3)?)?,
+        native::artifact_sizes(&compile(5)?)?,
+    );
+    let per_call = (three.relocations - sizes.relocations) / 2;
+    assert!(per_call > 0);
+    assert_eq!(three.relocations, sizes.relocations + 2 * per_call);
+    assert_eq!(five.relocations, sizes.relocations + 4 * per_call);
+    assert!(sizes.code < three.code && three.code < five.code);
+
+    assert!(native::artifact_sizes(&asm[..asm.len() / 2]).is_err());
+    assert!(native::artifact_sizes(b"not asm").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_match_selector() -> Result<()> {
+    // add.wat gets a custom section declaring two selectors
+
+    let abi = "a9059cbb transfer(address,uint256)\n0x70a08231 balanceOf(address)\n";
+    let name = binary::STYLUS_ABI_SECTION;
+    let mut section = vec![name.len() as u8];
+    section.extend(name.as_bytes());
+    section.extend(abi.as_bytes());
+    assert!(
+        section.len() < 128,
+        "section size must fit in one leb128 byte"
+    );
+
+    let wat = std::fs::read("tests/add.wat")?;
+    let mut wasm = wasmer::wat2wasm(&wat)?.to_vec();
+    wasm.extend([0, section.len() as u8]);
+    wasm.extend(section);
+
+    // the section doesn't affect activation
+    let mut gas = u64::MAX;
+    native::activate(&wasm, 1, 128, true, &mut gas)?;
+
+    let check = |calldata: &str| -> Result<Option<String>> {
+        binary::match_selector(&wasm, &hex::decode(calldata)?)
+    };
+    assert_eq!(
+        check("a9059cbb")?.as_deref(),
+        Some("transfer(address,uint256)")
+    );
+    assert_eq!(
+        check("70a08231ffff")?.as_deref(),
+        Some("balanceOf(address)")
+    );
+    assert_eq!(check("deadbeef")?, None);
+    assert_eq!(check("a905")?, None);
+
+    // modules without the section match nothing
+    let plain = wasmer::wat2wasm(&wat)?;
+    assert_eq!(
+        binary::match_selector(&plain, &hex::decode("a9059cbb")?)?,
+        None
+    );
+    Ok(())
+}
+
+#[test]
+fn test_ink_left() -> Result<()> {
+    // in ink-left.wat
+    //     the ink left is recorded in a global, with nothing after costing ink
+
+    let (mut compile, config, _) = test_configs();
+    compile.pricing.costs = |_, _| 0;
+    let mut native = TestInstance::new_linked("tests/ink-left.wat", &compile, config)?;
+
+    for ink in [1_000_000, 12_345_678, u32::MAX.into()] {
+        let outcome = native.run_main(&[], config, ink)?;
+        assert!(matches!(outcome, UserOutcome::Success(_)));
+
+        let recorded: u64 = native.get_global("ink")?;
+        assert_eq!(native.ink_left(), MachineMeter::Ready(recorded));
+        assert_eq!(recorded, ink - pricing::HOSTIO_INK);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ecpairing() -> Result<()> {
+    // in ecpairing.wat
+    //     the args are (G1, G2) pairs whose pairing product is checked, returning 0 or 1
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/ecpairing.wat", &compile, config)?;
+
+    let g1 = "0000000000000000000000000000000000000000000000000000000000000001\
+              0000000000000000000000000000000000000000000000000000000000000002";
+    let neg_g1 = "0000000000000000000000000000000000000000000000000000000000000001\
+                  30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+    let g2 = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+              1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+              090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+              12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+
+    let mut check = |pairs: &[(&str, &str)], expected: u8| -> Result<()> {
+        let input: Vec<u8> = pairs
+            .iter()
+            .flat_map(|(a, b)| hex::decode(format!("{a}{b}")).unwrap())
+            .collect();
+        let output = run_native(&mut native, &input, ink)?;
+        assert_eq!(output, [expected]);
+
+        let points = pairs.len() as u64;
+        let expected_gas = evm::ECPAIRING_GAS + evm::ECPAIRING_POINT_GAS * points;
+        let used = config.pricing.ink_to_gas(ink - native.ink_ready()?);
+        ensure!(
+            used >= expected_gas && used - expected_gas < 1_000,
+            "wrong gas {used}"
+        );
+        Ok(())
+    };
+
+    // e(P, Q) * e(-P, Q) = 1
+    check(&[(g1, g2), (neg_g1, g2)], 1)?;
+    check(&[(g1, g2)], 0)?;
+    check(&[], 1)?;
+
+    // malformed input reverts
+    let output = native.run_main(&[0; 191], config, ink)?;
+    assert!(matches!(output, UserOutcome::Failure(_)));
+    Ok(())
+}
+
+#[test]
+fn test_ecrecover() -> Result<()> {
+    // in ecrecover.wat
+    //     the args are the hash, v, r, and s, with the recovered signer returned
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/ecrecover.wat", &compile, config)?;
+
+    // signed with the private key 1, whose address is well known
+    let signer = "7e5f4552091a69125d5dfcb7b8c2659029395bdf";
+    let hash = "41e193b762fcbc7a71ebee538cb092cbd6e8b4845a688d25dd53e43531f89c60";
+    let r = "890af3a5a0cfd5ba9813d27951e6dedb92fbe62d31300845844b4ba387194c55";
+    let s = "9c5edf9f47717042893ee794dcf1883dda00f9ef512800f37a7fa2b66957fd03";
+    let low_s = "63a12060b88e8fbd76c1186b230e77c0e0ade2f75e209f484552bbd666de443e";
+
+    let mut recover = |v: u8, r: &str, s: &str| -> Result<String> {
+        let v = hex::encode(Bytes32::from(v as u32).0);
+        let input = hex::decode(format!("{hash}{v}{r}{s}"))?;
+        let output = run_native(&mut native, &input, ink)?;
+
+        let gas = config.pricing.ink_to_gas(ink - native.ink_ready()?);
+        ensure!(gas >= evm::ECRECOVER_GAS && gas < evm::ECRECOVER_GAS + 100);
+        Ok(hex::encode(output))
+    };
+
+    // the precompile accepts high-s signatures, unlike transactions
+    assert_eq!(recover(28, r, s)?, signer);
+    assert_eq!(recover(27, r, low_s)?, signer);
+
+    // tampering with the signature changes or breaks recovery
+    let zero = hex::encode([0; 20]);
+    assert_ne!(recover(27, r, s)?, signer);
+    assert_eq!(recover(29, r, s)?, zero);
+    assert_eq!(recover(1, r, s)?, zero);
+    assert_eq!(recover(28, &hex::encode([0; 32]), s)?, zero);
+    assert_eq!(recover(28, r, &hex::encode([0xff; 32]))?, zero);
+    Ok(())
+}
+
+#[test]
+fn test_revert_rollback() -> Result<()> {
+    // in storage-revert.wat
+    //     the args are a key, a value, and a status byte
+    //     the value is written to storage before exiting with the status
+
+    let filename = "tests/storage-revert.wat";
+    let (compile, config, ink) = test_configs();
+    let (mut native, mut evm) = TestInstance::new_with_evm(filename, &compile, config)?;
+
+    let key = random_bytes32();
+    let args = |value: Bytes32, status: u8| [&key[..], &value[..], &[status][..]].concat();
+
+    // calls leave reverting state to the EVM, so neither outcome makes a checkpoint
+    let kept = random_bytes32();
+    run_native(&mut native, &args(kept, 0), ink)?;
+    assert_eq!(evm.get_bytes32(key).0, kept);
+
+    let reverted = random_bytes32();
+    match native.run_main(&args(reverted, 1), config, ink)? {
+        UserOutcome::Revert(_) => {}
+        err => bail!("expected revert: {}", err.red()),
+    }
+    assert_eq!(evm.get_bytes32(key).0, reverted);
+    assert_eq!(evm.checkpoints(), 0);
+
+    // dry runs roll back whatever the outcome, leaving no checkpoints behind
+    for status in [0, 1] {
+        native.run_dry(&args(random_bytes32(), status), config, ink)?;
+        assert_eq!(evm.get_bytes32(key).0, reverted);
+        assert_eq!(evm.checkpoints(), 0);
+    }
+
+    // an unmatched rollback is ignored rather than fatal
+    evm.rollback();
+    assert_eq!(evm.get_bytes32(key).0, reverted);
+    Ok(())
+}
+
+#[test]
+fn test_debug_funcs() -> Result<()> {
+    // in debug-funcs.wat
+    //     the extended debug functions are each called once
+
+    let filename = "tests/debug-funcs.wat";
+    let (mut compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked(filename, &compile, config)?;
+    run_native(&mut native, &[], ink)?;
+
+    // without debug funcs, the imports shouldn't resolve
+    compile.debug.debug_funcs = false;
+    assert!(TestInstance::new_linked(filename, &compile, config).is_err());
+
+    // the extended set can be disabled on its own
+    compile.debug.debug_funcs = true;
+    compile.debug.extra_funcs = false;
+    assert!(TestInstance::new_linked(filename, &compile, config).is_err());
+
+    // and debug chains don't get it unless they ask
+    assert!(!CompileConfig::version(1, true).debug.extra_funcs);
+    Ok(())
+}
+
+#[test]
+fn test_evict_modules() -> Result<()> {
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (asm, _, _) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+
+    let hashes: Vec<_> = (0..5).map(|_| random_bytes32()).collect();
+    for hash in &hashes {
+        InitCache::insert(*hash, &asm, 1, true)?;
+    }
+
+    let packed: Vec<u8> = hashes[..3].iter().flat_map(|hash| hash.0).collect();
+    let packed = GoSliceData {
+        ptr: packed.as_ptr(),
+        len: packed.len(),
+    };
+    stylus_evict_modules(packed, 1, true);
+
+    for (i, hash) in hashes.iter().enumerate() {
+        let cached = InitCache::get(*hash, 1, true).is_some();
+        assert_eq!(cached, i >= 3, "wrong cache state for module {i}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_stack_left() -> Result<()> {
+    // in stack-left.wat
+    //     the stack left is recorded at each of three levels of recursion
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/stack-left.wat", &compile, config)?;
+    let output = run_native(&mut native, &[], ink)?;
+
+    let stack: Vec<_> = output
+        .chunks_exact(4)
+        .map(|x| u32::from_le_bytes(x.try_into().unwrap()))
+        .collect();
+    assert_eq!(stack.len(), 3);
+    assert!(stack[0] < config.max_depth);
+    assert!(stack[0] > stack[1] && stack[1] > stack[2]);
+    assert_eq!(stack[0] - stack[1], stack[1] - stack[2]);
+    Ok(())
+}
+
+#[test]
+fn test_storage_byte_order() -> Result<()> {
+    // in storage-order.wat
+    //     the args are a key and a value
+    //     the value is stored, loaded back, and returned
+
+    let filename = "tests/storage-order.wat";
+    let (compile, config, ink) = test_configs();
+    let (mut native, mut evm) = TestInstance::new_with_evm(filename, &compile, config)?;
+
+    // distinct bytes at either end reveal any swapping
+    let key = Bytes32::from(0x0102_u64);
+    let mut value = [0; 32];
+    value[0] = 0xaa;
+    value[31] = 0x01;
+
+    let output = run_native(&mut native, &[&key[..], &value[..]].concat(), ink)?;
+    assert_eq!(output, value);
+    assert_eq!(evm.get_bytes32(key).0, Bytes32(value));
+    assert_eq!(&key[30..], &[0x01, 0x02]);
+    Ok(())
+}
+
+#[test]
+fn test_max_logs() -> Result<()> {
+    // in logs.wat
+    //     the first arg byte is the number of logs to emit
+
+    let (compile, mut config, ink) = test_configs();
+    config.max_logs = 3;
+    let mut native = TestInstance::new_linked("tests/logs.wat", &compile, config)?;
+    run_native(&mut native, &[3], ink)?;
+
+    match native.run_main(&[4], config, ink)? {
+        UserOutcome::Failure(err) => assert!(format!("{err:?}").contains("too many logs")),
+        outcome => bail!("expected failure: {}", outcome.red()),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_op_limit() -> Result<()> {
+    // in op-limit.wat
+    //     the entrypoint loops forever, recording its progress in a global and in memory
+
+    let filename = "tests/op-limit.wat";
+    let (mut compile, mut config, ink) = test_configs();
+    compile.debug.op_limit = true;
+    config.max_ops = 1000;
+
+    let expect_limit = |outcome: UserOutcome| match outcome {
+        UserOutcome::Failure(err) => {
+            assert_eq!(err.to_string(), OP_LIMIT_ERROR);
+            Ok(())
+        }
+        outcome => bail!("expected failure: {}", outcome.red()),
+    };
+    let run_limited = || -> Result<_> {
+        let mut native = TestInstance::new_linked(filename, &compile, config)?;
+        expect_limit(native.run_main(&[], config, ink)?)?;
+        let iterations: u32 = native.get_global("iterations")?;
+        let memory = native.read_slice("memory", 0, 4)?;
+        Ok((
+            iterations,
+            memory,
+            native.ink_left(),
+            native.operator_counts()?,
+        ))
+    };
+
+    // identical runs stop at the same operator
+    let (iterations, memory, ink_left, counts) = run_limited()?;
+    assert!(iterations > 0);
+    assert_eq!(memory, iterations.to_le_bytes());
+    assert_eq!(
+        run_limited()?,
+        (iterations, memory.clone(), ink_left, counts.clone())
+    );
+
+    // and so does the prover
+    let mut machine = Machine::from_user_path(Path::new(filename), &compile)?;
+    expect_limit(machine.run_main(&[], config, ink)?)?;
+    let module = machine.find_module("user")?;
+    let machine_iterations: u32 = machine.get_global("iterations")?.try_into()?;
+    assert_eq!(machine_iterations, iterations);
+    assert_eq!(machine.read_memory(module, 0, 4)?, memory);
+    assert_eq!(machine.ink_left(), ink_left);
+    assert_eq!(machine.operator_counts()?, counts);
+
+    // a larger limit gets further
+    config.max_ops = 2000;
+    let mut native = TestInstance::new_linked(filename, &compile, config)?;
+    expect_limit(native.run_main(&[], config, ink)?)?;
+    assert!(native.get_global::<u32>("iterations")? > iterations);
+
+    // limits require the instrumentation
+    compile.debug.op_limit = false;
+    let mut native = TestInstance::new_linked(filename, &compile, config)?;
+    assert!(native.run_main(&[], config, ink).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_module_valid() -> Result<()> {
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (asm, _, _) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    assert!(native::module_valid(&asm, 1));
+
+    let mut corrupt = asm.clone();
+    let middle = corrupt.len() / 2;
+    corrupt.truncate(middle);
+    assert!(!native::module_valid(&corrupt, 1));
+    assert!(!native::module_valid(&[], 1));
+    assert!(!native::module_valid(&random_bytes32()[..], 1));
+    Ok(())
+}
+
+#[test]
+fn test_is_contract() -> Result<()> {
+    // in is-contract.wat
+    //     the args are an address whose contract status is returned
+
+    let filename = "tests/is-contract.wat";
+    let (compile, config, ink) = test_configs();
+    let (mut native, mut evm) = TestInstance::new_with_evm(filename, &compile, config)?;
+
+    let eoa = random_bytes20();
+    let contract = random_bytes20();
+    evm.set_code(eoa, vec![]);
+    evm.set_code(contract, vec![0x00, 0x61, 0x73, 0x6d]);
+
+    assert_eq!(run_native(&mut native, &eoa[..], ink)?, [0]);
+    assert_eq!(run_native(&mut native, &contract[..], ink)?, [1]);
+    assert_eq!(run_native(&mut native, &random_bytes20()[..], ink)?, [0]);
+    Ok(())
+}
+
+#[test]
+fn test_activation_params() -> Result<()> {
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (_, _, stylus_data) = native::activate(&wasm, 1, 96, true, &mut gas)?;
+    assert_eq!(stylus_data.page_limit, 96);
+    assert_eq!(stylus_data.version, 1);
+
+    let (mut page_limit, mut version) = (0, 0);
+    unsafe { stylus_activation_params(&stylus_data, &mut page_limit, &mut version) };
+    assert_eq!((page_limit, version), (96, 1));
+    Ok(())
+}
+
+#[test]
+fn test_activate_guarded() -> Result<()> {
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (asm, _, _) = native::activate_guarded(&wasm, 1, 128, true, &mut gas)?;
+    assert!(native::module_valid(&asm, 1));
+
+    // simulate the compiler panicking mid-activation
+    let err = util::catch_panic("activation", || -> Result<()> {
+        native::activate(&wasm, 1, 128, true, &mut gas)?;
+        panic!("compiler bug");
+    })
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("activation panicked: compiler bug"));
+
+    // errors pass through untouched
+    let err = native::activate_guarded(&[0x00], 1, 128, true, &mut gas).unwrap_err();
+    assert!(!err.to_string().contains("panicked"));
+    Ok(())
+}
+
+#[test]
+fn test_log_signature() -> Result<()> {
+    // in log-signature.wat
+    //     the args are two indexed topics and some data
+    //     these are emitted under the signature of an ERC-20 transfer
+
+    let filename = "tests/log-signature.wat";
+    let (compile, config, ink) = test_configs();
+    let (mut native, evm) = TestInstance::new_with_evm(filename, &compile, config)?;
+
+    let (from, to, amount) = (random_bytes32(), random_bytes32(), random_bytes32());
+    let args = [&from[..], &to[..], &amount[..]].concat();
+    run_native(&mut native, &args, ink)?;
+
+    let signature = crypto::keccak(b"Transfer(address,address,uint256)");
+    let expected = [&signature[..], &args].concat();
+    assert_eq!(evm.logs(), vec![(expected, 3)]);
+    Ok(())
+}
+
+#[test]
+fn test_library_linking() -> Result<()> {
+    // in link-main.wat
+    //     add_via_helper calls through to the add export of link-helper.wat
+
+    let (compile, config, ink) = test_configs();
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let libraries = [("helper", "tests/link-helper.wat")];
+    let mut native = TestInstance::from_path_linked(
+        "tests/link-main.wat",
+        &libraries,
+        evm,
+        evm_data,
+        &compile,
+        config,
+    )?;
+
+    let args = [Value::I32(40), Value::I32(2)];
+    let outs = native.run_export("add_via_helper", &args, ink)?;
+    assert_eq!(outs, vec![Value::I32(42)]);
+
+    // the primary module can't be instantiated without its library
+    assert!(TestInstance::new_linked("tests/link-main.wat", &compile, config).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_init_cache_archive() -> Result<()> {
+    // in exit-early.wat
+    //     the input is returned as the output
+
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (asm, _, _) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    let (_, config, ink) = test_configs();
+    let args = &[0xaa; 32];
+
+    // runs from the cache, since the module bytes are left empty
+    let run = |module_hash: Bytes32| -> Result<UserOutcome> {
+        let (evm, mut evm_data) = TestEvmApi::new(CompileConfig::version(1, true));
+        evm_data.module_hash = module_hash;
+        let mut native =
+            unsafe { TestInstance::deserialize_cached(&[], 1, evm, evm_data, true, false)? };
+        native.run_main(args, config, ink)
+    };
+
+    let hashes: Vec<_> = (0..3).map(|_| random_bytes32()).collect();
+    for hash in &hashes {
+        InitCache::insert(*hash, &asm, 1, true)?;
+    }
+    let expected: Vec<_> = hashes
+        .iter()
+        .map(|hash| run(*hash))
+        .collect::<Result<_>>()?;
+
+    let archive = InitCache::export()?;
+    InitCache::evict_many(hashes.iter().copied(), 1, true);
+    for hash in &hashes {
+        assert!(InitCache::get(*hash, 1, true).is_none());
+    }
+
+    InitCache::import(&archive)?;
+    for (hash, expected) in hashes.iter().zip(expected) {
+        assert!(InitCache::get(*hash, 1, true).is_some());
+        assert_eq!(run(*hash)?.into_data(), expected.into_data());
+    }
+
+    // corrupted archives are rejected
+    assert!(InitCache::import(&archive[..archive.len() - 1]).is_err());
+    assert!(InitCache::import(&[0xff]).is_err());
+
+    // as are those claiming more items than could ever fit
+    let huge = [&archive[..1], &u32::MAX.to_be_bytes()].concat();
+    assert!(InitCache::import(&huge).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_transient_guard() -> Result<()> {
+    // in transient-guard.wat
+    //     a reentrancy guard is entered, reentered, and exited
+
+    let filename = "tests/transient-guard.wat";
+    let (compile, config, ink) = test_configs();
+    let (mut native, mut evm) = TestInstance::new_with_evm(filename, &compile, config)?;
+
+    let output = run_native(&mut native, &[], ink)?;
+    assert_eq!(output, [1, 0, 1]);
+    assert_eq!(
+        evm.get_transient_bytes32(Bytes32::default()),
+        Bytes32::default()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_asm_size() -> Result<()> {
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let wasm = GoSliceData {
+        ptr: wasm.as_ptr(),
+        len: wasm.len(),
+    };
+    let empty = || RustBytes {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+        cap: 0,
+    };
+
+    let mut output = empty();
+    let (mut asm_len, mut module_hash) = (0, Bytes32::default());
+    let mut stylus_data = MaybeUninit::uninit();
+    let mut gas = u64::MAX;
+    let status = unsafe {
+        stylus_activate(
+            wasm,
+            128,
+            1,
+            true,
+            &mut output,
+            &mut asm_len,
+            &mut module_hash,
+            stylus_data.as_mut_ptr(),
+            &mut gas,
+        )
+    };
+    assert_eq!(status, UserOutcomeKind::Success);
+    unsafe { stylus_drop_vec(output) };
+
+    let mut output = empty();
+    let mut asm_size = 0;
+    let mut gas = u64::MAX;
+    let status =
+        unsafe { stylus_asm_size(wasm, 128, 1, true, &mut output, &mut asm_size, &mut gas) };
+    assert_eq!(status, UserOutcomeKind::Success);
+    assert_eq!(asm_size, asm_len);
+    Ok(())
+}
+
+#[test]
+fn test_codehash() -> Result<()> {
+    // in codehash.wat
+    //     the args are an address whose code hash is returned
+
+    let filename = "tests/codehash.wat";
+    let (compile, config, ink) = test_configs();
+    let (mut native, mut evm) = TestInstance::new_with_evm(filename, &compile, config)?;
+
+    let eoa = random_bytes20();
+    let contract = random_bytes20();
+    let code = std::fs::read(filename)?;
+    evm.set_code(eoa, vec![]);
+    evm.set_code(contract, code.clone());
+
+    let mut check = |address: Bytes20, hash: [u8; 32]| -> Result<()> {
+        assert_eq!(run_native(&mut native, &address[..], ink)?, hash);
+        Ok(())
+    };
+    check(random_bytes20(), [0; 32])?;
+    check(eoa, crypto::keccak(b""))?;
+    check(contract, crypto::keccak(code))
+}
+
+#[test]
+fn test_msg_value() -> Result<()> {
+    // in msg-value.wat
+    //     the program returns the 32-byte call value
+
+    let (compile, config, ink) = test_configs();
+    let (evm, mut evm_data) = TestEvmApi::new(compile.clone());
+    let value = random_bytes32();
+    evm_data.msg_value = value;
+
+    let mut native =
+        TestInstance::from_path("tests/msg-value.wat", evm, evm_data, &compile, config)?;
+    let output = run_native(&mut native, &[], ink)?;
+    assert_eq!(output, value.to_vec());
+    Ok(())
+}
+
+#[test]
+fn test_allocator_hook() -> Result<()> {
+    // the allocator is global to the process, so the checks run in a child process
+    if !in_child_process("test::native::test_allocator_hook")? {
+        return Ok(());
+    }
+
+    /// Tracks the buffers it hands out.
+    #[derive(Default)]
+    struct Recorder {
+        live: Mutex<HashSet<usize>>,
+        freed: Mutex<HashSet<usize>>,
+    }
+
+    unsafe extern "C" fn record_alloc(ctx: usize, len: usize) -> *mut u8 {
+        let recorder = &*(ctx as *const Recorder);
+        let ptr = alloc::alloc(Layout::array::<u8>(len).unwrap());
+        recorder.live.lock().insert(ptr as usize);
+        ptr
+    }
+
+    unsafe extern "C" fn record_free(ctx: usize, ptr: *mut u8, len: usize) {
+        let recorder = &*(ctx as *const Recorder);
+        assert!(recorder.live.lock().remove(&(ptr as usize)), "foreign free");
+        recorder.freed.lock().insert(ptr as usize);
+        alloc::dealloc(ptr, Layout::array::<u8>(len).unwrap());
+    }
+
+    let recorder = Recorder::default();
+    let allocator = BytesAllocator {
+        alloc: record_alloc,
+        free: record_free,
+        ctx: &recorder as *const Recorder as usize,
+    };
+
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let activate = || {
+        let mut output = RustBytes {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+            cap: 0,
+        };
+        let (mut asm_len, mut module_hash) = (0, Bytes32::default());
+        let mut stylus_data = MaybeUninit::uninit();
+        let mut gas = u64::MAX;
+        let status = unsafe {
+            stylus_activate(
+                GoSliceData {
+                    ptr: wasm.as_ptr(),
+                    len: wasm.len(),
+                },
+                128,
+                1,
+                true,
+                &mut output,
+                &mut asm_len,
+                &mut module_hash,
+                stylus_data.as_mut_ptr(),
+                &mut gas,
+            )
+        };
+        assert_eq!(status, UserOutcomeKind::Success);
+        output
+    };
+
+    unsafe { stylus_set_allocator(&allocator) };
+    let output = activate();
+    let ptr = output.ptr as usize;
+    assert!(recorder.live.lock().contains(&ptr));
+    assert_eq!(output.len, output.cap);
+
+    // outputs return to the allocator that wrote them, even after it's uninstalled
+    unsafe { stylus_set_allocator(std::ptr::null()) };
+    let other = activate();
+    unsafe { stylus_drop_vec(output) };
+    unsafe { stylus_drop_vec(other) };
+    assert!(recorder.live.lock().is_empty());
+    assert!(recorder.freed.lock().contains(&ptr));
+    Ok(())
+}
+
+#[test]
+fn test_allocator_failure() -> Result<()> {
+    // failed allocations abort rather than yield outputs that look empty
+    if !in_dying_child_process("test::native::test_allocator_failure")? {
+        return Ok(());
+    }
+
+    unsafe extern "C" fn fail_alloc(_: usize, _: usize) -> *mut u8 {
+        std::ptr::null_mut()
+    }
+    unsafe extern "C" fn never_free(_: usize, _: *mut u8, _: usize) {
+        unreachable!("nothing was allocated")
+    }
+
+    let allocator = BytesAllocator {
+        alloc: fail_alloc,
+        free: never_free,
+        ctx: 0,
+    };
+    let mut output = RustBytes {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+        cap: 0,
+    };
+    let config = StylusConfig::default();
+    unsafe { stylus_set_allocator(&allocator) };
+    unsafe { stylus_host_cost_table(config, &mut output) };
+    bail!("the failed allocation returned")
+}
+
+#[test]
+fn test_finish() -> Result<()> {
+    // in finish.wat
+    //     the first byte of the args selects finish_return or finish_revert
+    //     the remaining bytes are passed along as the return data
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/finish.wat", &compile, config)?;
+    let data = random_bytes32();
+
+    let args = [&[0][..], &data[..]].concat();
+    match native.run_main(&args, config, ink)? {
+        UserOutcome::Success(output) => assert_eq!(output, data.to_vec()),
+        outcome => bail!("wrong outcome: {outcome:?}"),
+    }
+
+    let args = [&[1][..], &data[..]].concat();
+    match native.run_main(&args, config, ink)? {
+        UserOutcome::Revert(output) => assert_eq!(output, data.to_vec()),
+        outcome => bail!("wrong outcome: {outcome:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_instrumented() -> Result<()> {
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+
+    let module = native::instrumented(&wasm, CompileConfig::version(1, true))?;
+    let ink = module
+        .exports()
+        .find(|export| export.name() == STYLUS_INK_LEFT);
+    assert!(matches!(
+        ink.map(|x| x.ty().clone()),
+        Some(ExternType::Global(_))
+    ));
+
+    // instrumentation internals aren't exposed outside of debug mode
+    assert!(native::instrumented(&wasm, CompileConfig::version(1, false)).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_count_diff() -> Result<()> {
+    // in add-loop.wat
+    //     each iteration executes a single i32.add and i32.sub
+
+    let mut counts = vec![];
+    for iters in [1, 3] {
+        let mut native = TestInstance::new_test("tests/add-loop.wat", test_compile_config())?;
+        native.run_export("add_loop", &[Value::I32(iters)], u64::MAX)?;
+        counts.push(native.operator_counts()?);
+    }
+
+    let diff = CountDiff::new(&counts[0], &counts[1]);
+    let add = OperatorCode::from(&Operator::I32Add);
+    let sub = OperatorCode::from(&Operator::I32Sub);
+    assert_eq!(diff.changed.get(&add), Some(&2));
+    assert_eq!(diff.changed.get(&sub), Some(&2));
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+    assert!(diff.to_string().contains("~ I32Add +2\n"));
+
+    let reverse = CountDiff::new(&counts[1], &counts[0]);
+    assert_eq!(reverse.changed.get(&add), Some(&-2));
+    assert!(CountDiff::new(&counts[0], &counts[0]).is_empty());
+
+    let mut fewer = counts[0].clone();
+    let removed = fewer.remove(&sub).unwrap();
+    let diff = CountDiff::new(&counts[0], &fewer);
+    assert_eq!(diff.removed.get(&sub), Some(&removed));
+    assert_eq!(
+        CountDiff::new(&fewer, &counts[0]).added.get(&sub),
+        Some(&removed)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_storage_load_bytes() -> Result<()> {
+    // in storage-bytes.wat
+    //     the program returns the 70 bytes stored across the 3 slots following the key in its args
+    //     an optional destination follows the key
+
+    let (compile, config, ink) = test_configs();
+    let (mut native, mut evm) =
+        TestInstance::new_with_evm("tests/storage-bytes.wat", &compile, config)?;
+
+    let blob: Vec<u8> = (0..96).collect();
+    let mut base = Bytes32::default();
+    base[31] = 0xff; // the next slot carries into the adjacent byte
+    for (i, chunk) in blob.chunks(32).enumerate() {
+        let mut key = base;
+        key[31] = key[31].wrapping_add(i as u8);
+        key[30] = (i > 0) as u8;
+        evm.cache_bytes32(key, Bytes32::try_from(chunk)?);
+    }
+
+    let output = run_native(&mut native, &base[..], ink)?;
+    assert_eq!(output, blob[..70]);
+
+    // each slot is charged as an SLOAD, which the test api prices at 2100 gas
+    let MachineMeter::Ready(ink_left) = native.ink_left() else {
+        bail!("out of ink");
+    };
+    let used = ink - ink_left;
+    assert!(used >= config.pricing.gas_to_ink(3 * 2100));
+    assert!(used < config.pricing.gas_to_ink(4 * 2100));
+
+    // a destination past the end of memory fails before any slot is loaded
+    let dest = 0x10000 - 69;
+    let args = [&base[..], &u32::to_le_bytes(dest)].concat();
+    match native.run_main(&args, config, ink)? {
+        UserOutcome::Failure(_) => {}
+        outcome => bail!("expected failure: {outcome:?}"),
+    }
+    let MachineMeter::Ready(ink_left) = native.ink_left() else {
+        bail!("out of ink");
+    };
+    assert!(ink - ink_left < config.pricing.gas_to_ink(2100));
+    Ok(())
+}
+
+#[test]
+fn test_dry_run() -> Result<()> {
+    // in storage-order.wat
+    //     the program writes the value in its args to the key in its args, then returns it
+
+    let (compile, config, ink) = test_configs();
+    let (mut native, mut evm) =
+        TestInstance::new_with_evm("tests/storage-order.wat", &compile, config)?;
+
+    let key = random_bytes32();
+    let value = random_bytes32();
+    let args = [key.0, value.0].concat();
+
+    let ink_used = |native: &mut TestInstance| match native.ink_left() {
+        MachineMeter::Ready(ink_left) => ink - ink_left,
+        MachineMeter::Exhausted => ink,
+    };
+
+    match native.run_dry(&args, config, ink)? {
+        UserOutcome::Success(output) => assert_eq!(output, value.to_vec()),
+        outcome => bail!("dry run failed: {outcome:?}"),
+    }
+    let dry_ink = ink_used(&mut native);
+    assert_eq!(evm.get_bytes32(key).0, Bytes32::default());
+
+    // the dry run charged for the write as a real call would
+    match native.run_main(&args, config, ink)? {
+        UserOutcome::Success(output) => assert_eq!(output, value.to_vec()),
+        outcome => bail!("call failed: {outcome:?}"),
+    }
+    assert_eq!(ink_used(&mut native), dry_ink);
+    assert_eq!(evm.get_bytes32(key).0, value);
+    Ok(())
+}
+
+#[test]
+fn test_activate_and_cache() -> Result<()> {
+    // in exit-early.wat
+    //     the input is returned as the output
+
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let wasm = GoSliceData {
+        ptr: wasm.as_ptr(),
+        len: wasm.len(),
+    };
+    let mut output = RustBytes {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+        cap: 0,
+    };
+    let (mut asm_len, mut module_hash) = (0, Bytes32::default());
+    let mut stylus_data = MaybeUninit::uninit();
+    let mut gas = u64::MAX;
+
+    let status = unsafe {
+        stylus_activate_and_cache(
+            wasm,
+            128,
+            1,
+            true,
+            &mut output,
+            &mut asm_len,
+            &mut module_hash,
+            stylus_data.as_mut_ptr(),
+            &mut gas,
+        )
+    };
+    assert_eq!(status, UserOutcomeKind::Success);
+    unsafe { stylus_drop_vec(output) };
+    assert!(stylus_cache_contains(module_hash, 1, true));
+    assert!(!stylus_cache_contains(module_hash, 1, false));
+
+    // runs from the cache, since the module bytes are left empty
+    let (_, config, ink) = test_configs();
+    let (evm, mut evm_data) = TestEvmApi::new(CompileConfig::version(1, true));
+    evm_data.module_hash = module_hash;
+    let mut native =
+        unsafe { TestInstance::deserialize_cached(&[], 1, evm, evm_data, true, false)? };
+    let args = &[0x00, 0xaa, 0xbb];
+    match native.run_main(args, config, ink)? {
+        UserOutcome::Success(output) => assert_eq!(output, args),
+        outcome => bail!("call failed: {outcome:?}"),
+    }
+
+    InitCache::evict(module_hash, 1, true);
+    assert!(!stylus_cache_contains(module_hash, 1, true));
+    Ok(())
+}
+
+#[test]
+fn test_is_static() -> Result<()> {
+    // in is-static.wat
+    //     the program returns 1 when called in a static context, and 0 otherwise
+
+    let (compile, config, ink) = test_configs();
+    for is_static in [true, false] {
+        let (evm, mut evm_data) = TestEvmApi::new(compile.clone());
+        evm_data.is_static = is_static;
+
+        let mut native =
+            TestInstance::from_path("tests/is-static.wat", evm, evm_data, &compile, config)?;
+        let output = run_native(&mut native, &[], ink)?;
+        assert_eq!(output, vec![is_static as u8]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_init_cache_persistence() -> Result<()> {
+    // in exit-early.wat
+    //     the input is returned as the output
+
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (asm, _, _) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    let (_, config, ink) = test_configs();
+    let args = &[0x00, 0xaa, 0xbb];
+
+    // runs from the cache, since the module bytes are left empty
+    let run = |module_hash: Bytes32| -> Result<Vec<u8>> {
+        let (evm, mut evm_data) = TestEvmApi::new(CompileConfig::version(1, true));
+        evm_data.module_hash = module_hash;
+        let mut native =
+            unsafe { TestInstance::deserialize_cached(&[], 1, evm, evm_data, true, false)? };
+        Ok(native.run_main(args, config, ink)?.into_data().1)
+    };
+
+    let dir = std::env::temp_dir();
+    let mut outputs = vec![];
+    for compression in [None, Some(11)] {
+        let hash = random_bytes32();
+        InitCache::insert(hash, &asm, 1, true)?;
+
+        let path = dir.join(format!("stylus-init-cache-{}", hex::encode(hash)));
+        InitCache::persist(&path, compression)?;
+        InitCache::evict(hash, 1, true);
+        assert!(InitCache::get(hash, 1, true).is_none());
+
+        InitCache::restore(&path)?;
+        std::fs::remove_file(&path)?;
+
+        outputs.push(run(hash)?);
+        InitCache::evict(hash, 1, true);
+    }
+    assert_eq!(outputs[0], args);
+    assert_eq!(outputs[0], outputs[1]);
+    Ok(())
+}
+
+#[test]
+fn test_native_modexp() -> Result<()> {
+    // in modexp.wat
+    //     the program returns base ** exponent % modulus for the operands in its args
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/modexp.wat", &compile, config)?;
+
+    let args = |base: &[u8], exp: &[u8], modulus: &[u8]| {
+        let mut args = vec![];
+        for len in [base.len(), exp.len(), modulus.len()] {
+            args.extend((len as u32).to_le_bytes());
+        }
+        [args, base.to_vec(), exp.to_vec(), modulus.to_vec()].concat()
+    };
+
+    // textbook RSA: encrypting 65 with (n, e) = (3233, 17) yields 2790, which (n, d) = (3233, 2753) decrypts
+    let n = 3233_u16.to_be_bytes();
+    let cipher = run_native(&mut native, &args(&[65], &[17], &n), ink)?;
+    assert_eq!(cipher, 2790_u16.to_be_bytes());
+    let plain = run_native(
+        &mut native,
+        &args(&cipher, &2753_u16.to_be_bytes(), &n),
+        ink,
+    )?;
+    assert_eq!(plain, 65_u16.to_be_bytes());
+
+    // the first vector of EIP-198, which EIP-2565 prices at 1360 gas
+    let exp = hex::decode("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e")?;
+    let modulus = hex::decode("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f")?;
+    assert_eq!(evm::modexp_gas(1, &exp, 32), 1360);
+
+    let output = run_native(&mut native, &args(&[3], &exp, &modulus), ink)?;
+    let mut expected = [0; 32];
+    expected[31] = 1;
+    assert_eq!(output, expected);
+
+    let MachineMeter::Ready(ink_left) = native.ink_left() else {
+        bail!("out of ink");
+    };
+    let used = ink - ink_left;
+    assert!(used >= config.pricing.gas_to_ink(1360));
+    assert!(used < config.pricing.gas_to_ink(1360 + 100));
+
+    // small operations are charged the minimum, and a zero modulus yields zeros
+    assert_eq!(evm::modexp_gas(1, &[17], 2), evm::MODEXP_MIN_GAS);
+    let output = run_native(&mut native, &args(&[7], &[3], &[0, 0]), ink)?;
+    assert_eq!(output, [0, 0]);
+    Ok(())
+}
+
+#[test]
+fn test_ffi_panic() {
+    let mut output = RustBytes {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+        cap: 0,
+    };
+    let status = util::ffi_guard(&mut output, |_| panic!("invariant broken"));
+    assert_eq!(status, UserOutcomeKind::Failure);
+
+    // the panic message is surfaced in the output rather than aborting the process
+    let error = unsafe { std::slice::from_raw_parts(output.ptr, output.len) };
+    let error = String::from_utf8_lossy(error).to_string();
+    unsafe { stylus_drop_vec(output) };
+    assert!(error.contains("panicked: invariant broken"), "{error}");
+    assert!(error.contains("native.rs"), "{error}");
+}
+
+#[test]
+fn test_storage_op_cost() {
+    use StorageOp::*;
+
+    let word = |x: u8| {
+        let mut word = Bytes32::default();
+        word[31] = x;
+        word
+    };
+    let (zero, one, two, three) = (word(0), word(1), word(2), word(3));
+    let cost = |op: StorageOp, cold, original, current, new| {
+        stylus_storage_op_cost(op.into(), cold, original, current, new)
+    };
+
+    // loads only depend on whether the slot is warm (EIP-2929)
+    assert_eq!(cost(Load, true, one, two, three), 2100);
+    assert_eq!(cost(Load, false, one, two, three), 100);
+
+    // no-ops pay for the read (EIP-2200)
+    assert_eq!(cost(Store, false, one, one, one), 100);
+    assert_eq!(cost(Store, true, one, one, one), 2100 + 100);
+
+    // clean slots pay to set or reset
+    assert_eq!(cost(Store, false, zero, zero, one), 20000);
+    assert_eq!(cost(Store, true, zero, zero, one), 2100 + 20000);
+    assert_eq!(cost(Store, false, one, one, two), 2900);
+    assert_eq!(cost(Store, false, one, one, zero), 2900);
+    assert_eq!(cost(Store, true, one, one, zero), 2100 + 2900);
+
+    // dirty slots pay only for the read, even when restored (EIP-3529 changes only refunds)
+    assert_eq!(cost(Store, false, zero, one, two), 100);
+    assert_eq!(cost(Store, false, one, two, one), 100);
+    assert_eq!(cost(Store, false, one, zero, one), 100);
+    assert_eq!(cost(Store, true, one, two, three), 2100 + 100);
+
+    // unknown ops are unaffordable rather than undefined behavior
+    let unknown = stylus_storage_op_cost(2, false, one, one, one);
+    assert_eq!(unknown, u64::MAX);
+}
+
+#[test]
+fn test_output_hint() -> Result<()> {
+    // in write-results.wat
+    //     the return data is set twice, first to half the args and then to all of them
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/write-results.wat", &compile, config)?;
+    let args: Vec<u8> = (0..0x8000).map(|x| x as u8).collect();
+
+    // an accurate hint is filled in place, so the output is never reallocated
+    native.env_mut().outs.reserve_exact(args.len());
+    let reserved = native.env().outs.as_ptr();
+    let output = run_native(&mut native, &args, ink)?;
+    assert_eq!(output, args);
+    assert_eq!(output.as_ptr(), reserved);
+
+    // wrong hints are harmless
+    for hint in [0, 1, args.len() / 2, 2 * args.len()] {
+        native.env_mut().outs.reserve_exact(hint);
+        assert_eq!(run_native(&mut native, &args, ink)?, args);
+    }
+
+    // even ones too large to reserve
+    let wat = std::fs::read("tests/write-results.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (asm, ..) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    let mut evm_data = EvmData::default();
+    evm_data.module_hash = random_bytes32();
+    let req_handler = NativeRequestHandler {
+        handle_request_fptr: no_requests,
+        id: 0,
+    };
+    let mut output = RustBytes {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+        cap: 0,
+    };
+    let mut gas = 1_000_000;
+    let status = unsafe {
+        stylus_call_sized(
+            GoSliceData {
+                ptr: asm.as_ptr(),
+                len: asm.len(),
+            },
+            GoSliceData {
+                ptr: args.as_ptr(),
+                len: args.len(),
+            },
+            config,
+            req_handler,
+            evm_data,
+            true,
+            &mut output,
+            &mut gas,
+            usize::MAX,
+        )
+    };
+    let outs = unsafe { std::slice::from_raw_parts(output.ptr, output.len) }.to_vec();
+    unsafe { stylus_drop_vec(output) };
+    assert_eq!(status, UserOutcomeKind::Success);
+    assert_eq!(outs, args);
+    Ok(())
+}
+
+#[test]
+fn test_streaming_output() -> Result<()> {
+    // in write-results.wat
+    //     the return data is set twice, first to half the args and then to all of them
+
+    /// Collects the latest return data in chunks, rejecting any written past the limit.
+    #[derive(Default)]
+    struct Sunk {
+        chunks: Vec<Vec<u8>>,
+        restarts: usize,
+        writes: usize,
+        limit: usize,
+    }
+
+    unsafe extern "C" fn write(ctx: usize, chunk: RustSlice, restart: bool) -> bool {
+        let sunk = &mut *(ctx as *mut Sunk);
+        if restart {
+            sunk.chunks.clear();
+            sunk.restarts += 1;
+        }
+        let chunk = std::slice::from_raw_parts(chunk.ptr, chunk.len);
+        sunk.chunks.push(chunk.to_vec());
+        sunk.writes += 1;
+        sunk.writes <= sunk.limit
+    }
+
+    let wat = std::fs::read("tests/write-results.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let (_, config, _) = test_configs();
+    let mut gas = u64::MAX;
+    let (asm, ..) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    let calldata: Vec<u8> = (0..0x8000).map(|x| x as u8).collect();
+
+    let call = |chunk_size: usize, limit: usize| -> (UserOutcomeKind, Sunk, Vec<u8>) {
+        let mut sunk = Sunk {
+            limit,
+            ..Sunk::default()
+        };
+        let sink = OutputSink {
+            write,
+            ctx: &mut sunk as *mut _ as usize,
+            chunk_size,
+        };
+        let mut evm_data = EvmData::default();
+        evm_data.module_hash = random_bytes32();
+        let req_handler = NativeRequestHandler {
+            handle_request_fptr: no_requests,
+            id: 0,
+        };
+        let mut output = RustBytes {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+            cap: 0,
+        };
+        let mut gas = 1_000_000;
+        let status = unsafe {
+            stylus_call_streaming(
+                GoSliceData {
+                    ptr: asm.as_ptr(),
+                    len: asm.len(),
+                },
+                GoSliceData {
+                    ptr: calldata.as_ptr(),
+                    len: calldata.len(),
+                },
+                config,
+                req_handler,
+                evm_data,
+                true,
+                sink,
+                &mut output,
+                &mut gas,
+            )
+        };
+        let outs = unsafe { std::slice::from_raw_parts(output.ptr, output.len) }.to_vec();
+        unsafe { stylus_drop_vec(output) };
+        (status, sunk, outs)
+    };
+
+    // each time the return data is set, it arrives in order in chunks of at most the requested size
+    let (status, sunk, outs) = call(1000, usize::MAX);
+    assert_eq!(status, UserOutcomeKind::Success);
+    assert_eq!(sunk.restarts, 2);
+    assert_eq!(sunk.writes, 17 + 33);
+    assert_eq!(sunk.chunks.len(), 33);
+    assert!(sunk.chunks.iter().all(|chunk| chunk.len() <= 1000));
+    assert_eq!(sunk.chunks.concat(), calldata);
+    assert!(outs.is_empty());
+
+    // a rejected chunk fails the program right away, whichever write it's part of
+    for (limit, restarts) in [(3, 1), (20, 2)] {
+        let (status, sunk, outs) = call(1000, limit);
+        assert_eq!(status, UserOutcomeKind::Failure);
+        assert_eq!(sunk.writes, limit + 1);
+        assert_eq!(sunk.restarts, restarts);
+        assert!(String::from_utf8_lossy(&outs).contains("rejected"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_disabled_cache() -> Result<()> {
+    // disabling the cache is permanent, so the checks run in a child process
+    if !in_child_process("test::native::test_disabled_cache")? {
+        return Ok(());
+    }
+
+    let wat = std::fs::read("tests/write-results.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let (_, mut config, _) = test_configs();
+    config.version = 1;
+    let mut gas = u64::MAX;
+    let (asm, ..) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    let module = GoSliceData {
+        ptr: asm.as_ptr(),
+        len: asm.len(),
+    };
+    let calldata = vec![1, 2, 3, 4];
+    let module_hash = random_bytes32();
+
+    unsafe { stylus_cache_module(module, module_hash, 1, true) };
+    assert!(stylus_cache_contains(module_hash, 1, true));
+    stylus_disable_init_cache();
+    assert!(!stylus_cache_contains(module_hash, 1, true));
+
+    // caching silently does nothing
+    unsafe { stylus_cache_module(module, module_hash, 1, true) };
+    assert!(!stylus_cache_contains(module_hash, 1, true));
+    assert_eq!(InitCache::size_bytes(), 0);
+
+    // calls never hit the cache, yet still run correctly
+    for cached in [true, false, true] {
+        let mut evm_data = EvmData::default();
+        evm_data.module_hash = module_hash;
+        evm_data.cached = cached;
+        let req_handler = NativeRequestHandler {
+            handle_request_fptr: no_requests,
+            id: 0,
+        };
+        let mut output = RustBytes {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+            cap: 0,
+        };
+        let mut gas = 1_000_000;
+        let status = unsafe {
+            stylus_call(
+                module,
+                GoSliceData {
+                    ptr: calldata.as_ptr(),
+                    len: calldata.len(),
+                },
+                config,
+                req_handler,
+                evm_data,
+                true,
+                &mut output,
+                &mut gas,
+            )
+        };
+        let outs = unsafe { std::slice::from_raw_parts(output.ptr, output.len) }.to_vec();
+        unsafe { stylus_drop_vec(output) };
+        assert_eq!(status, UserOutcomeKind::Success);
+        assert_eq!(outs, calldata);
+        assert!(!stylus_cache_contains(module_hash, 1, true));
+    }
+    assert!(InitCache::disabled());
+    Ok(())
+}
+
+#[test]
+fn test_max_cached_module_size() -> Result<()> {
+    // the bound applies to the whole process, so the checks run in a child process
+    if !in_child_process("test::native::test_max_cached_module_size")? {
+        return Ok(());
+    }
+
+    let wat = std::fs::read("tests/write-results.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (asm, ..) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    let slice = |data: &[u8]| GoSliceData {
+        ptr: data.as_ptr(),
+        len: data.len(),
+    };
+    let module_hash = random_bytes32();
+    stylus_set_max_cached_module_size(asm.len() as u64);
+
+    // oversized blobs are rejected before being read, so even invalid ones don't panic
+    let blob = vec![0xff; asm.len() + 1];
+    unsafe { stylus_cache_module(slice(&blob), module_hash, 1, true) };
+    assert!(!stylus_cache_contains(module_hash, 1, true));
+    assert_eq!(InitCache::size_bytes(), 0);
+
+    // modules within the bound are cached as usual
+    unsafe { stylus_cache_module(slice(&asm), module_hash, 1, true) };
+    assert!(stylus_cache_contains(module_hash, 1, true));
+
+    // tightening the bound keeps what's cached, while oversized modules remain usable
+    stylus_set_max_cached_module_size(asm.len() as u64 - 1);
+    let other = random_bytes32();
+    InitCache::insert(other, &asm, 1, true)?;
+    InitCache::insert_lru(other, &asm, 1, true)?;
+    assert!(!stylus_cache_contains(other, 1, true));
+    assert!(stylus_cache_contains(module_hash, 1, true));
+
+    // a bound of 0 removes it
+    stylus_set_max_cached_module_size(0);
+    InitCache::insert(other, &asm, 1, true)?;
+    assert!(stylus_cache_contains(other, 1, true));
+    Ok(())
+}
+
+#[test]
+fn test_partial_outs() -> Result<()> {
+    // in partial-outs.wat
+    //     the args are set as the return data before looping until out of ink
+
+    let wat = std::fs::read("tests/partial-outs.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let (_, config, _) = test_configs();
+    let calldata = random_bytes32();
+
+    let call = |debug: bool| -> Result<(UserOutcomeKind, Vec<u8>)> {
+        let mut gas = u64::MAX;
+        let (asm, ..) = native::activate(&wasm, 1, 128, debug, &mut gas)?;
+
+        let mut evm_data = EvmData::default();
+        evm_data.module_hash = random_bytes32();
+        let req_handler = NativeRequestHandler {
+            handle_request_fptr: no_requests,
+            id: 0,
+        };
+        let mut output = RustBytes {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+            cap: 0,
+        };
+        let mut gas = 1_000_000;
+        let status = unsafe {
+            stylus_call(
+                GoSliceData {
+                    ptr: asm.as_ptr(),
+                    len: asm.len(),
+                },
+                GoSliceData {
+                    ptr: calldata.as_ptr(),
+                    len: calldata.len(),
+                },
+                config,
+                req_handler,
+                evm_data,
+                debug,
+                &mut output,
+                &mut gas,
+            )
+        };
+        let outs = unsafe { std::slice::from_raw_parts(output.ptr, output.len) }.to_vec();
+        unsafe { stylus_drop_vec(output) };
+        assert_eq!(gas, 0);
+        Ok((status, outs))
+    };
+
+    // debug mode surfaces the return data set before running out of ink
+    let (status, outs) = call(true)?;
+    assert_eq!(status, UserOutcomeKind::OutOfInk);
+    assert_eq!(outs, calldata);
+
+    // production behavior is unchanged
+    let (status, outs) = call(false)?;
+    assert_eq!(status, UserOutcomeKind::OutOfInk);
+    assert!(outs.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_memory_gas() -> Result<()> {
+    // in memory-gas.wat
+    //     each byte of the args grows memory by that many pages
+    //     the program returns the memory gas charged after each step
+
+    let (compile, config, ink) = test_configs();
+    let (mut native, mut evm) =
+        TestInstance::new_with_evm("tests/memory-gas.wat", &compile, config)?;
+    evm.set_pages(1);
+
+    let steps = [8; 8];
+    let output = run_native(&mut native, &steps, ink)?;
+    let charged: Vec<u64> = output
+        .chunks_exact(8)
+        .map(|x| u64::from_le_bytes(x.try_into().unwrap()))
+        .collect();
+
+    // the test api prices memory with 2 free pages and 1000 gas per page
+    let model = MemoryModel::new(2, 1000);
+    let (mut open, mut total) = (1, 0);
+    for (step, charged) in steps.into_iter().zip(&charged) {
+        total += model.gas_cost(step.into(), open, open);
+        open += u16::from(step);
+        assert_eq!(*charged, total);
+    }
+
+    // each step costs more than the last
+    let costs: Vec<_> = [0].iter().chain(&charged).collect();
+    let costs: Vec<_> = costs.windows(2).map(|x| x[1] - x[0]).collect();
+    assert!(costs.windows(2).all(|x| x[0] < x[1]), "{costs:?}");
+
+    let MachineMeter::Ready(ink_left) = native.ink_left() else {
+        bail!("out of ink");
+    };
+    assert!(ink - ink_left >= config.pricing.gas_to_ink(total));
+    Ok(())
+}
+
+#[test]
+fn test_record_replay() -> Result<()> {
+    // in replay.wat
+    //     the program loads the slot named in its args, then passes its value to the contract
+    //     named in its args, returning the contract's return data
+
+    /// Stands in for Go, which echoes calldata in reverse.
+    struct Geth {
+        slots: HashMap<Bytes32, Bytes32>,
+    }
+
+    impl RequestHandler<VecReader> for Geth {
+        fn request(
+            &mut self,
+            req_type: EvmApiMethod,
+            req_data: impl AsRef<[u8]>,
+        ) -> (Vec<u8>, VecReader, u64) {
+            let data = req_data.as_ref();
+            match req_type {
+                EvmApiMethod::GetBytes32 => {
+                    let key = Bytes32::try_from(data).unwrap();
+                    (self.slots[&key].to_vec(), VecReader::new(vec![]), 2100)
+                }
+                EvmApiMethod::ContractCall => {
+                    let mut input = data[20 + 32 + 8..].to_vec();
+                    input.reverse();
+                    let status = vec![UserOutcomeKind::Success.into()];
+                    (status, VecReader::new(input), 1000)
+                }
+                EvmApiMethod::Checkpoint | EvmApiMethod::Rollback => {
+                    (vec![], VecReader::new(vec![]), 0)
+                }
+                _ => unimplemented!("{req_type:?}"),
+            }
+        }
+    }
+
+    let (compile, config, ink) = test_configs();
+    let (key, value) = (random_bytes32(), random_bytes32());
+    let args = [&key[..], &random_bytes20()[..]].concat();
+    let mut expected = value.to_vec();
+    expected.reverse();
+
+    let geth = Geth {
+        slots: HashMap::from([(key, value)]),
+    };
+    let evm_api = EvmApiRequestor::new(Recorder::new(geth, true));
+    let mut native = NativeInstance::from_path(
+        "tests/replay.wat",
+        evm_api,
+        EvmData::default(),
+        &compile,
+        config,
+    )?;
+    let UserOutcome::Success(output) = native.run_main(&args, config, ink)? else {
+        bail!("recorded call failed");
+    };
+    assert_eq!(output, expected);
+    let ink_left = native.ink_left();
+
+    // save the trace, then reproduce the call with only the recording
+    let trace = native.env_mut().evm_api.request_handler().take_trace();
+    let trace = trace.expect("not recording");
+    assert_eq!(trace.requests.len(), 2);
+
+    let path = std::env::temp_dir().join(format!("stylus-trace-{}", hex::encode(key)));
+    trace.save(&path)?;
+    let trace = Trace::load(&path)?;
+    let bytes = trace.to_bytes();
+    std::fs::remove_file(&path)?;
+
+    let evm_api = EvmApiRequestor::new(Replayer::new(trace));
+    let mut native = NativeInstance::from_path(
+        "tests/replay.wat",
+        evm_api,
+        EvmData::default(),
+        &compile,
+        config,
+    )?;
+    let UserOutcome::Success(replayed) = native.run_main(&args, config, ink)? else {
+        bail!("replayed call failed");
+    };
+    assert_eq!(replayed, output);
+    assert_eq!(native.ink_left(), ink_left);
+    assert_eq!(native.env_mut().evm_api.request_handler().remaining(), 0);
+
+    // corrupted traces are rejected, even when claiming more requests than could ever fit
+    assert!(Trace::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    let huge = [&bytes[..1], &u32::MAX.to_be_bytes()].concat();
+    assert!(Trace::from_bytes(&huge).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_unreachable() -> Result<()> {
+    // in unreachable.wat
+    //     both the `start` function and the entrypoint hit `unreachable`
+
+    let filename = "tests/unreachable.wat";
+    let (compile, config, ink) = test_configs();
+
+    fn check(outcome: UserOutcome) {
+        match outcome {
+            UserOutcome::Failure(error) => assert_eq!(error.to_string(), UNREACHABLE_ERROR),
+            outcome => panic!("wrong outcome: {outcome:?}"),
+        }
+    }
+
+    // since instrumentation moves `start`, both run as calls with the same outcome
+    let mut native = TestInstance::new_linked(filename, &compile, config)?;
+    check(native.run_main(&[], config, ink)?);
+    check(native.run_start(ink)?);
+
+    // uninstrumented, `start` runs during instantiation, which fails rather than producing an outcome
+    assert!(TestInstance::new_vanilla(filename).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_activated_count() -> Result<()> {
+    // unique programs, so that concurrent tests can't have activated them already
+    let program = || {
+        let status = rand::random::<u32>();
+        let wat = format!(
+            r#"(module
+                (memory (export "memory") 0 0)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    i32.const {status}))"#
+        );
+        wasmer::wat2wasm(wat.as_bytes()).map(|x| x.to_vec())
+    };
+    let activate = |wasm: &[u8]| {
+        let wasm = GoSliceData {
+            ptr: wasm.as_ptr(),
+            len: wasm.len(),
+        };
+        let mut output = RustBytes {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+            cap: 0,
+        };
+        let (mut asm_len, mut module_hash) = (0, Bytes32::default());
+        let mut stylus_data = MaybeUninit::uninit();
+        let mut gas = u64::MAX;
+        let status = unsafe {
+            stylus_activate(
+                wasm,
+                128,
+                1,
+                true,
+                &mut output,
+                &mut asm_len,
+                &mut module_hash,
+                stylus_data.as_mut_ptr(),
+                &mut gas,
+            )
+        };
+        assert_eq!(status, UserOutcomeKind::Success);
+        unsafe { stylus_drop_vec(output) };
+        module_hash
+    };
+
+    let (first, second) = (program()?, program()?);
+    let before = stylus_activated_count();
+    let hashes = [activate(&first), activate(&second), activate(&first)];
+    let after = stylus_activated_count();
+    assert_ne!(hashes[0], hashes[1]);
+    assert_eq!(hashes[0], hashes[2]);
+
+    // other tests may activate programs concurrently, but only ever add to the count
+    let ours: HashSet<_> = hashes.into_iter().collect();
+    assert_eq!(ours.len(), 2);
+    assert!(ours.iter().all(|hash| ACTIVATED.lock().contains(hash)));
+    assert!(after >= before + 2);
+    Ok(())
+}
+
+#[test]
+fn test_block_coinbase_and_prevrandao() -> Result<()> {
+    // in block-data.wat
+    //     the program returns the block's coinbase followed by its prevrandao
+
+    let (compile, config, ink) = test_configs();
+    let (evm, mut evm_data) = TestEvmApi::new(compile.clone());
+    evm_data.block_coinbase = random_bytes20();
+    evm_data.block_prevrandao = random_bytes32();
+
+    let mut native =
+        TestInstance::from_path("tests/block-data.wat", evm, evm_data, &compile, config)?;
+    let output = run_native(&mut native, &[], ink)?;
+    assert_eq!(output[..20], evm_data.block_coinbase[..]);
+    assert_eq!(output[20..], evm_data.block_prevrandao[..]);
+    Ok(())
+}
+
+#[test]
+fn test_store_pool() -> Result<()> {
+    // in exit-early.wat
+    //     the input is returned as the output
+
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (asm, _, _) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    let (_, config, ink) = test_configs();
+
+    let hash = random_bytes32();
+    InitCache::insert(hash, &asm, 1, true)?;
+
+    let run = |args: &[u8]| -> Result<(Vec<u8>, u64)> {
+        let (evm, mut evm_data) = TestEvmApi::new(CompileConfig::version(1, true));
+        evm_data.module_hash = hash;
+        let mut native =
+            unsafe { TestInstance::deserialize_cached(&[], 1, evm, evm_data, true, false)? };
+        let output = native.run_main(args, config, ink)?.into_data().1;
+        let ink_left = native.ink_left().into();
+        native.recycle();
+        Ok((output, ink_left))
+    };
+
+    let calls: Vec<_> = (0..8_u8)
+        .map(|i| vec![0x00, i, i.wrapping_mul(3)])
+        .collect();
+    let unpooled: Vec<_> = calls.iter().map(|args| run(args)).collect::<Result<_>>()?;
+
+    // stores are reused across calls, and discarded after 3 uses
+    InitCache::set_store_pool(Some(StorePoolConfig {
+        capacity: 2,
+        max_uses: 3,
+    }));
+    let pooled: Vec<_> = calls.iter().map(|args| run(args)).collect::<Result<_>>()?;
+    InitCache::set_store_pool(None);
+    InitCache::evict(hash, 1, true);
+
+    assert_eq!(pooled, unpooled);
+    for ((output, _), args) in pooled.iter().zip(&calls) {
+        assert_eq!(output, args);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_instantiation_cost() -> Result<()> {
+    // the budget is generous, catching only large regressions in the time instantiation takes
+    const BUDGET: u64 = 1_000_000;
+
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (asm, ..) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    let (_, config, ink) = test_configs();
+    let hash = random_bytes32();
+
+    // the first instantiation deserializes the module, after which it's cached
+    for cached in [false, true] {
+        let (evm, mut evm_data) = TestEvmApi::new(CompileConfig::version(1, true));
+        evm_data.module_hash = hash;
+        evm_data.cached = true;
+        let (mut native, cost) = unsafe {
+            TestInstance::deserialize_cached_measured(&asm, 1, evm, evm_data, true, false)?
+        };
+        assert_eq!(cost.cached, cached);
+        assert!(cost.nanos > 0);
+        assert!(cost.gas <= BUDGET, "instantiation cost {} gas", cost.gas);
+        assert_eq!(cost, InstantiationCost::new(cost.nanos, cached));
+
+        // execution isn't included
+        let args = vec![0x00, 0x01];
+        assert_eq!(native.run_main(&args, config, ink)?.into_data().1, args);
+    }
+    InitCache::evict(hash, 1, true);
+
+    // time converts to gas at the speed limit
+    assert_eq!(InstantiationCost::new(1_000_000_000, false).gas, 7_000_000);
+    assert_eq!(
+        InstantiationCost::new(u64::MAX, false).gas,
+        129127208515966861
+    );
+    Ok(())
+}
+
+#[test]
+fn test_modules_equal() -> Result<()> {
+    let activate = |path: &str| -> Result<Vec<u8>> {
+        let wat = std::fs::read(path)?;
+        let wasm = wasmer::wat2wasm(&wat)?;
+        let mut gas = u64::MAX;
+        Ok(native::activate(&wasm, 1, 128, true, &mut gas)?.0)
+    };
+    let slice = |asm: &[u8]| GoSliceData {
+        ptr: asm.as_ptr(),
+        len: asm.len(),
+    };
+
+    // activating the same wasm twice produces equivalent modules
+    let first = activate("tests/exit-early/exit-early.wat")?;
+    let second = activate("tests/exit-early/exit-early.wat")?;
+    assert!(stylus_modules_equal(slice(&first), slice(&second), 1));
+
+    // different programs differ, and invalid asm equals nothing
+    let other = activate("tests/memory.wat")?;
+    assert!(!stylus_modules_equal(slice(&first), slice(&other), 1));
+    assert!(!stylus_modules_equal(slice(&first), slice(&[0xff; 8]), 1));
+
+    // programs with the same interface but different code differ too
+    let returning = |status: u8| -> Result<Vec<u8>> {
+        let wat = format!(
+            r#"(module
+                (memory (export "memory") 0 0)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    i32.const {status}))"#
+        );
+        let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+        let mut gas = u64::MAX;
+        Ok(native::activate(&wasm, 1, 128, true, &mut gas)?.0)
+    };
+    let (zero, one) = (returning(0)?, returning(1)?);
+    assert!(stylus_modules_equal(slice(&zero), slice(&returning(0)?), 1));
+    assert!(!stylus_modules_equal(slice(&zero), slice(&one), 1));
+    Ok(())
+}
+
+#[test]
+fn test_injected_globals() -> Result<()> {
+    let wat = std::fs::read("tests/exit-early/exit-early.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+    let (module, ..) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+
+    let globals = native::injected_globals(&module, 1)?;
+    for name in [STYLUS_INK_LEFT, STYLUS_INK_STATUS, STYLUS_STACK_LEFT] {
+        assert!(globals.contains(&name.to_owned()), "missing {name}");
+    }
+    assert!(globals.windows(2).all(|w| w[0] < w[1]));
+    assert!(native::injected_globals(&[0xff; 8], 1).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_math_wrap() -> Result<()> {
+    // in math-wrap.wat
+    //     the args are two 256-bit words a and b, and the output is a + b followed by a * b
+
+    let (compile, config, ink) = test_configs();
+    let word = |high: u8, low: u8| {
+        let mut word = [0; 32];
+        word[0] = high;
+        word[31] = low;
+        word
+    };
+    let max = [0xff; 32];
+    let mut max_less_one = max;
+    max_less_one[31] = 0xfe;
+
+    // like the EVM's ADD and MUL, overflow wraps modulo 2^256
+    let cases = [
+        (word(0, 7), word(0, 9), word(0, 16), word(0, 63)),
+        (max, max, max_less_one, word(0, 1)),
+        (word(0x80, 3), word(0, 6), word(0x80, 9), word(0, 18)),
+        (max, word(0, 1), word(0, 0), max),
+    ];
+    for (a, b, sum, product) in cases {
+        let mut native = TestInstance::new_linked("tests/math-wrap.wat", &compile, config)?;
+        let output = run_native(&mut native, &[a, b].concat(), ink)?;
+        assert_eq!(output, [sum, product].concat());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_batch_activate() -> Result<()> {
+    let mut wasms = vec![];
+    for file in [
+        "tests/exit-early/exit-early.wat",
+        "tests/memory.wat",
+        "tests/modexp.wat",
+        "tests/sha256.wat",
+        "tests/ripemd160.wat",
+    ] {
+        wasms.push(wasmer::wat2wasm(&std::fs::read(file)?)?.to_vec());
+    }
+    let wasms: Vec<&[u8]> = wasms.iter().map(Vec::as_slice).collect();
+
+    // one program gets too little gas, which mustn't affect the others
+    let budgets = [u64::MAX, u64::MAX, 0, u64::MAX, u64::MAX];
+    let mut gas = budgets;
+    let batch = native::batch_activate(&wasms, 2, 128, true, &mut gas, 3);
+    assert_eq!(batch.len(), wasms.len());
+
+    for (i, result) in batch.into_iter().enumerate() {
+        let mut serial_gas = budgets[i];
+        let serial = native::activate(wasms[i], 2, 128, true, &mut serial_gas);
+        assert_eq!(gas[i], serial_gas);
+        assert_eq!(result.is_err(), i == 2);
+
+        match (result, serial) {
+            (Ok((asm, module, data)), Ok((serial_asm, serial_module, serial_data))) => {
+                assert_eq!(asm, serial_asm);
+                assert_eq!(module.hash(), serial_module.hash());
+                assert_eq!(format!("{data:?}"), format!("{serial_data:?}"));
+            }
+            (Err(err), Err(serial_err)) => assert_eq!(err.to_string(), serial_err.to_string()),
+            (result, serial) => bail!("{i}: {:?} != {:?}", result.is_ok(), serial.is_ok()),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_check_page_limit() -> Result<()> {
+    let program = |memory: &str| -> Result<Vec<u8>> {
+        let wat = format!(
+            "(module (memory (export \"memory\") {memory})
+                (func (export \"user_entrypoint\") (param i32) (result i32) i32.const 0))"
+        );
+        Ok(wasmer::wat2wasm(wat.as_bytes())?.to_vec())
+    };
+    let activate = |wasm: &[u8]| {
+        let mut gas = u64::MAX;
+        native::activate(wasm, 1, 128, true, &mut gas)
+    };
+
+    // programs whose memory starts out too large are rejected, as activation would
+    let large = program("129 129")?;
+    let err = ProverModule::check_page_limit(&large, 128).unwrap_err();
+    assert!(err.to_string().contains("memory exceeds limit"), "{err}");
+    assert!(activate(&large).is_err());
+
+    // so are those declaring a larger maximum, even though activation only bounds the initial size
+    let growable = program("1 1000")?;
+    let err = ProverModule::check_page_limit(&growable, 128).unwrap_err();
+    assert!(err.to_string().contains("memory exceeds limit"), "{err}");
+    activate(&growable)?;
+
+    // memories within the limit pass, those without a maximum bounded by their initial size
+    for memory in ["128", "0", "1 128"] {
+        let wasm = program(memory)?;
+        ProverModule::check_page_limit(&wasm, 128)?;
+        activate(&wasm)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_activation_gas_floor() -> Result<()> {
+    let activation_cost = |wasm: &[u8]| -> Result<u64> {
+        let mut gas = u64::MAX;
+        native::activate(wasm, 2, 128, true, &mut gas)?;
+        Ok(u64::MAX - gas)
+    };
+
+    for file in [
+        "tests/exit-early/exit-early.wat",
+        "tests/memory.wat",
+        "tests/modexp.wat",
+    ] {
+        let wat = std::fs::read(file)?;
+        let wasm = wasmer::wat2wasm(&wat)?;
+        let floor = ProverModule::activation_gas_floor(&wasm)?;
+        let cost = activation_cost(&wasm)?;
+        assert!(floor <= cost, "{file}: floor {floor} exceeds cost {cost}");
+
+        // a borderline budget still activates
+        let mut gas = cost;
+        native::activate_bounded(&wasm, 2, 128, true, &mut gas)?;
+        assert_eq!(gas, 0);
+
+        // budgets below the floor are rejected early, without charging any gas
+        if floor > 0 {
+            let mut gas = floor - 1;
+            let err = native::activate_bounded(&wasm, 2, 128, true, &mut gas).unwrap_err();
+            assert!(err.to_string().contains("activation requires at least"));
+            assert_eq!(gas, floor - 1);
+        }
+    }
+
+    // the floor is computed without validation, so invalid wasm with a large floor is rejected early
+    let wat = "(module (memory 1000) (func (export \"user_entrypoint\") (result i32) i64.const 0))";
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+    let mut gas = 1_000_000;
+    let err = native::activate_bounded(&wasm, 2, 128, true, &mut gas).unwrap_err();
+    assert!(err.to_string().contains("activation requires at least"));
+    Ok(())
+}
+
+#[test]
+fn test_precompile() -> Result<()> {
+    // a unique data segment keeps other tests from activating the precompiled wasm
+    let wat = format!(
+        r#"(module
+            (func (export "user_entrypoint") (param i32) (result i32) i32.const 0)
+            (data (i32.const 0) "{}")
+            (memory (export "memory") 1 1))"#,
+        random_bytes32()
+    );
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+    let precompile = |wasm: &[u8], version: u16| {
+        let mut output = RustBytes {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+            cap: 0,
+        };
+        let slice = GoSliceData {
+            ptr: wasm.as_ptr(),
+            len: wasm.len(),
+        };
+        let status = unsafe { stylus_precompile(slice, version, true, &mut output) };
+        let outs = unsafe { std::slice::from_raw_parts(output.ptr, output.len) }.to_vec();
+        unsafe { stylus_drop_vec(output) };
+        (status, String::from_utf8_lossy(&outs).to_string())
+    };
+    let activate = |debug: bool| -> Result<Vec<u8>> {
+        let mut gas = u64::MAX;
+        Ok(native::activate(&wasm, 1, 128, debug, &mut gas)?.0)
+    };
+
+    assert_eq!(precompile(&wasm, 1).0, UserOutcomeKind::Success);
+    assert!(CompileCache::contains(&wasm, 1, true));
+
+    // activation hits the cache, producing the same program as compiling afresh
+    let before = CompileCache::stats();
+    let asm = activate(true)?;
+    assert_eq!(CompileCache::stats().hits, before.hits + 1);
+    let fresh = native::module(&wasm, CompileConfig::version(1, true))?;
+    assert!(native::modules_equal(&asm, &fresh, 1));
+
+    // the debug flag is part of the key
+    let before = CompileCache::stats();
+    activate(false)?;
+    assert_eq!(CompileCache::stats().hits, before.hits);
+
+    // invalidating the program drops its asm, so activation misses and compiles it again
+    let mut gas = u64::MAX;
+    let (_, module, _) = native::activate(&wasm, 1, 128, true, &mut gas)?;
+    let slice = GoSliceData {
+        ptr: wasm.as_ptr(),
+        len: wasm.len(),
+    };
+    stylus_invalidate_module(slice, module.hash(), 1, true);
+    assert!(!CompileCache::contains(&wasm, 1, true));
+    let before = CompileCache::stats();
+    activate(true)?;
+    assert!(CompileCache::stats().misses > before.misses);
+
+    // failures are reported rather than cached
+    let (status, error) = precompile(b"not wasm", 1);
+    assert_eq!(status, UserOutcomeKind::Failure);
+    assert!(error.contains("failed to precompile"), "{error}");
+    let (status, error) = precompile(&wasm, u16::MAX);
+    assert_eq!(status, UserOutcomeKind::Failure);
+    assert!(error.contains("unsupported Stylus version"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn test_activation_gas_bounds() -> Result<()> {
+    let activation_cost = |wasm: &[u8]| -> Result<u64> {
+        let mut gas = u64::MAX;
+        native::activate(wasm, 2, 128, true, &mut gas)?;
+        Ok(u64::MAX - gas)
+    };
+    let check = |name: &str, wasm: &[u8]| -> Result<()> {
+        let bounds = ProverModule::activation_gas_bounds(wasm)?;
+        let cost = activation_cost(wasm)?;
+        assert!(
+            bounds.contains(&cost),
+            "{name}: {bounds:?} doesn't contain {cost}"
+        );
+        assert_eq!(*bounds.start(), ProverModule::activation_gas_floor(wasm)?);
+        Ok(())
+    };
+
+    for file in [
+        "tests/add.wat",
+        "tests/bulk-memory-oob.wat",
+        "tests/exit-early/exit-early.wat",
+        "tests/grow-thrice.wat",
+        "tests/memory.wat",
+        "tests/modexp.wat",
+    ] {
+        let wat = std::fs::read(file)?;
+        check(file, &wasmer::wat2wasm(&wat)?)?;
+    }
+
+    // elements count towards the upper bound
+    let wat = r#"
+        (module
+            (table 3 funcref)
+            (elem (i32.const 0) $f $f $f)
+            (func $f)
+            (func (export "user_entrypoint") (param i32) (result i32) i32.const 0)
+            (memory (export "memory") 1 1))"#;
+    check("elements", &wasmer::wat2wasm(wat.as_bytes())?)
+}
+
+#[test]
+fn test_data_segments() -> Result<()> {
+    // in data-segments.wat
+    //     there are two active segments and a passive one between them
+
+    let wat = std::fs::read("tests/data-segments.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let segments = native::data_segments(&wasm)?;
+
+    let segment = |offset, data: &[u8]| DataSegment {
+        offset,
+        data: data.to_vec(),
+    };
+    let expected = [
+        segment(Some(0x10), b"stylus"),
+        segment(None, b"passive\0bytes"),
+        segment(Some(0x400), &[0xde, 0xad, 0xbe, 0xef]),
+    ];
+    assert_eq!(segments, expected);
+    Ok(())
+}
+
+#[test]
+fn test_arb_block_numbers() -> Result<()> {
+    // in block-numbers.wat
+    //     the program returns the L1 block number followed by the L2 one, in little endian
+
+    let (compile, config, ink) = test_configs();
+    let (evm, mut evm_data) = TestEvmApi::new(compile.clone());
+    evm_data.block_number = 19_000_000;
+    evm_data.l2_block_number = 200_000_000;
+
+    let mut native =
+        TestInstance::from_path("tests/block-numbers.wat", evm, evm_data, &compile, config)?;
+    let output = run_native(&mut native, &[], ink)?;
+    assert_eq!(output[..8], 19_000_000_u64.to_le_bytes());
+    assert_eq!(output[8..], 200_000_000_u64.to_le_bytes());
+    Ok(())
+}
+
+#[test]
+fn test_effective_gas_price() -> Result<()> {
+    // in effective-gas-price.wat
+    //     the program returns the effective gas price followed by the tx gas price
+
+    let (compile, config, ink) = test_configs();
+    let gwei = |x: u64| {
+        let mut word = [0; 32];
+        word[16..].copy_from_slice(&(x as u128 * 1_000_000_000).to_be_bytes());
+        Bytes32(word)
+    };
+
+    let mut check = |basefee: u64, fee_cap: u64, tip_cap: u64, expected: u64| -> Result<()> {
+        let (evm, mut evm_data) = TestEvmApi::new(compile.clone());
+        evm_data.block_basefee = gwei(basefee);
+        evm_data.tx_gas_fee_cap = gwei(fee_cap);
+        evm_data.tx_gas_tip_cap = gwei(tip_cap);
+        evm_data.tx_gas_price = gwei(7);
+
+        let path = "tests/effective-gas-price.wat";
+        let mut native = TestInstance::from_path(path, evm, evm_data, &compile, config)?;
+        let output = run_native(&mut native, &[], ink)?;
+        assert_eq!(output[..32], *gwei(expected));
+        assert_eq!(output[32..], *gwei(7));
+        Ok(())
+    };
+
+    // the tip is paid in full when the max fee allows
+    check(100, 150, 20, 120)?;
+    check(100, 120, 20, 120)?;
+
+    // otherwise the max fee caps the price
+    check(100, 150, 80, 150)?;
+    check(100, 100, 20, 100)?;
+
+    // without a tip only the basefee is paid
+    check(100, 150, 0, 100)
+}
+
+#[test]
+fn test_chain_param() -> Result<()> {
+    // in chain-param.wat
+    //     the args are a little-endian param id whose little-endian value is returned
+
+    let (compile, config, ink) = test_configs();
+    let (mut evm, evm_data) = TestEvmApi::new(compile.clone());
+    evm.set_chain_param(ChainParam::MaxCodeSize, 24576);
+    evm.set_chain_param(ChainParam::GenesisBlockNum, 22207817);
+
+    let mut native =
+        TestInstance::from_path("tests/chain-param.wat", evm, evm_data, &compile, config)?;
+    let mut param = |id: u32| -> Result<u64> {
+        let output = run_native(&mut native, &id.to_le_bytes(), ink)?;
+        Ok(u64::from_le_bytes(output.try_into().unwrap()))
+    };
+
+    assert_eq!(param(ChainParam::MaxCodeSize.into())?, 24576);
+    assert_eq!(param(ChainParam::GenesisBlockNum.into())?, 22207817);
+
+    // unset and unknown params read as zero
+    assert_eq!(param(ChainParam::MaxInitCodeSize.into())?, 0);
+    assert_eq!(param(1000)?, 0);
+    assert_eq!(param(u32::MAX)?, 0);
+    Ok(())
+}
+
+#[test]
+fn test_tx_origin() -> Result<()> {
+    // in tx-origin.wat
+    //     the program returns the tx origin followed by the msg sender
+
+    let (compile, config, ink) = test_configs();
+    let filename = "tests/tx-origin.wat";
+
+    let (evm, mut evm_data) = TestEvmApi::new(compile.clone());
+    evm_data.tx_origin = random_bytes20();
+    evm_data.msg_sender = random_bytes20();
+    assert_ne!(evm_data.tx_origin, evm_data.msg_sender);
+
+    let mut native = TestInstance::from_path(filename, evm, evm_data, &compile, config)?;
+    let output = run_native(&mut native, &[], ink)?;
+    assert_eq!(output[..20], evm_data.tx_origin[..]);
+    assert_eq!(output[20..], evm_data.msg_sender[..]);
+    Ok(())
+}
+
+#[test]
+fn test_keccak256_pair() -> Result<()> {
+    // in keccak-pair.wat
+    //     the args are two words whose concatenated hash is returned
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/keccak-pair.wat", &compile, config)?;
+    let mut hash = |left: Bytes32, right: Bytes32| -> Result<Vec<u8>> {
+        run_native(&mut native, &[left.0, right.0].concat(), ink)
+    };
+
+    // the empty node of keccak sparse Merkle trees
+    let zero = hex::decode("ad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5")?;
+    assert_eq!(hash(Bytes32::default(), Bytes32::default())?, zero);
+
+    for _ in 0..4 {
+        let (left, right) = (random_bytes32(), random_bytes32());
+        let expected = crypto::keccak([left.0, right.0].concat());
+        assert_eq!(hash(left, right)?, expected);
+        assert_ne!(hash(right, left)?, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_l1_sender() -> Result<()> {
+    // in l1-sender.wat
+    //     the program returns the L1 sender followed by the msg sender
+
+    let (compile, config, ink) = test_configs();
+    let filename = "tests/l1-sender.wat";
+
+    for l1_sender in [random_bytes20(), Bytes20::default()] {
+        let (evm, mut evm_data) = TestEvmApi::new(compile.clone());
+        evm_data.l1_sender = l1_sender;
+        evm_data.msg_sender = random_bytes20();
+
+        let mut native = TestInstance::from_path(filename, evm, evm_data, &compile, config)?;
+        let output = run_native(&mut native, &[], ink)?;
+        assert_eq!(output[..20], l1_sender[..]);
+        assert_eq!(output[20..], evm_data.msg_sender[..]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_instantiation_timeout() -> Result<()> {
+    let compile = test_compile_config();
+    let wat = std::fs::read("tests/slow-instantiation.wat")?;
+    let asm = Module::new(&compile.store(), wat)?.serialize()?;
+
+    // a tight timeout aborts instantiation rather than waiting it out
+    let timeout = Duration::from_nanos(1);
+    let start = Instant::now();
+    let err = native::check_instantiation(&asm, &compile, timeout).unwrap_err();
+    assert!(err.to_string().contains("instantiation exceeded"), "{err}");
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    // generous timeouts succeed
+    native::check_instantiation(&asm, &compile, Duration::from_secs(60))?;
+
+    // over FFI, timeouts are reported as failures rather than panics
+    let mut output = RustBytes {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+        cap: 0,
+    };
+    let module = GoSliceData {
+        ptr: asm.as_ptr(),
+        len: asm.len(),
+    };
+    let (version, debug) = (compile.version, compile.debug.debug_funcs);
+    let status = unsafe { stylus_check_instantiation(module, version, debug, 0, &mut output) };
+    let error = unsafe { std::slice::from_raw_parts(output.ptr, output.len) };
+    let error = String::from_utf8_lossy(error).into_owned();
+    unsafe { stylus_drop_vec(output) };
+    assert_eq!(status, UserOutcomeKind::Failure);
+    assert!(error.contains("instantiation exceeded"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn test_args_view() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/sha256.wat", &compile, config)?;
+    assert!(native.env().args().is_empty());
+
+    let calldata = random_bytes32();
+    run_native(&mut native, &calldata, ink)?;
+
+    // the view borrows the stored calldata rather than copying it
+    let env = native.env();
+    assert_eq!(env.args(), calldata);
+    assert_eq!(env.args().as_ptr(), env.args.as_ptr());
+    Ok(())
+}
+
+#[test]
+fn test_debug_trace() -> Result<()> {
+    // in debug-trace.wat
+    //     the program traces the values 21000 and u64::MAX, tagged "gas" and "slot"
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/debug-trace.wat", &compile, config)?;
+
+    let expected = vec![("gas".to_owned(), 21000), ("slot".to_owned(), u64::MAX)];
+    run_native(&mut native, &[], ink)?;
+    assert_eq!(native.env().debug_traces, expected);
+
+    // entries are collected per call
+    run_native(&mut native, &[], ink)?;
+    assert_eq!(native.env().debug_traces, expected);
+    Ok(())
+}
+
+#[test]
+fn test_memory_grow_limit() -> Result<()> {
+    // in grow-thrice.wat
+    //     memory grows by 1 page three times, after a zero-page grow
+
+    let (compile, mut config, ink) = test_configs();
+    for (limit, allowed) in [(0, true), (3, true), (2, false)] {
+        let mut native = TestInstance::new_linked("tests/grow-thrice.wat", &compile, config)?;
+        config.max_memory_grows = limit;
+
+        match native.run_main(&[], config, ink)? {
+            UserOutcome::Success(_) if allowed => assert_eq!(native.memory_size(), Pages(3)),
+            UserOutcome::Failure(err) if !allowed => {
+                assert!(format!("{err:?}").contains("too many memory grows"));
+                assert_eq!(native.memory_size(), Pages(2));
+            }
+            outcome => bail!("unexpected outcome with limit {limit}: {outcome:?}"),
+        }
+    }
+
+    // in grow-past-max.wat
+    //     a grow past the max fails before a grow within it succeeds
+
+    config.max_memory_grows = 1;
+    let mut native = TestInstance::new_linked("tests/grow-past-max.wat", &compile, config)?;
+    run_native(&mut native, &[], ink)?;
+    assert_eq!(native.memory_size(), Pages(1));
+    Ok(())
+}
+
+#[test]
+fn test_memory_growths() -> Result<()> {
+    // in grow-thrice.wat
+    //     memory grows by 1 page three times, after a zero-page grow
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/grow-thrice.wat", &compile, config)?;
+    run_native(&mut native, &[], ink)?;
+    assert_eq!(native.env().memory_growths, None);
+
+    let mut native = TestInstance::new_linked("tests/grow-thrice.wat", &compile, config)?;
+    native.record_memory_growths()?;
+    run_native(&mut native, &[], ink)?;
+
+    // zero-page grows aren't recorded
+    let growths = native.env().memory_growths.clone().unwrap();
+    let expected: Vec<_> = (1..=3)
+        .map(|total| MemoryGrowth { delta: 1, total })
+        .collect();
+    assert_eq!(growths, expected);
+    assert_eq!(native.memory_size(), Pages(3));
+
+    // in grow-past-max.wat
+    //     memory grows by 1 page twice, around a grow past the max that fails
+    let mut native = TestInstance::new_linked("tests/grow-past-max.wat", &compile, config)?;
+    native.record_memory_growths()?;
+    run_native(&mut native, &[], ink)?;
+
+    // failed grows aren't recorded
+    let growths = native.env().memory_growths.clone().unwrap();
+    let expected: Vec<_> = (1..=2)
+        .map(|total| MemoryGrowth { delta: 1, total })
+        .collect();
+    assert_eq!(growths, expected);
+    assert_eq!(native.memory_size(), Pages(2));
+
+    // recording is only for debug mode
+    let mut compile = compile;
+    compile.debug.debug_funcs = false;
+    let mut native = TestInstance::new_linked("tests/grow-thrice.wat", &compile, config)?;
+    assert!(native.record_memory_growths().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_config_validate() -> Result<()> {
+    let validate = |config: StylusConfig| -> (UserOutcomeKind, String) {
+        let mut output = RustBytes {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+            cap: 0,
+        };
+        let status = unsafe { stylus_config_validate(config, &mut output) };
+        let error = match status {
+            UserOutcomeKind::Success => String::new(),
+            _ => {
+                let error = unsafe { std::slice::from_raw_parts(output.ptr, output.len) };
+                let error = String::from_utf8_lossy(error).to_string();
+                unsafe { stylus_drop_vec(output) };
+                error
+            }
+        };
+        (status, error)
+    };
+
+    let (_, config, _) = test_configs();
+    assert_eq!(validate(config).0, UserOutcomeKind::Success);
+
+    let mut unsupported = config;
+    unsupported.version = StylusConfig::MAX_VERSION + 1;
+    let (status, error) = validate(unsupported);
+    assert_eq!(status, UserOutcomeKind::Failure);
+    assert!(error.contains("unsupported Stylus version 3"), "{error}");
+
+    let mut free = config;
+    free.pricing.ink_price = 0;
+    let (status, error) = validate(free);
+    assert_eq!(status, UserOutcomeKind::Failure);
+    assert!(error.contains("ink price must be nonzero"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn test_version_range() -> Result<()> {
+    assert_eq!(stylus_min_version(), StylusConfig::MIN_VERSION);
+    assert_eq!(stylus_max_version(), StylusConfig::MAX_VERSION);
+
+    let wat = std::fs::read("tests/add.wat")?;
+    let wasm = wasmer::wat2wasm(&wat)?;
+    let mut gas = u64::MAX;
+
+    // versions past the max are rejected before any gas is charged
+    let version = stylus_max_version() + 1;
+    let error = native::activate(&wasm, version, 128, true, &mut gas).unwrap_err();
+    let error = error.to_string();
+    assert!(error.contains("unsupported Stylus version 3"), "{error}");
+    assert!(error.contains("supports 0 through 2"), "{error}");
+    assert_eq!(gas, u64::MAX);
+
+    native::activate(&wasm, stylus_max_version(), 128, true, &mut gas)?;
+    Ok(())
+}
+
+#[test]
+fn test_code_size() -> Result<()> {
+    // in code-size.wat
+    //     the program returns the size of its own code
+
+    let filename = "tests/code-size.wat";
+    let (compile, config, ink) = test_configs();
+    let code = wasmer::wat2wasm(&std::fs::read(filename)?)?.to_vec();
+
+    let (mut native, mut evm) = TestInstance::new_with_evm(filename, &compile, config)?;
+    evm.set_code(Bytes20::default(), code.clone());
+
+    let output = run_native(&mut native, &[], ink)?;
+    assert_eq!(output, (code.len() as u32).to_le_bytes());
+    Ok(())
+}
+
+#[test]
+fn test_safe_division() -> Result<()> {
+    // in div-zero.wat
+    //     the program returns a / b as signed i32s, then a % b as unsigned i64s
+
+    let (mut compile, config, ink) = test_configs();
+    let run = |compile: &CompileConfig, a: i32, b: i32| -> Result<(UserOutcomeKind, Vec<u8>)> {
+        let mut native = TestInstance::new_linked("tests/div-zero.wat", compile, config)?;
+        let args = [a.to_le_bytes(), b.to_le_bytes()].concat();
+        Ok(native.run_main(&args, config, ink)?.into_data())
+    };
+    let expect = |quotient: i32, remainder: u64| {
+        let outs = [&quotient.to_le_bytes()[..], &remainder.to_le_bytes()].concat();
+        (UserOutcomeKind::Success, outs)
+    };
+
+    // by default, arithmetic traps fail the call
+    assert_eq!(run(&compile, 7, 2)?, expect(3, 1));
+    assert_eq!(run(&compile, 7, 0)?.0, UserOutcomeKind::Failure);
+    assert_eq!(run(&compile, i32::MIN, -1)?.0, UserOutcomeKind::Failure);
+
+    // in the non-consensus mode, the program continues with defined values
+    compile.debug.safe_division = true;
+    assert_eq!(run(&compile, 7, 2)?, expect(3, 1));
+    assert_eq!(run(&compile, -7, 2)?, expect(-3, 0xffff_fff9 % 2));
+    assert_eq!(run(&compile, 7, 0)?, expect(0, 0));
+    assert_eq!(
+        run(&compile, i32::MIN, -1)?,
+        expect(i32::MIN, 0x8000_0000 % 0xffff_ffff)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_host_cost_table() -> Result<()> {
+    let (_, config, _) = test_configs();
+    let mut output = RustBytes {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+        cap: 0,
+    };
+    unsafe { stylus_host_cost_table(config, &mut output) };
+    let table = unsafe { std::slice::from_raw_parts(output.ptr, output.len) };
+    let table = String::from_utf8(table.to_vec())?;
+    unsafe { stylus_drop_vec(output) };
+
+    let mut costs = HashMap::new();
+    for line in table.lines() {
+        let [hostio, ink, gas] = line.split(' ').collect::<Vec<_>>()[..] else {
+            bail!("malformed line {line}");
+        };
+        let (ink, gas): (u64, u64) = (ink.parse()?, gas.parse()?);
+        assert_eq!(gas, config.pricing.ink_to_gas(ink));
+        costs.insert(hostio.to_owned(), ink);
+    }
+
+    // every hostio the forwarder links is in the table, and nothing else is
+    let forward = include_str!("../../../wasm-libraries/forward/src/main.rs");
+    let hostios: Vec<_> = forward
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("[\""))
+        .filter_map(|line| line.split('"').next())
+        .collect();
+    assert_eq!(hostios.len(), costs.len());
+    for hostio in hostios {
+        assert!(costs.contains_key(hostio), "missing {hostio}");
+    }
+
+    let ptr = pricing::PTR_INK;
+    assert_eq!(costs["read_args"], pricing::HOSTIO_INK);
+    assert_eq!(costs["exit_early"], 0);
+    assert_eq!(costs["block_basefee"], pricing::HOSTIO_INK + ptr);
+    assert_eq!(costs["native_keccak256"], pricing::KECCAK_INK);
+    assert_eq!(
+        costs["math_mul_mod"],
+        pricing::HOSTIO_INK + 4 * ptr + pricing::MUL_MOD_INK
+    );
+    assert_eq!(
+        costs["transient_compare_and_set"],
+        pricing::HOSTIO_INK + 3 * ptr + pricing::EVM_API_INK
+    );
+    Ok(())
+}
+
+#[test]
+fn test_seeded_env() -> Result<()> {
+    // in block-data.wat
+    //     the program returns the block's coinbase followed by its prevrandao
+
+    let (compile, config, ink) = test_configs();
+    let run = |seed: u64| -> Result<Vec<u8>> {
+        let (evm, evm_data) = TestEvmApi::new_seeded(compile.clone(), seed);
+        let mut native =
+            TestInstance::from_path("tests/block-data.wat", evm, evm_data, &compile, config)?;
+        let output = run_native(&mut native, &[], ink)?;
+        assert_eq!(output[20..], evm_data.block_prevrandao[..]);
+        Ok(output)
+    };
+
+    // the same seed always produces the same values, and different seeds differ
+    let output = run(42)?;
+    assert_ne!(output[20..], [0; 32]);
+    assert_eq!(output, run(42)?);
+    assert_ne!(output, run(43)?);
+    Ok(())
+}
+
+#[test]
+fn test_hostio_out_of_bounds() -> Result<()> {
+    // in hostio-oob.wat
+    //     the first arg byte selects a hostio call whose memory range lies outside the program
+
+    let (compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/hostio-oob.wat", &compile, config)?;
+    assert_eq!(run_native(&mut native, &[0], ink)?, [0]);
+
+    for case in 1..=3 {
+        match native.run_main(&[case], config, ink)? {
+            UserOutcome::Failure(err) => assert!(format!("{err:?}").contains("out of bounds")),
+            outcome => bail!("case {case} expected failure: {}", outcome.red()),
+        }
+    }
+
+    // the rejected write leaves memory untouched
+    assert_eq!(native.read_slice("memory", 0xfff6, 10)?, [0; 10]);
+    Ok(())
+}
+
+#[test]
+fn test_hostio_alignment() -> Result<()> {
+    // in hostio-align.wat
+    //     the first arg byte offsets the pointer the program passes to msg_sender
+
+    let (mut compile, config, ink) = test_configs();
+    let mut native = TestInstance::new_linked("tests/hostio-align.wat", &compile, config)?;
+    for offset in [0, 1, 4] {
+        assert_eq!(run_native(&mut native, &[offset], ink)?.len(), 20);
+    }
+
+    let mut run = |alignment, offset| -> Result<UserOutcome> {
+        compile.debug.hostio_alignment = alignment;
+        let mut native = TestInstance::new_linked("tests/hostio-align.wat", &compile, config)?;
+        native.run_main(&[offset], config, ink)
+    };
+    for (alignment, offset) in [(4, 1), (4, 6), (8, 4)] {
+        match run(alignment, offset)? {
+            UserOutcome::Failure(err) => assert!(format!("{err:?}").contains("unaligned pointer")),
+            outcome => bail!("offset {offset} expected failure: {}", outcome.red()),
+        }
+    }
+    for (alignment, offset) in [(1, 3), (4, 4), (8, 8)] {
+        assert!(matches!(run(alignment, offset)?, UserOutcome::Success(_)));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_blob_fees() -> Result<()> {
+    // in blob-fees.wat
+    //     the program returns the blob basefee followed by the excess blob gas
+
+    let (compile, config, ink) = test_configs();
+    let filename = "tests/blob-fees.wat";
+
+    let (evm, mut evm_data) = TestEvmApi::new(compile.clone());
+    evm_data.block_blob_basefee = random_bytes32();
+    evm_data.block_excess_blob_gas = 0x2_0000_0000;
+
+    let mut native = TestInstance::from_path(filename, evm, evm_data, &compile, config)?;
+    let output = run_native(&mut native, &[], ink)?;
+    assert_eq!(output[..32], evm_data.block_blob_basefee[..]);
+    assert_eq!(output[32..], 0x2_0000_0000_u64.to_le_bytes());
+    Ok(())
+}
@@ -1,8 +1,25 @@
 // Copyright 2024, Offchain Labs, Inc.
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
-use arbutil::crypto;
-use eyre::Report;
+use crate::RustBytes;
+use arbutil::{crypto, evm::user::UserOutcomeKind};
+use eyre::{bail, eyre, Report, Result};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+};
+
+thread_local! {
+    /// Whether the current thread is running an FFI entry point guarded by `ffi_guard`.
+    static IN_FFI: Cell<bool> = const { Cell::new(false) };
+
+    /// The message of the last panic raised within a guarded FFI entry point.
+    static PANIC_MESSAGE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static PANIC_HOOK: Once = Once::new();
 
 /// This function panics while saving an offending wasm to disk.
 pub fn panic_with_wasm(wasm: &[u8], error: Report) -> ! {
@@ -18,3 +35,63 @@ pub fn panic_with_wasm(wasm: &[u8], error: Report) -> ! {
     }
     panic!("encountered fatal wasm: {error:?}");
 }
+
+/// Runs a closure, converting any panic into an error instead of unwinding further.
+pub fn catch_panic<T>(name: &str, func: impl FnOnce() -> Result<T>) -> Result<T> {
+    let payload = match panic::catch_unwind(AssertUnwindSafe(func)) {
+        Ok(result) => return result,
+        Err(payload) => payload,
+    };
+    let message = panic_message(&*payload);
+    bail!("{name} panicked: {message}")
+}
+
+/// Runs an FFI entry point, converting any panic into a failure whose `output` holds the panic
+/// message. This keeps panics from unwinding across the FFI boundary, which is undefined behavior.
+/// Only for entry points outside consensus, where a panic must stay fatal rather than become a
+/// user failure.
+pub fn ffi_guard(
+    output: &mut RustBytes,
+    func: impl FnOnce(&mut RustBytes) -> UserOutcomeKind,
+) -> UserOutcomeKind {
+    install_panic_hook();
+    let outer = IN_FFI.with(|x| x.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| func(output)));
+    IN_FFI.with(|x| x.set(outer));
+
+    let payload = match result {
+        Ok(status) => return status,
+        Err(payload) => payload,
+    };
+    let message = PANIC_MESSAGE.with(|x| x.take());
+    let message = message.unwrap_or_else(|| panic_message(&*payload));
+    unsafe { output.write_err(eyre!("panicked: {message}")) }
+}
+
+/// Installs, on first use, a panic hook that records the messages of panics raised within
+/// `ffi_guard` rather than printing them. Panics elsewhere go to the previous hook as usual.
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !IN_FFI.with(Cell::get) {
+                return previous(info);
+            }
+            let mut message = panic_message(info.payload());
+            if let Some(location) = info.location() {
+                message = format!("{message} at {location}");
+            }
+            PANIC_MESSAGE.with(|x| x.replace(Some(message)));
+        }));
+    });
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "unknown panic".to_string(),
+        },
+    }
+}
@@ -0,0 +1,16 @@
+;; Copyright 2023, Offchain Labs, Inc.
+;; For license information, see https://github.com/nitro/blob/master/LICENSE
+
+(module
+    (memory 0 0)
+    (export "memory" (memory 0))
+    (func (export "add_loop") (param $count i32) (result i32)
+        (local $sum i32)
+        (loop $top
+            (local.set $sum (i32.add (local.get $sum) (i32.const 1)))
+            (local.set $count (i32.sub (local.get $count) (i32.const 1)))
+            (br_if $top (local.get $count)))
+        (local.get $sum))
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (i32.const 0)
+    ))
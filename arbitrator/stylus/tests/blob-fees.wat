@@ -0,0 +1,16 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "write_result"          (func $write_result          (param i32 i32)))
+    (import "vm_hooks" "block_blob_basefee"    (func $block_blob_basefee    (param i32)))
+    (import "vm_hooks" "block_excess_blob_gas" (func $block_excess_blob_gas (result i64)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; return the blob basefee followed by the little-endian excess blob gas
+        (call $block_blob_basefee (i32.const 0))
+        (i64.store (i32.const 32) (call $block_excess_blob_gas))
+        (call $write_result (i32.const 0) (i32.const 40))
+        i32.const 0
+    )
+)
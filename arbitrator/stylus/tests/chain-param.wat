@@ -0,0 +1,18 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"       (func $read_args       (param i32)))
+    (import "vm_hooks" "write_result"    (func $write_result    (param i32 i32)))
+    (import "vm_hooks" "arb_chain_param" (func $arb_chain_param (param i32) (result i64)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; the args are a little-endian param id
+        (call $read_args (i32.const 0))
+
+        ;; return the param's little-endian value
+        (i64.store (i32.const 0x20) (call $arb_chain_param (i32.load (i32.const 0))))
+        (call $write_result (i32.const 0x20) (i32.const 8))
+        i32.const 0
+    )
+)
@@ -0,0 +1,26 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"          (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result"       (func $write_result (param i32 i32)))
+    (import "vm_hooks" "contract_code_copy" (func $code_copy    (param i32 i32 i32) (result i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (local $len i32)
+
+        ;; the args are a little-endian offset followed by a size
+        (call $read_args (i32.const 0))
+
+        ;; copy the program's code to offset 0x100
+        (call $code_copy
+            (i32.load (i32.const 0))
+            (i32.load (i32.const 4))
+            (i32.const 0x100))
+        local.set $len
+
+        ;; return the bytes copied
+        (call $write_result (i32.const 0x100) (local.get $len))
+        i32.const 0
+    )
+)
@@ -0,0 +1,12 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (memory (export "memory") 1 1)
+    (data (i32.const 0x10) "stylus")
+    (data "passive\00bytes")
+    (data (i32.const 0x400) "\de\ad\be\ef")
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        i32.const 0
+    )
+)
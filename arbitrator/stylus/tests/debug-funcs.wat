@@ -0,0 +1,16 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "debug" "print_i64"   (func $print_i64   (param i64)))
+    (import "debug" "dump_memory" (func $dump_memory (param i32 i32)))
+    (import "debug" "breakpoint"  (func $breakpoint))
+    (memory (export "memory") 1 1)
+    (data (i32.const 0x20) "\de\ad\be\ef")
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (call $print_i64 (i64.const -42))
+        (call $dump_memory (i32.const 0x20) (i32.const 4))
+        (call $breakpoint)
+        i32.const 0
+    )
+)
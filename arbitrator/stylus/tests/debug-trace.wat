@@ -0,0 +1,14 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "debug" "trace" (func $trace (param i32 i32 i64)))
+    (memory (export "memory") 1 1)
+    (data (i32.const 0) "gasslot")
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; trace two tagged values
+        (call $trace (i32.const 0) (i32.const 3) (i64.const 21000))
+        (call $trace (i32.const 3) (i32.const 4) (i64.const -1))
+        i32.const 0
+    )
+)
@@ -13,6 +13,14 @@
         i32.add             ;; pop 2, push 1 -- 2 on stack
         global.set $depth   ;; pop 1         -- 1 on stack
         call $recurse)
+    (func $descend (export "descend") (param $n i32)
+        local.get $n        ;; push 1        -- 1 on stack
+        if                  ;; pop 1         -- 0 on stack
+            local.get $n    ;; push 1        -- 1 on stack
+            i32.const 1     ;; push 1        -- 2 on stack  <- 2 words max
+            i32.sub         ;; pop 2, push 1 -- 1 on stack
+            call $descend   ;; pop 1         -- 0 on stack
+        end)
     (func (export "user_entrypoint") (param $args_len i32) (result i32)
         (i32.const 0)
     ))
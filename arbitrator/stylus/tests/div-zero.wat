@@ -0,0 +1,23 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"    (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; the args are two little-endian i32s, a and b
+        (call $read_args (i32.const 0))
+
+        ;; write a / b as signed i32s
+        (i32.store (i32.const 0x100)
+            (i32.div_s (i32.load (i32.const 0)) (i32.load (i32.const 4))))
+
+        ;; write a % b as unsigned i64s
+        (i64.store (i32.const 0x104)
+            (i64.rem_u (i64.load32_u (i32.const 0)) (i64.load32_u (i32.const 4))))
+
+        (call $write_result (i32.const 0x100) (i32.const 12))
+        i32.const 0
+    )
+)
@@ -0,0 +1,20 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"        (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result"     (func $write_result (param i32 i32)))
+    (import "vm_hooks" "native_ecpairing" (func $ecpairing    (param i32 i32) (result i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; write args to offset 0x20
+        (call $read_args (i32.const 0x20))
+
+        ;; check the pairing, storing the result at offset 0
+        (i32.store8 (i32.const 0) (call $ecpairing (i32.const 0x20) (local.get $args_len)))
+
+        ;; return the result
+        (call $write_result (i32.const 0) (i32.const 1))
+        i32.const 0
+    )
+)
@@ -0,0 +1,16 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "write_result"        (func $write_result        (param i32 i32)))
+    (import "vm_hooks" "effective_gas_price" (func $effective_gas_price (param i32)))
+    (import "vm_hooks" "tx_gas_price"        (func $tx_gas_price        (param i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; write the effective gas price followed by the tx gas price
+        (call $effective_gas_price (i32.const 0))
+        (call $tx_gas_price (i32.const 32))
+        (call $write_result (i32.const 0) (i32.const 64))
+        i32.const 0
+    )
+)
@@ -0,0 +1,17 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"     (func $read_args     (param i32)))
+    (import "vm_hooks" "finish_return" (func $finish_return (param i32 i32)))
+    (import "vm_hooks" "finish_revert" (func $finish_revert (param i32 i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; the first byte picks the outcome, the rest is the data
+        (call $read_args (i32.const 0))
+        (if (i32.load8_u (i32.const 0))
+            (then (call $finish_revert (i32.const 1) (i32.sub (local.get $args_len) (i32.const 1))))
+            (else (call $finish_return (i32.const 1) (i32.sub (local.get $args_len) (i32.const 1)))))
+        unreachable
+    )
+)
@@ -0,0 +1,14 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (memory (export "memory") 0 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; exceeds the max, so memory doesn't grow
+        (drop (memory.grow (i32.const 2)))
+
+        ;; within the max
+        (drop (memory.grow (i32.const 1)))
+        i32.const 0
+    )
+)
@@ -0,0 +1,16 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (memory (export "memory") 0 8)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; zero-page grows don't reallocate
+        (drop (memory.grow (i32.const 0)))
+
+        ;; grow one page at a time, staying well within the max
+        (drop (memory.grow (i32.const 1)))
+        (drop (memory.grow (i32.const 1)))
+        (drop (memory.grow (i32.const 1)))
+        i32.const 0
+    )
+)
@@ -0,0 +1,19 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"    (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+    (import "vm_hooks" "msg_sender"   (func $msg_sender   (param i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (local $ptr i32)
+        (call $read_args (i32.const 0))
+
+        ;; the first arg byte is where the hostio writes the 20-byte sender
+        (local.set $ptr (i32.add (i32.const 0x100) (i32.load8_u (i32.const 0))))
+        (call $msg_sender (local.get $ptr))
+        (call $write_result (local.get $ptr) (i32.const 20))
+        i32.const 0
+    )
+)
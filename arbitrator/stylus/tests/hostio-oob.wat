@@ -0,0 +1,34 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"    (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+    (import "vm_hooks" "msg_sender"   (func $msg_sender   (param i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (call $read_args (i32.const 0))
+
+        ;; the first arg byte selects which out-of-bounds access to make
+        (block $writes
+            (block $straddles
+                (block $overflows
+                    (block $succeeds
+                        (br_table $succeeds $overflows $straddles $writes (i32.load8_u (i32.const 0)))
+                    )
+                    (call $write_result (i32.const 0) (i32.const 1))
+                    (return (i32.const 0))
+                )
+                ;; ptr + len wraps around the 32-bit address space
+                (call $write_result (i32.const 0xffff_fff0) (i32.const 0x20))
+                (return (i32.const 0))
+            )
+            ;; the range starts in bounds but ends past the last page
+            (call $write_result (i32.const 0xfff0) (i32.const 0x20))
+            (return (i32.const 0))
+        )
+        ;; the hostio writes 20 bytes with only 10 left
+        (call $msg_sender (i32.const 0xfff6))
+        i32.const 0
+    )
+)
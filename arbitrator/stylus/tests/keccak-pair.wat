@@ -0,0 +1,18 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"      (func $read_args      (param i32)))
+    (import "vm_hooks" "write_result"   (func $write_result   (param i32 i32)))
+    (import "vm_hooks" "keccak256_pair" (func $keccak256_pair (param i32 i32 i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; the args are the left and right words
+        (call $read_args (i32.const 0))
+
+        ;; return the hash of their concatenation
+        (call $keccak256_pair (i32.const 0) (i32.const 32) (i32.const 64))
+        (call $write_result (i32.const 64) (i32.const 32))
+        i32.const 0
+    )
+)
@@ -0,0 +1,10 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "helper" "add" (func $add (param i32 i32) (result i32)))
+    (memory (export "memory") 1 1)
+    (func (export "add_via_helper") (param i32 i32) (result i32)
+        (call $add (local.get 0) (local.get 1)))
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        i32.const 0))
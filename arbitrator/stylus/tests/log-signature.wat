@@ -0,0 +1,19 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"               (func $read_args (param i32)))
+    (import "vm_hooks" "emit_log_with_signature" (func $emit_log  (param i32 i32 i32 i32 i32)))
+    (memory (export "memory") 1 1)
+    (data (i32.const 0x100) "Transfer(address,address,uint256)")
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; the args are two indexed topics followed by the unindexed data
+        (call $read_args (i32.const 0))
+
+        ;; emit under the signature at 0x100
+        (call $emit_log
+            (i32.const 0x100) (i32.const 33)
+            (i32.const 0) (local.get $args_len) (i32.const 2))
+        i32.const 0
+    )
+)
@@ -0,0 +1,23 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args" (func $read_args (param i32)))
+    (import "vm_hooks" "emit_log"  (func $emit_log  (param i32 i32 i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (local $count i32)
+
+        ;; the first arg byte is the number of topic-less logs to emit
+        (call $read_args (i32.const 0))
+        (local.set $count (i32.load8_u (i32.const 0)))
+
+        (block $done
+            (loop $next
+                (br_if $done (i32.eqz (local.get $count)))
+                (call $emit_log (i32.const 0) (i32.const 1) (i32.const 0))
+                (local.set $count (i32.sub (local.get $count) (i32.const 1)))
+                (br $next)))
+        i32.const 0
+    )
+)
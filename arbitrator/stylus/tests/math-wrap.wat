@@ -0,0 +1,24 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"    (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+    (import "vm_hooks" "math_add"     (func $math_add     (param i32 i32)))
+    (import "vm_hooks" "math_mul"     (func $math_mul     (param i32 i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; the args are two 32-byte operands, a and b
+        (call $read_args (i32.const 0))
+
+        ;; copy a so that both the sum and product can be computed in place
+        (memory.copy (i32.const 64) (i32.const 0) (i32.const 32))
+
+        ;; write a + b followed by a * b
+        (call $math_add (i32.const 0) (i32.const 32))
+        (call $math_mul (i32.const 64) (i32.const 32))
+        (memory.copy (i32.const 32) (i32.const 64) (i32.const 32))
+        (call $write_result (i32.const 0) (i32.const 64))
+        i32.const 0
+    )
+)
@@ -0,0 +1,29 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "pay_for_memory_grow" (func (param i32)))
+    (import "vm_hooks" "read_args"           (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result"        (func $write_result (param i32 i32)))
+    (import "vm_hooks" "memory_gas_charged"  (func $memory_gas   (result i64)))
+    (memory (export "memory") 1 128)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (local $i i32)
+
+        ;; each byte of the args is a number of pages to grow by
+        (call $read_args (i32.const 0))
+        (loop $loop
+            (drop (memory.grow (i32.load8_u (local.get $i))))
+
+            ;; record the memory gas charged so far just past the args
+            (i64.store
+                (i32.add (local.get $args_len) (i32.mul (local.get $i) (i32.const 8)))
+                (call $memory_gas))
+
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br_if $loop (i32.lt_u (local.get $i) (local.get $args_len)))
+        )
+        (call $write_result (local.get $args_len) (i32.mul (local.get $args_len) (i32.const 8)))
+        i32.const 0
+    )
+)
@@ -0,0 +1,41 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"             (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result"          (func $write_result (param i32 i32)))
+    (import "vm_hooks" "storage_load_bytes32"  (func $load         (param i32 i32)))
+    (import "vm_hooks" "storage_cache_bytes32" (func $cache        (param i32 i32)))
+    (import "vm_hooks" "storage_flush_cache"   (func $flush        (param i32)))
+    (import "vm_hooks" "emit_log"              (func $emit_log     (param i32 i32 i32)))
+    (import "vm_hooks" "native_keccak256"      (func $keccak       (param i32 i32 i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (local $i i32)
+
+        ;; write args to 0x0
+        (call $read_args (i32.const 0))
+
+        ;; hash the args into 0x100, then store the digest under the args
+        (call $keccak (i32.const 0) (local.get $args_len) (i32.const 0x100))
+        (call $cache (i32.const 0) (i32.const 0x100))
+        (call $flush (i32.const 0))
+
+        ;; load the digest back into 0x200
+        (call $load (i32.const 0) (i32.const 0x200))
+
+        ;; emit a log with the digest as its only topic
+        (call $emit_log (i32.const 0x100) (i32.const 64) (i32.const 1))
+
+        ;; burn some compute
+        (local.set $i (i32.const 1000))
+        (loop
+            (local.set $i (i32.add (local.get $i) (i32.const -1)))
+            (br_if 0 (local.get $i))
+        )
+
+        ;; return the digest
+        (call $write_result (i32.const 0x200) (i32.const 32))
+        i32.const 0
+    )
+)
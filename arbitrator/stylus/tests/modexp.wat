@@ -0,0 +1,30 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"     (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result"  (func $write_result (param i32 i32)))
+    (import "vm_hooks" "native_modexp" (func $modexp       (param i32 i32 i32 i32 i32 i32 i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (local $base_len i32) (local $exp_len i32) (local $mod_len i32)
+
+        ;; the args are 3 little-endian lengths followed by the base, exponent, and modulus
+        (call $read_args (i32.const 0))
+        (local.set $base_len (i32.load (i32.const 0)))
+        (local.set $exp_len  (i32.load (i32.const 4)))
+        (local.set $mod_len  (i32.load (i32.const 8)))
+
+        ;; write the result just past the args
+        (call $modexp
+            (i32.const 12)
+            (local.get $base_len)
+            (i32.add (i32.const 12) (local.get $base_len))
+            (local.get $exp_len)
+            (i32.add (i32.add (i32.const 12) (local.get $base_len)) (local.get $exp_len))
+            (local.get $mod_len)
+            (local.get $args_len))
+        (call $write_result (local.get $args_len) (local.get $mod_len))
+        i32.const 0
+    )
+)
@@ -0,0 +1,14 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+    (import "vm_hooks" "msg_value"    (func $msg_value    (param i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; return the value sent with the call
+        (call $msg_value (i32.const 0))
+        (call $write_result (i32.const 0) (i32.const 32))
+        i32.const 0
+    )
+)
@@ -0,0 +1,13 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/nitro/blob/master/LICENSE
+
+(module
+    (global $iterations (export "iterations") (mut i32) (i32.const 0))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; count forever, recording progress in both a global and memory
+        (loop $top
+            (global.set $iterations (i32.add (global.get $iterations) (i32.const 1)))
+            (i32.store (i32.const 0) (global.get $iterations))
+            (br $top))
+        (i32.const 0)))
@@ -0,0 +1,19 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"    (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; set the args as the return data
+        (call $read_args (i32.const 0))
+        (call $write_result (i32.const 0) (local.get $args_len))
+
+        ;; loop until out of ink
+        (loop $loop
+            br $loop
+        )
+        i32.const 0
+    )
+)
@@ -0,0 +1,35 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"            (func $read_args        (param i32)))
+    (import "vm_hooks" "write_result"         (func $write_result     (param i32 i32)))
+    (import "vm_hooks" "storage_load_bytes32" (func $load_bytes32     (param i32 i32)))
+    (import "vm_hooks" "call_contract"        (func $call_contract    (param i32 i32 i32 i32 i64 i32) (result i32)))
+    (import "vm_hooks" "read_return_data"     (func $read_return_data (param i32 i32 i32) (result i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (local $status i32)
+
+        ;; the args are a storage key followed by a contract address
+        (call $read_args (i32.const 0))
+
+        ;; load the slot's value
+        (call $load_bytes32 (i32.const 0) (i32.const 0x40))
+
+        ;; call the contract with the value, sending no eth
+        (call $call_contract
+            (i32.const 0x20)    ;; contract
+            (i32.const 0x40)    ;; calldata
+            (i32.const 32)      ;; calldata len
+            (i32.const 0x80)    ;; zero value
+            (i64.const -1)      ;; all gas
+            (i32.const 0x100))  ;; return data len
+        local.set $status
+
+        ;; return the return data
+        (drop (call $read_return_data (i32.const 0x200) (i32.const 0) (i32.load (i32.const 0x100))))
+        (call $write_result (i32.const 0x200) (i32.load (i32.const 0x100)))
+        local.get $status
+    )
+)
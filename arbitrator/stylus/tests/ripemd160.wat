@@ -0,0 +1,20 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"        (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result"     (func $write_result (param i32 i32)))
+    (import "vm_hooks" "native_ripemd160" (func $ripemd160    (param i32 i32 i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; write args to offset 0x20
+        (call $read_args (i32.const 0x20))
+
+        ;; hash the args into offset 0
+        (call $ripemd160 (i32.const 0x20) (local.get $args_len) (i32.const 0))
+
+        ;; return the padded digest
+        (call $write_result (i32.const 0) (i32.const 32))
+        i32.const 0
+    )
+)
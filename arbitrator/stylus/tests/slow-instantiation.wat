@@ -0,0 +1,12 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    ;; a huge table is cheap to validate but slow to instantiate
+    (table 5000000 funcref)
+    (elem (i32.const 4999999) $user_entrypoint)
+    (memory (export "memory") 1 1)
+    (func $user_entrypoint (export "user_entrypoint") (param $args_len i32) (result i32)
+        i32.const 0
+    )
+)
@@ -0,0 +1,22 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+    (import "debug"    "stack_left"   (func $stack_left   (param i32)))
+    (memory (export "memory") 1 1)
+    (func $recurse (param $depth i32)
+        ;; record the stack left at this depth
+        (call $stack_left
+            (i32.add (i32.const 0x100) (i32.mul (local.get $depth) (i32.const 4))))
+
+        ;; go three levels deep
+        (if (i32.lt_u (local.get $depth) (i32.const 2))
+            (then (call $recurse (i32.add (local.get $depth) (i32.const 1)))))
+    )
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (call $recurse (i32.const 0))
+        (call $write_result (i32.const 0x100) (i32.const 12))
+        i32.const 0
+    )
+)
@@ -0,0 +1,24 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "read_args"             (func $read_args    (param i32)))
+    (import "vm_hooks" "write_result"          (func $write_result (param i32 i32)))
+    (import "vm_hooks" "storage_cache_bytes32" (func $cache        (param i32 i32)))
+    (import "vm_hooks" "storage_flush_cache"   (func $flush        (param i32)))
+    (import "vm_hooks" "storage_load_bytes32"  (func $load         (param i32 i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; the args are a key and a value
+        (call $read_args (i32.const 0))
+
+        ;; persist the value, clearing the cache so the load hits the EVM
+        (call $cache (i32.const 0) (i32.const 32))
+        (call $flush (i32.const 1))
+
+        ;; load the value back into offset 0x40 and return it
+        (call $load (i32.const 0) (i32.const 0x40))
+        (call $write_result (i32.const 0x40) (i32.const 32))
+        i32.const 0
+    )
+)
@@ -0,0 +1,22 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "write_result"              (func $write_result (param i32 i32)))
+    (import "vm_hooks" "transient_compare_and_set" (func $cas (param i32 i32 i32) (result i32)))
+    (memory (export "memory") 1 1)
+    ;; the guard's key is at 0x00, while 0x20 and 0x40 hold the unlocked and locked values
+    (data (i32.const 0x5f) "\01")
+    (func $enter (result i32)
+        (call $cas (i32.const 0x00) (i32.const 0x20) (i32.const 0x40)))
+    (func $exit (result i32)
+        (call $cas (i32.const 0x00) (i32.const 0x40) (i32.const 0x20)))
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; lock, try to reenter, then unlock
+        (i32.store8 (i32.const 0x100) (call $enter))
+        (i32.store8 (i32.const 0x101) (call $enter))
+        (i32.store8 (i32.const 0x102) (call $exit))
+        (call $write_result (i32.const 0x100) (i32.const 3))
+        i32.const 0
+    )
+)
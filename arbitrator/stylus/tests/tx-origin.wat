@@ -0,0 +1,16 @@
+;; Copyright 2024, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+    (import "vm_hooks" "tx_origin"    (func $tx_origin    (param i32)))
+    (import "vm_hooks" "msg_sender"   (func $msg_sender   (param i32)))
+    (memory (export "memory") 1 1)
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        ;; return the tx origin followed by the msg sender
+        (call $tx_origin (i32.const 0))
+        (call $msg_sender (i32.const 20))
+        (call $write_result (i32.const 0) (i32.const 40))
+        i32.const 0
+    )
+)
@@ -6,7 +6,7 @@ use std::{fs::File, io::Write, path::PathBuf};
 use structopt::StructOpt;
 
 /// order matters!
-const HOSTIOS: [[&str; 3]; 42] = [
+const HOSTIOS: [[&str; 3]; 68] = [
     ["read_args", "i32", ""],
     ["write_result", "i32 i32", ""],
     ["exit_early", "i32", ""],
@@ -49,6 +49,32 @@ const HOSTIOS: [[&str; 3]; 42] = [
     ["tx_ink_price", "", "i32"],
     ["tx_origin", "i32", ""],
     ["pay_for_memory_grow", "i32", ""],
+    ["contract_code_copy", "i32 i32 i32", "i32"],
+    ["native_sha256", "i32 i32 i32", ""],
+    ["account_is_contract", "i32", "i32"],
+    ["emit_log_with_signature", "i32 i32 i32 i32 i32", ""],
+    ["transient_compare_and_set", "i32 i32 i32", "i32"],
+    ["finish_return", "i32 i32", ""],
+    ["finish_revert", "i32 i32", ""],
+    ["storage_load_bytes", "i32 i32 i32", ""],
+    ["is_static", "", "i32"],
+    ["native_modexp", "i32 i32 i32 i32 i32 i32 i32", ""],
+    ["memory_gas_charged", "", "i64"],
+    ["block_prevrandao", "i32", ""],
+    ["math_add", "i32 i32", ""],
+    ["math_mul", "i32 i32", ""],
+    ["arb_l1_block_number", "", "i64"],
+    ["arb_l2_block_number", "", "i64"],
+    ["contract_code_size", "", "i32"],
+    ["native_ripemd160", "i32 i32 i32", ""],
+    ["effective_gas_price", "i32", ""],
+    ["native_ecpairing", "i32 i32", "i32"],
+    ["native_ecrecover", "i32 i32", ""],
+    ["arb_chain_param", "i32", "i64"],
+    ["arb_l1_sender", "i32", ""],
+    ["keccak256_pair", "i32 i32 i32", ""],
+    ["block_blob_basefee", "i32", ""],
+    ["block_excess_blob_gas", "", "i64"],
 ];
 
 #[derive(StructOpt)]
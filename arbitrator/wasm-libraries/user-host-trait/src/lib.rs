@@ -8,19 +8,20 @@ use arbutil::{
         api::{DataReader, EvmApi},
         storage::StorageCache,
         user::UserOutcomeKind,
-        EvmData,
+        ChainParam, EvmData,
     },
     pricing::{self, EVM_API_INK, HOSTIO_INK, PTR_INK},
     Bytes20, Bytes32,
 };
 pub use caller_env::GuestPtr;
 use eyre::{eyre, Result};
+use num_bigint::BigUint;
 use prover::{
     programs::{meter::OutOfInkError, prelude::*},
     value::Value,
 };
 use ruint2::Uint;
-use std::fmt::Display;
+use std::{fmt::Display, mem};
 
 macro_rules! be {
     ($int:expr) => {
@@ -69,13 +70,32 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
     fn evm_api(&mut self) -> &mut Self::A;
     fn evm_data(&self) -> &EvmData;
     fn evm_return_data_len(&mut self) -> &mut u32;
+    fn logs_emitted(&mut self) -> &mut u32;
+    fn memory_gas(&mut self) -> &mut u64;
+    fn config(&self) -> StylusConfig;
 
     fn read_slice(&self, ptr: GuestPtr, len: u32) -> Result<Vec<u8>, Self::MemoryErr>;
     fn read_fixed<const N: usize>(&self, ptr: GuestPtr) -> Result<[u8; N], Self::MemoryErr>;
 
+    /// Reads into `dest`, which implementations may override to reuse its allocation.
+    fn read_slice_into(
+        &self,
+        ptr: GuestPtr,
+        len: u32,
+        dest: &mut Vec<u8>,
+    ) -> Result<(), Self::MemoryErr> {
+        *dest = self.read_slice(ptr, len)?;
+        Ok(())
+    }
+
     fn write_u32(&mut self, ptr: GuestPtr, x: u32) -> Result<(), Self::MemoryErr>;
     fn write_slice(&self, ptr: GuestPtr, src: &[u8]) -> Result<(), Self::MemoryErr>;
 
+    /// Ensures `bytes` bytes starting at `ptr` lie within the program's memory.
+    fn check_memory_access(&self, ptr: GuestPtr, bytes: u32) -> Result<(), Self::MemoryErr>;
+
+    // Words are copied verbatim, so guest memory holds them big-endian, as in the EVM.
+    // Conversion to and from integers is left to the `Bytes32` impls in `arbutil`.
     fn read_bytes20(&self, ptr: GuestPtr) -> Result<Bytes20, Self::MemoryErr> {
         self.read_fixed(ptr).map(Into::into)
     }
@@ -90,6 +110,15 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
     fn say<D: Display>(&self, text: D);
     fn trace(&mut self, name: &str, args: &[u8], outs: &[u8], end_ink: u64);
 
+    /// Sets the return data, reusing any capacity reserved in advance.
+    /// Implementations may override this to consume the data elsewhere.
+    fn read_outs(&mut self, ptr: GuestPtr, len: u32) -> Result<(), Self::Err> {
+        let mut outs = mem::take(self.outs());
+        let result = self.read_slice_into(ptr, len, &mut outs);
+        *self.outs() = outs;
+        Ok(result?)
+    }
+
     fn write_bytes20(&self, ptr: GuestPtr, src: Bytes20) -> Result<(), Self::MemoryErr> {
         self.write_slice(ptr, &src.0)
     }
@@ -115,7 +144,7 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         self.buy_ink(HOSTIO_INK)?;
         self.pay_for_read(len)?;
         self.pay_for_geth_bytes(len)?; // returned after call
-        *self.outs() = self.read_slice(ptr, len)?;
+        self.read_outs(ptr, len)?;
         trace!("write_result", self, &*self.outs(), &[])
     }
 
@@ -132,6 +161,30 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("exit_early", self, be!(status), &[])
     }
 
+    /// Sets the return data and ends execution successfully. The semantics are equivalent to that
+    /// of the EVM's [`Return`] opcode. Like `exit_early`, it's up to the caller to actually exit.
+    ///
+    /// [`Return`]: https://www.evm.codes/#f3
+    fn finish_return(&mut self, ptr: GuestPtr, len: u32) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK)?;
+        self.pay_for_read(len)?;
+        self.pay_for_geth_bytes(len)?; // returned after call
+        self.read_outs(ptr, len)?;
+        trace!("finish_return", self, &*self.outs(), &[])
+    }
+
+    /// Sets the revert data and ends execution with a revert. The semantics are equivalent to that
+    /// of the EVM's [`Revert`] opcode. Like `exit_early`, it's up to the caller to actually exit.
+    ///
+    /// [`Revert`]: https://www.evm.codes/#fd
+    fn finish_revert(&mut self, ptr: GuestPtr, len: u32) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK)?;
+        self.pay_for_read(len)?;
+        self.pay_for_geth_bytes(len)?; // returned after call
+        self.read_outs(ptr, len)?;
+        trace!("finish_revert", self, &*self.outs(), &[])
+    }
+
     /// Reads a 32-byte value from permanent storage. Stylus's storage format is identical to
     /// that of the EVM. This means that, under the hood, this hostio is accessing the 32-byte
     /// value stored in the EVM state trie at offset `key`, which will be `0` when not previously
@@ -153,6 +206,37 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("storage_load_bytes32", self, key, value)
     }
 
+    /// Reads `len` bytes packed across the contiguous storage slots starting at offset `key`,
+    /// writing them to `dest`. This is equivalent to calling `storage_load_bytes32` for each of
+    /// the `ceil(len / 32)` consecutive keys, and costs the same, except that the final slot is
+    /// truncated to fit and only one hostio is made.
+    fn storage_load_bytes(
+        &mut self,
+        key: GuestPtr,
+        dest: GuestPtr,
+        len: u32,
+    ) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + 2 * PTR_INK)?;
+        self.pay_for_write(len)?;
+        let (base, base32) = self.read_u256(key)?;
+
+        // bound the buffer by the program's memory before growing it a slot at a time
+        self.check_memory_access(dest, len)?;
+        let mut data = vec![];
+        for slot in 0..evm::evm_words(len) {
+            self.require_gas(
+                evm::COLD_SLOAD_GAS + EVM_API_INK + StorageCache::REQUIRED_ACCESS_GAS,
+            )?; // cache-miss case
+            let key = base.wrapping_add(U256::from(slot)).into();
+            let (value, gas_cost) = self.evm_api().get_bytes32(key);
+            self.buy_gas(gas_cost)?;
+            data.extend(value);
+        }
+        data.truncate(len as usize);
+        self.write_slice(dest, &data)?;
+        trace!("storage_load_bytes", self, [base32, be!(len)], data)
+    }
+
     /// Writes a 32-byte value to the permanent storage cache. Stylus's storage format is identical to that
     /// of the EVM. This means that, under the hood, this hostio represents storing a 32-byte value into
     /// the EVM state trie at offset `key`. Refunds are tabulated exactly as in the EVM. The semantics, then,
@@ -221,6 +305,41 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("transient_store_bytes32", self, [key, value], &[])
     }
 
+    /// Atomically replaces the transient value at `key` with `value` if it equals `expected`,
+    /// returning `1` if so and `0` otherwise. This is equivalent to a [`TLOAD`] followed by a
+    /// conditional [`TSTORE`], and so is a cheap way of implementing reentrancy guards. The
+    /// store is only paid for if it happens.
+    ///
+    /// [`TLOAD`]: https://www.evm.codes/#5c
+    /// [`TSTORE`]: https://www.evm.codes/#5d
+    fn transient_compare_and_set(
+        &mut self,
+        key: GuestPtr,
+        expected: GuestPtr,
+        value: GuestPtr,
+    ) -> Result<u32, Self::Err> {
+        self.buy_ink(HOSTIO_INK + 3 * PTR_INK + EVM_API_INK)?;
+        self.buy_gas(evm::TLOAD_GAS)?;
+
+        let key = self.read_bytes32(key)?;
+        let expected = self.read_bytes32(expected)?;
+        let value = self.read_bytes32(value)?;
+
+        let swapped = self.evm_api().get_transient_bytes32(key) == expected;
+        if swapped {
+            self.buy_gas(evm::TSTORE_GAS)?;
+            self.evm_api().set_transient_bytes32(key, value)?;
+        }
+        let swapped = swapped as u32;
+        trace!(
+            "transient_compare_and_set",
+            self,
+            [key, expected, value],
+            be!(swapped),
+            swapped
+        )
+    }
+
     /// Calls the contract at the given address with options for passing value and to limit the
     /// amount of gas supplied. The return status indicates whether the call succeeded, and is
     /// nonzero on failure.
@@ -518,6 +637,23 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         )
     }
 
+    /// Gets the size of the current program's own deployed code in bytes. The semantics are
+    /// equivalent to that of the EVM's [`CODE_SIZE`] opcode.
+    ///
+    /// [`CODE_SIZE`]: https://www.evm.codes/#38
+    fn contract_code_size(&mut self) -> Result<u32, Self::Err> {
+        self.buy_ink(HOSTIO_INK + EVM_API_INK)?;
+        let address = self.evm_data().contract_address;
+        let gas = self.gas_left()?;
+
+        // the current program is always warm, so this won't charge for a cold access
+        let (code, gas_cost) = self.evm_api().account_code(address, gas);
+        self.buy_gas(gas_cost)?;
+
+        let size = code.slice().len() as u32;
+        trace!("contract_code_size", self, &[], be!(size), size)
+    }
+
     /// Returns the length of the last EVM call or deployment return result, or `0` if neither have
     /// happened during the program's execution. The semantics are equivalent to that of the EVM's
     /// [`RETURN_DATA_SIZE`] opcode.
@@ -544,14 +680,56 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         if topics > 4 || len < topics * 32 {
             Err(eyre!("bad topic data"))?;
         }
+        let max_logs = self.config().max_logs;
+        if max_logs != 0 && *self.logs_emitted() >= max_logs {
+            Err(eyre!("too many logs"))?;
+        }
         self.pay_for_read(len)?;
         self.pay_for_evm_log(topics, len - topics * 32)?;
 
         let data = self.read_slice(data, len)?;
         self.evm_api().emit_log(data.clone(), topics)?;
+        *self.logs_emitted() += 1;
         trace!("emit_log", self, [be!(topics), data], &[])
     }
 
+    /// Emits an EVM log whose first topic is the keccak of the given event signature, such as
+    /// `Transfer(address,address,uint256)`. The `data` is otherwise laid out as in `emit_log`,
+    /// with `topics` counting only the indexed topics that follow the signature's, of which
+    /// there may be at most `3`. This saves programs from hashing the signature themselves.
+    fn emit_log_with_signature(
+        &mut self,
+        signature: GuestPtr,
+        signature_len: u32,
+        data: GuestPtr,
+        len: u32,
+        topics: u32,
+    ) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + EVM_API_INK)?;
+        if topics > 3 || len < topics * 32 {
+            Err(eyre!("bad topic data"))?;
+        }
+        let max_logs = self.config().max_logs;
+        if max_logs != 0 && *self.logs_emitted() >= max_logs {
+            Err(eyre!("too many logs"))?;
+        }
+        self.pay_for_keccak(signature_len)?;
+        self.pay_for_read(len)?;
+        self.pay_for_evm_log(topics + 1, len - topics * 32)?;
+
+        let signature = self.read_slice(signature, signature_len)?;
+        let data = self.read_slice(data, len)?;
+        let log = [&crypto::keccak(&signature)[..], &data].concat();
+        self.evm_api().emit_log(log, topics + 1)?;
+        *self.logs_emitted() += 1;
+        trace!(
+            "emit_log_with_signature",
+            self,
+            [be!(topics), signature, data],
+            &[]
+        )
+    }
+
     /// Gets the ETH balance in wei of the account at the given address.
     /// The semantics are equivalent to that of the EVM's [`BALANCE`] opcode.
     ///
@@ -641,6 +819,32 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("account_codehash", self, address, hash)
     }
 
+    /// Whether the account at the given address has code, as if by checking that the EVM's
+    /// [`EXT_CODESIZE`] is nonzero. The code hash is used instead of loading the code, so
+    /// the cost is that of [`EXT_CODEHASH`].
+    ///
+    /// [`EXT_CODESIZE`]: https://www.evm.codes/#3B
+    /// [`EXT_CODEHASH`]: https://www.evm.codes/#3F
+    fn account_is_contract(&mut self, address: GuestPtr) -> Result<u32, Self::Err> {
+        self.buy_ink(HOSTIO_INK + PTR_INK + EVM_API_INK)?;
+        self.require_gas(evm::COLD_ACCOUNT_GAS)?;
+        let address = self.read_bytes20(address)?;
+
+        // nonexistent accounts hash to zero, and those without code to the empty hash
+        let (hash, gas_cost) = self.evm_api().account_codehash(address);
+        self.buy_gas(gas_cost)?;
+        let empty = Bytes32(crypto::keccak(b""));
+        let is_contract = hash != Bytes32::default() && hash != empty;
+        let is_contract = is_contract as u32;
+        trace!(
+            "account_is_contract",
+            self,
+            address,
+            be!(is_contract),
+            is_contract
+        )
+    }
+
     /// Gets the basefee of the current block. The semantics are equivalent to that of the EVM's
     /// [`BASEFEE`] opcode.
     ///
@@ -651,6 +855,31 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("block_basefee", self, &[], self.evm_data().block_basefee)
     }
 
+    /// Gets the blob basefee of the current block. The semantics are equivalent to that of the
+    /// EVM's [`BLOBBASEFEE`] opcode.
+    ///
+    /// [`BLOBBASEFEE`]: https://www.evm.codes/#4a
+    fn block_blob_basefee(&mut self, ptr: GuestPtr) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + PTR_INK)?;
+        self.write_bytes32(ptr, self.evm_data().block_blob_basefee)?;
+        trace!(
+            "block_blob_basefee",
+            self,
+            &[],
+            self.evm_data().block_blob_basefee
+        )
+    }
+
+    /// Gets the excess blob gas of the current block, from which [`EIP-4844`] derives the blob
+    /// basefee. The EVM has no opcode for this, so it's read from the block header.
+    ///
+    /// [`EIP-4844`]: https://eips.ethereum.org/EIPS/eip-4844
+    fn block_excess_blob_gas(&mut self) -> Result<u64, Self::Err> {
+        self.buy_ink(HOSTIO_INK)?;
+        let excess = self.evm_data().block_excess_blob_gas;
+        trace!("block_excess_blob_gas", self, &[], be!(excess), excess)
+    }
+
     /// Gets the coinbase of the current block, which on Arbitrum chains is the L1 batch poster's
     /// address. This differs from Ethereum where the validator including the transaction
     /// determines the coinbase.
@@ -684,6 +913,25 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("block_number", self, &[], be!(number), number)
     }
 
+    /// Gets the L1 block number, which is what `block_number` returns on Arbitrum chains. This is
+    /// an explicit alias for programs that need to disambiguate it from the L2 block number.
+    /// See [`Block Numbers and Time`] for more information on how this value is determined.
+    ///
+    /// [`Block Numbers and Time`]: https://developer.arbitrum.io/time
+    fn arb_l1_block_number(&mut self) -> Result<u64, Self::Err> {
+        self.buy_ink(HOSTIO_INK)?;
+        let number = self.evm_data().block_number;
+        trace!("arb_l1_block_number", self, &[], be!(number), number)
+    }
+
+    /// Gets the number of the L2 block containing the transaction, which is the value that the
+    /// `ArbSys` precompile's `arbBlockNumber` method returns.
+    fn arb_l2_block_number(&mut self) -> Result<u64, Self::Err> {
+        self.buy_ink(HOSTIO_INK)?;
+        let number = self.evm_data().l2_block_number;
+        trace!("arb_l2_block_number", self, &[], be!(number), number)
+    }
+
     /// Gets a bounded estimate of the Unix timestamp at which the Sequencer sequenced the
     /// transaction. See [`Block Numbers and Time`] for more information on how this value is
     /// determined.
@@ -695,6 +943,22 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("block_timestamp", self, &[], be!(timestamp), timestamp)
     }
 
+    /// Gets the `PREVRANDAO` of the current block, writing the 32-byte value to the given pointer.
+    /// The semantics are equivalent to that of the EVM's [`PREVRANDAO`] opcode. Arbitrum chains
+    /// don't produce randomness this way, so the value is a constant and must not be relied upon.
+    ///
+    /// [`PREVRANDAO`]: https://www.evm.codes/#44
+    fn block_prevrandao(&mut self, ptr: GuestPtr) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + PTR_INK)?;
+        self.write_bytes32(ptr, self.evm_data().block_prevrandao)?;
+        trace!(
+            "block_prevrandao",
+            self,
+            &[],
+            self.evm_data().block_prevrandao
+        )
+    }
+
     /// Gets the unique chain identifier of the Arbitrum chain. The semantics are equivalent to
     /// that of the EVM's [`CHAIN_ID`] opcode.
     ///
@@ -705,6 +969,20 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("chainid", self, &[], be!(chainid), chainid)
     }
 
+    /// Gets a constant of the chain's configuration, as identified by a [`ChainParam`] id.
+    /// Unknown ids read as zero, so programs may probe for parameters newer runtimes support.
+    /// Not analogous to any EVM opcode.
+    ///
+    /// [`ChainParam`]: arbutil::evm::ChainParam
+    fn arb_chain_param(&mut self, param: u32) -> Result<u64, Self::Err> {
+        self.buy_ink(HOSTIO_INK + EVM_API_INK)?;
+        let value = match ChainParam::try_from(param) {
+            Ok(param) => self.evm_api().chain_param(param),
+            Err(_) => 0,
+        };
+        trace!("arb_chain_param", self, be!(param), be!(value), value)
+    }
+
     /// Gets the address of the current program. The semantics are equivalent to that of the EVM's
     /// [`ADDRESS`] opcode.
     ///
@@ -720,6 +998,43 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         )
     }
 
+    /// Copies a subset of the current program's own deployed code into `dest`. The semantics are
+    /// equivalent to that of the EVM's [`CODE_COPY`] opcode, aside from one small detail: as with
+    /// `account_code`, the write to the buffer `dest` will stop after the last byte is written
+    /// rather than right padding with zeros. The return value is the number of bytes written.
+    ///
+    /// [`CODE_COPY`]: https://www.evm.codes/#39
+    fn contract_code_copy(
+        &mut self,
+        offset: u32,
+        size: u32,
+        dest: GuestPtr,
+    ) -> Result<u32, Self::Err> {
+        self.buy_ink(HOSTIO_INK + EVM_API_INK)?;
+        self.buy_gas(evm::COPY_WORD_GAS.saturating_mul(evm::evm_words(size).into()))?;
+
+        let address = self.evm_data().contract_address;
+        let gas = self.gas_left()?;
+
+        // the current program is always warm, so this will only charge for the copy
+        let (code, gas_cost) = self.evm_api().account_code(address, gas);
+        self.buy_gas(gas_cost)?;
+
+        let code = code.slice();
+        let out_slice = arbutil::slice_with_runoff(&code, offset, offset.saturating_add(size));
+        let out_len = out_slice.len() as u32;
+        self.pay_for_write(out_len)?;
+        self.write_slice(dest, out_slice)?;
+
+        trace!(
+            "contract_code_copy",
+            self,
+            [be!(offset), be!(size)],
+            out_slice.to_vec(),
+            out_len
+        )
+    }
+
     /// Gets the amount of gas left after paying for the cost of this hostio. The semantics are
     /// equivalent to that of the EVM's [`GAS`] opcode.
     ///
@@ -772,6 +1087,34 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("math_mod", self, [a32, b32], result)
     }
 
+    /// Computes `value + addend` using 256-bit math, writing the result to the first.
+    /// The semantics are equivalent to that of the EVM's [`ADD`] opcode, which means that overflow wraps.
+    ///
+    /// [`ADD`]: https://www.evm.codes/#01
+    fn math_add(&mut self, value: GuestPtr, addend: GuestPtr) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + 3 * PTR_INK + pricing::ADD_INK)?;
+        let (a, a32) = self.read_u256(value)?;
+        let (b, b32) = self.read_u256(addend)?;
+
+        let result = a.wrapping_add(b).into();
+        self.write_bytes32(value, result)?;
+        trace!("math_add", self, [a32, b32], result)
+    }
+
+    /// Computes `value * multiplier` using 256-bit math, writing the result to the first.
+    /// The semantics are equivalent to that of the EVM's [`MUL`] opcode, which means that overflow wraps.
+    ///
+    /// [`MUL`]: https://www.evm.codes/#02
+    fn math_mul(&mut self, value: GuestPtr, multiplier: GuestPtr) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + 3 * PTR_INK + pricing::MUL_INK)?;
+        let (a, a32) = self.read_u256(value)?;
+        let (b, b32) = self.read_u256(multiplier)?;
+
+        let result = a.wrapping_mul(b).into();
+        self.write_bytes32(value, result)?;
+        trace!("math_mul", self, [a32, b32], result)
+    }
+
     /// Computes `value ^ exponent` using 256-bit math, writing the result to the first.
     /// The semantics are equivalent to that of the EVM's [`EXP`] opcode.
     ///
@@ -836,6 +1179,16 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("msg_reentrant", self, &[], be!(reentrant), reentrant)
     }
 
+    /// Whether the current call is static, in which case any state changes will revert.
+    /// The semantics are equivalent to that of the EVM's [`STATICCALL`] context.
+    ///
+    /// [`STATICCALL`]: https://www.evm.codes/#fa
+    fn is_static(&mut self) -> Result<u32, Self::Err> {
+        self.buy_ink(HOSTIO_INK)?;
+        let is_static = self.evm_data().is_static as u32;
+        trace!("is_static", self, &[], be!(is_static), is_static)
+    }
+
     /// Gets the address of the account that called the program. For normal L2-to-L2 transactions
     /// the semantics are equivalent to that of the EVM's [`CALLER`] opcode, including in cases
     /// arising from [`DELEGATE_CALL`].
@@ -881,6 +1234,149 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("native_keccak256", self, preimage, digest)
     }
 
+    /// Computes the [`keccak256`] hash of two concatenated words, as when hashing the nodes of a
+    /// Merkle tree. Equivalent to `native_keccak256` of the 64-byte preimage `left || right`, but
+    /// without the length handling, so the ink charged is fixed.
+    ///
+    /// [`keccak256`]: https://en.wikipedia.org/wiki/SHA-3
+    fn keccak256_pair(
+        &mut self,
+        left: GuestPtr,
+        right: GuestPtr,
+        output: GuestPtr,
+    ) -> Result<(), Self::Err> {
+        self.buy_ink(pricing::KECCAK_INK)?;
+
+        let left = self.read_bytes32(left)?;
+        let right = self.read_bytes32(right)?;
+        let digest = crypto::keccak([left.0, right.0].concat());
+        self.write_bytes32(output, digest.into())?;
+        trace!("keccak256_pair", self, [left, right], digest)
+    }
+
+    /// Efficiently computes the [`sha256`] hash of the given preimage.
+    /// The semantics and gas costs are equivalent to that of the EVM's [`SHA256`] precompile.
+    ///
+    /// [`sha256`]: https://en.wikipedia.org/wiki/SHA-2
+    /// [`SHA256`]: https://www.evm.codes/precompiled#0x02
+    fn native_sha256(
+        &mut self,
+        input: GuestPtr,
+        len: u32,
+        output: GuestPtr,
+    ) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + 2 * PTR_INK)?;
+        self.pay_for_read(len)?;
+
+        let words = evm::evm_words(len).into();
+        self.buy_gas(evm::SHA256_GAS.saturating_add(evm::SHA256_WORD_GAS.saturating_mul(words)))?;
+
+        let preimage = self.read_slice(input, len)?;
+        let digest = crypto::sha256(&preimage);
+        self.write_bytes32(output, digest.into())?;
+        trace!("native_sha256", self, preimage, digest)
+    }
+
+    /// Efficiently computes the [`ripemd160`] hash of the given preimage, writing the 20-byte
+    /// digest left-padded to 32 bytes. The semantics and gas costs are equivalent to that of the
+    /// EVM's [`RIPEMD160`] precompile.
+    ///
+    /// [`ripemd160`]: https://en.wikipedia.org/wiki/RIPEMD
+    /// [`RIPEMD160`]: https://www.evm.codes/precompiled#0x03
+    fn native_ripemd160(
+        &mut self,
+        input: GuestPtr,
+        len: u32,
+        output: GuestPtr,
+    ) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + 2 * PTR_INK)?;
+        self.pay_for_read(len)?;
+
+        let words = evm::evm_words(len).into();
+        let gas = evm::RIPEMD160_WORD_GAS.saturating_mul(words);
+        self.buy_gas(evm::RIPEMD160_GAS.saturating_add(gas))?;
+
+        let preimage = self.read_slice(input, len)?;
+        let mut digest = [0; 32];
+        digest[12..].copy_from_slice(&crypto::ripemd160(&preimage));
+        self.write_bytes32(output, digest.into())?;
+        trace!("native_ripemd160", self, preimage, digest)
+    }
+
+    /// Efficiently computes `base ** exponent % modulus` over arbitrarily-sized big-endian integers,
+    /// writing `mod_len` bytes to `output`. The semantics and gas costs are equivalent to that of
+    /// the EVM's [`MODEXP`] precompile, which means that a `modulus` of `0` writes all zeros.
+    ///
+    /// [`MODEXP`]: https://www.evm.codes/precompiled#0x05
+    fn native_modexp(
+        &mut self,
+        base: GuestPtr,
+        base_len: u32,
+        exponent: GuestPtr,
+        exp_len: u32,
+        modulus: GuestPtr,
+        mod_len: u32,
+        output: GuestPtr,
+    ) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + 4 * PTR_INK)?;
+        self.pay_for_read(base_len.saturating_add(exp_len).saturating_add(mod_len))?;
+        self.pay_for_write(mod_len)?;
+
+        let exp = self.read_slice(exponent, exp_len)?;
+        self.buy_gas(evm::modexp_gas(base_len, &exp, mod_len))?;
+
+        let value = self.read_slice(base, base_len)?;
+        let modu = self.read_slice(modulus, mod_len)?;
+
+        let mut result = vec![0; mod_len as usize];
+        let m = BigUint::from_bytes_be(&modu);
+        if m != BigUint::default() {
+            let b = BigUint::from_bytes_be(&value);
+            let e = BigUint::from_bytes_be(&exp);
+            let digits = b.modpow(&e, &m).to_bytes_be();
+            result[mod_len as usize - digits.len()..].copy_from_slice(&digits);
+        }
+        self.write_slice(output, &result)?;
+        trace!("native_modexp", self, [value, exp, modu].concat(), result)
+    }
+
+    /// Checks whether the product of the bn256 pairings of the given (G1, G2) points is one,
+    /// returning `1` if so and `0` otherwise. The semantics and gas costs are equivalent to that
+    /// of the EVM's [`ECPAIRING`] precompile, which means malformed input reverts.
+    ///
+    /// [`ECPAIRING`]: https://www.evm.codes/precompiled#0x08
+    fn native_ecpairing(&mut self, input: GuestPtr, len: u32) -> Result<u32, Self::Err> {
+        self.buy_ink(HOSTIO_INK + PTR_INK)?;
+        self.pay_for_read(len)?;
+
+        if len as usize % crypto::BN256_PAIR_LEN != 0 {
+            Err(eyre!("invalid ecpairing input length {len}"))?;
+        }
+        let pairs = (len as usize / crypto::BN256_PAIR_LEN) as u64;
+        let gas = evm::ECPAIRING_POINT_GAS.saturating_mul(pairs);
+        self.buy_gas(evm::ECPAIRING_GAS.saturating_add(gas))?;
+
+        let points = self.read_slice(input, len)?;
+        let success = crypto::bn256_pairing(&points)? as u32;
+        trace!("native_ecpairing", self, points, be!(success), success)
+    }
+
+    /// Recovers the address that signed a hash, writing the 20-byte address to `output`, or all
+    /// zeros if the signature is invalid. The input is the 32-byte words `hash`, `v`, `r`, and
+    /// `s`, with `v` being 27 or 28. The semantics and gas costs are equivalent to that of the
+    /// EVM's [`ECRECOVER`] precompile.
+    ///
+    /// [`ECRECOVER`]: https://www.evm.codes/precompiled#0x01
+    fn native_ecrecover(&mut self, input: GuestPtr, output: GuestPtr) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + 2 * PTR_INK)?;
+        self.buy_gas(evm::ECRECOVER_GAS)?;
+
+        let signature = self.read_fixed::<128>(input)?;
+        let signer = crypto::ecrecover(&signature).unwrap_or_default();
+        self.write_slice(output, &signer)?;
+        trace!("native_ecrecover", self, signature, signer)
+    }
+
     /// Gets the gas price in wei per gas, which on Arbitrum chains equals the basefee. The
     /// semantics are equivalent to that of the EVM's [`GAS_PRICE`] opcode.
     ///
@@ -891,6 +1387,19 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("tx_gas_price", self, &[], self.evm_data().tx_gas_price)
     }
 
+    /// Gets the effective gas price of [`EIP-1559`] in wei per gas, which is the basefee plus the
+    /// tip, capped by the max fee per gas. Unlike [`tx_gas_price`], this is computed from the
+    /// transaction's fee parameters.
+    ///
+    /// [`EIP-1559`]: https://eips.ethereum.org/EIPS/eip-1559
+    /// [`tx_gas_price`]: Self::tx_gas_price
+    fn effective_gas_price(&mut self, ptr: GuestPtr) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + PTR_INK)?;
+        let price = self.evm_data().effective_gas_price();
+        self.write_bytes32(ptr, price)?;
+        trace!("effective_gas_price", self, &[], price)
+    }
+
     /// Gets the price of ink in evm gas basis points. See [`Ink and Gas`] for more information on
     /// Stylus's compute-pricing model.
     ///
@@ -911,7 +1420,23 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         trace!("tx_origin", self, &[], self.evm_data().tx_origin)
     }
 
+    /// Gets the L1 address that sent the transaction, for transactions submitted from L1 such as
+    /// retryables. Unlike `tx_origin`, which is aliased for these transactions, this is the
+    /// original L1 address, letting programs authenticate L1 callers. The zero address is
+    /// returned for transactions that originate on L2.
+    fn arb_l1_sender(&mut self, ptr: GuestPtr) -> Result<(), Self::Err> {
+        self.buy_ink(HOSTIO_INK + PTR_INK)?;
+        self.write_bytes20(ptr, self.evm_data().l1_sender)?;
+        trace!("arb_l1_sender", self, &[], self.evm_data().l1_sender)
+    }
+
     /// Pays for new pages as needed before the memory.grow opcode is invoked.
+    ///
+    /// Rather than the EVM's quadratic curve, each page beyond the tx's free pages costs a flat
+    /// amount, plus an exponential term in the most pages ever open during the tx.
+    /// See [`MemoryModel`] for details.
+    ///
+    /// [`MemoryModel`]: prover::programs::memory::MemoryModel
     fn pay_for_memory_grow(&mut self, pages: u16) -> Result<(), Self::Err> {
         if pages == 0 {
             self.buy_ink(HOSTIO_INK)?;
@@ -919,9 +1444,19 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         }
         let gas_cost = self.evm_api().add_pages(pages); // no sentry needed since the work happens after the hostio
         self.buy_gas(gas_cost)?;
+
+        let charged = self.memory_gas();
+        *charged = charged.saturating_add(gas_cost);
         trace!("pay_for_memory_grow", self, be!(pages), &[])
     }
 
+    /// Gets the total gas charged for memory expansion so far during the current call.
+    fn memory_gas_charged(&mut self) -> Result<u64, Self::Err> {
+        self.buy_ink(HOSTIO_INK)?;
+        let gas = *self.memory_gas();
+        trace!("memory_gas_charged", self, &[], be!(gas), gas)
+    }
+
     /// Prints a UTF-8 encoded string to the console. Only available in debug mode.
     fn console_log_text(&mut self, ptr: GuestPtr, len: u32) -> Result<(), Self::Err> {
         let text = self.read_slice(ptr, len)?;
@@ -941,4 +1476,25 @@ pub trait UserHost<DR: DataReader>: GasMeteredMachine {
         self.say(value.into());
         Ok(value)
     }
+
+    /// Prints a signed 64-bit integer to the console. Only available in debug mode.
+    fn debug_print_i64(&mut self, value: u64) -> Result<(), Self::Err> {
+        self.say(value as i64);
+        trace!("debug_print_i64", self, be!(value), &[])
+    }
+
+    /// Prints a region of memory to the console in hex. Only available in debug mode.
+    fn debug_dump_memory(&mut self, ptr: GuestPtr, len: u32) -> Result<(), Self::Err> {
+        let data = self.read_slice(ptr, len)?;
+        let hex: String = data.iter().map(|byte| format!("{byte:02x}")).collect();
+        self.say(format!("memory[{:#x}..+{len}]: {hex}", *ptr));
+        trace!("debug_dump_memory", self, [be!(ptr), be!(len)], &[])
+    }
+
+    /// Reports the ink left, giving native debuggers a place to break. Only available in debug mode.
+    fn debug_breakpoint(&mut self) -> Result<(), Self::Err> {
+        let ink = self.ink_ready()?;
+        self.say(format!("breakpoint with {ink} ink left"));
+        trace!("debug_breakpoint", self, &[], &[])
+    }
 }
@@ -38,6 +38,20 @@ pub unsafe extern "C" fn user_host__exit_early(status: u32) {
     set_trap();
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__finish_return(ptr: GuestPtr, len: u32) {
+    hostio!(finish_return(ptr, len));
+    Program::current().early_exit = Some(UserOutcomeKind::Success);
+    set_trap();
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__finish_revert(ptr: GuestPtr, len: u32) {
+    hostio!(finish_revert(ptr, len));
+    Program::current().early_exit = Some(UserOutcomeKind::Revert);
+    set_trap();
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__write_result(ptr: GuestPtr, len: u32) {
     hostio!(write_result(ptr, len))
@@ -48,6 +62,11 @@ pub unsafe extern "C" fn user_host__storage_load_bytes32(key: GuestPtr, dest: Gu
     hostio!(storage_load_bytes32(key, dest))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__storage_load_bytes(key: GuestPtr, dest: GuestPtr, len: u32) {
+    hostio!(storage_load_bytes(key, dest, len))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__storage_cache_bytes32(key: GuestPtr, value: GuestPtr) {
     hostio!(storage_cache_bytes32(key, value))
@@ -68,6 +87,15 @@ pub unsafe extern "C" fn user_host__transient_store_bytes32(key: GuestPtr, value
     hostio!(transient_store_bytes32(key, value))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__transient_compare_and_set(
+    key: GuestPtr,
+    expected: GuestPtr,
+    value: GuestPtr,
+) -> u32 {
+    hostio!(transient_compare_and_set(key, expected, value))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__call_contract(
     contract: GuestPtr,
@@ -146,6 +174,23 @@ pub unsafe extern "C" fn user_host__emit_log(data: GuestPtr, len: u32, topics: u
     hostio!(emit_log(data, len, topics))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__emit_log_with_signature(
+    signature: GuestPtr,
+    signature_len: u32,
+    data: GuestPtr,
+    len: u32,
+    topics: u32,
+) {
+    hostio!(emit_log_with_signature(
+        signature,
+        signature_len,
+        data,
+        len,
+        topics
+    ))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__account_balance(address: GuestPtr, ptr: GuestPtr) {
     hostio!(account_balance(address, ptr))
@@ -170,16 +215,36 @@ pub unsafe extern "C" fn user_host__account_codehash(address: GuestPtr, ptr: Gue
     hostio!(account_codehash(address, ptr))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__account_is_contract(address: GuestPtr) -> u32 {
+    hostio!(account_is_contract(address))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__block_basefee(ptr: GuestPtr) {
     hostio!(block_basefee(ptr))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__block_blob_basefee(ptr: GuestPtr) {
+    hostio!(block_blob_basefee(ptr))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__block_excess_blob_gas() -> u64 {
+    hostio!(block_excess_blob_gas())
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__block_coinbase(ptr: GuestPtr) {
     hostio!(block_coinbase(ptr))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__block_prevrandao(ptr: GuestPtr) {
+    hostio!(block_prevrandao(ptr))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__block_gas_limit() -> u64 {
     hostio!(block_gas_limit())
@@ -190,6 +255,16 @@ pub unsafe extern "C" fn user_host__block_number() -> u64 {
     hostio!(block_number())
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__arb_l1_block_number() -> u64 {
+    hostio!(arb_l1_block_number())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__arb_l2_block_number() -> u64 {
+    hostio!(arb_l2_block_number())
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__block_timestamp() -> u64 {
     hostio!(block_timestamp())
@@ -200,11 +275,30 @@ pub unsafe extern "C" fn user_host__chainid() -> u64 {
     hostio!(chainid())
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__arb_chain_param(param: u32) -> u64 {
+    hostio!(arb_chain_param(param))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__contract_address(ptr: GuestPtr) {
     hostio!(contract_address(ptr))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__contract_code_copy(
+    offset: u32,
+    size: u32,
+    dest: GuestPtr,
+) -> u32 {
+    hostio!(contract_code_copy(offset, size, dest))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__contract_code_size() -> u32 {
+    hostio!(contract_code_size())
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__evm_gas_left() -> u64 {
     hostio!(evm_gas_left())
@@ -225,6 +319,16 @@ pub unsafe extern "C" fn user_host__math_mod(value: GuestPtr, modulus: GuestPtr)
     hostio!(math_mod(value, modulus))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__math_add(value: GuestPtr, addend: GuestPtr) {
+    hostio!(math_add(value, addend))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__math_mul(value: GuestPtr, multiplier: GuestPtr) {
+    hostio!(math_mul(value, multiplier))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__math_pow(value: GuestPtr, exponent: GuestPtr) {
     hostio!(math_pow(value, exponent))
@@ -253,6 +357,11 @@ pub unsafe extern "C" fn user_host__msg_reentrant() -> u32 {
     hostio!(msg_reentrant())
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__is_static() -> u32 {
+    hostio!(is_static())
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__msg_sender(ptr: GuestPtr) {
     hostio!(msg_sender(ptr))
@@ -268,11 +377,60 @@ pub unsafe extern "C" fn user_host__native_keccak256(input: GuestPtr, len: u32,
     hostio!(native_keccak256(input, len, output))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__keccak256_pair(
+    left: GuestPtr,
+    right: GuestPtr,
+    output: GuestPtr,
+) {
+    hostio!(keccak256_pair(left, right, output))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__native_sha256(input: GuestPtr, len: u32, output: GuestPtr) {
+    hostio!(native_sha256(input, len, output))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__native_ripemd160(input: GuestPtr, len: u32, output: GuestPtr) {
+    hostio!(native_ripemd160(input, len, output))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__native_modexp(
+    base: GuestPtr,
+    base_len: u32,
+    exponent: GuestPtr,
+    exp_len: u32,
+    modulus: GuestPtr,
+    mod_len: u32,
+    output: GuestPtr,
+) {
+    hostio!(native_modexp(
+        base, base_len, exponent, exp_len, modulus, mod_len, output
+    ))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__native_ecpairing(input: GuestPtr, len: u32) -> u32 {
+    hostio!(native_ecpairing(input, len))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__native_ecrecover(input: GuestPtr, output: GuestPtr) {
+    hostio!(native_ecrecover(input, output))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__tx_gas_price(ptr: GuestPtr) {
     hostio!(tx_gas_price(ptr))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__effective_gas_price(ptr: GuestPtr) {
+    hostio!(effective_gas_price(ptr))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__tx_ink_price() -> u32 {
     hostio!(tx_ink_price())
@@ -283,7 +441,17 @@ pub unsafe extern "C" fn user_host__tx_origin(ptr: GuestPtr) {
     hostio!(tx_origin(ptr))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__arb_l1_sender(ptr: GuestPtr) {
+    hostio!(arb_l1_sender(ptr))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__pay_for_memory_grow(pages: u16) {
     hostio!(pay_for_memory_grow(pages))
 }
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__memory_gas_charged() -> u64 {
+    hostio!(memory_gas_charged())
+}
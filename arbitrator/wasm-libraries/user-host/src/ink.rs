@@ -29,6 +29,18 @@ impl MeteredMachine for Program {
             user_set_ink(meter.ink(), meter.status());
         }
     }
+
+    fn set_ink_left(&mut self, ink: u64) {
+        unsafe {
+            user_set_ink(ink, user_ink_status());
+        }
+    }
+
+    fn set_ink_status(&mut self, status: u32) {
+        unsafe {
+            user_set_ink(user_ink_left(), status);
+        }
+    }
 }
 
 impl GasMeteredMachine for Program {
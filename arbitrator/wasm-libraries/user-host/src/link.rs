@@ -231,9 +231,11 @@ pub unsafe extern "C" fn programs__create_stylus_config(
     version: u16,
     max_depth: u32,
     ink_price: u32,
+    max_logs: u32,
     _debug: u32,
 ) -> u64 {
-    let config = StylusConfig::new(version, max_depth, ink_price);
+    let mut config = StylusConfig::new(version, max_depth, ink_price);
+    config.max_logs = max_logs;
     heapify(config) as u64
 }
 
@@ -242,35 +244,51 @@ pub unsafe extern "C" fn programs__create_stylus_config(
 #[no_mangle]
 pub unsafe extern "C" fn programs__create_evm_data(
     block_basefee_ptr: GuestPtr,
+    block_blob_basefee_ptr: GuestPtr,
+    block_excess_blob_gas: u64,
     chainid: u64,
     block_coinbase_ptr: GuestPtr,
     block_gas_limit: u64,
     block_number: u64,
+    l2_block_number: u64,
     block_timestamp: u64,
+    block_prevrandao_ptr: GuestPtr,
     contract_address_ptr: GuestPtr,
     module_hash_ptr: GuestPtr,
     msg_sender_ptr: GuestPtr,
     msg_value_ptr: GuestPtr,
     tx_gas_price_ptr: GuestPtr,
+    tx_gas_fee_cap_ptr: GuestPtr,
+    tx_gas_tip_cap_ptr: GuestPtr,
     tx_origin_ptr: GuestPtr,
+    l1_sender_ptr: GuestPtr,
     cached: u32,
     reentrant: u32,
+    is_static: u32,
 ) -> u64 {
     let evm_data = EvmData {
         block_basefee: read_bytes32(block_basefee_ptr),
+        block_blob_basefee: read_bytes32(block_blob_basefee_ptr),
+        block_excess_blob_gas,
         cached: cached != 0,
         chainid,
         block_coinbase: read_bytes20(block_coinbase_ptr),
         block_gas_limit,
         block_number,
+        l2_block_number,
         block_timestamp,
+        block_prevrandao: read_bytes32(block_prevrandao_ptr),
         contract_address: read_bytes20(contract_address_ptr),
         module_hash: read_bytes32(module_hash_ptr),
         msg_sender: read_bytes20(msg_sender_ptr),
         msg_value: read_bytes32(msg_value_ptr),
         tx_gas_price: read_bytes32(tx_gas_price_ptr),
+        tx_gas_fee_cap: read_bytes32(tx_gas_fee_cap_ptr),
+        tx_gas_tip_cap: read_bytes32(tx_gas_tip_cap_ptr),
         tx_origin: read_bytes20(tx_origin_ptr),
+        l1_sender: read_bytes20(l1_sender_ptr),
         reentrant,
+        is_static: is_static != 0,
         return_data_len: 0,
         tracing: false,
     };
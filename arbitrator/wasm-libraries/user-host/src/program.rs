@@ -81,6 +81,10 @@ pub(crate) struct Program {
     pub config: StylusConfig,
     /// Whether the program exited early.
     pub early_exit: Option<UserOutcomeKind>,
+    /// The number of logs emitted so far.
+    pub logs_emitted: u32,
+    /// The gas charged for memory expansion so far.
+    pub memory_gas: u64,
 }
 
 #[link(wasm_import_module = "hostio")]
@@ -170,6 +174,8 @@ impl Program {
             module,
             config,
             early_exit: None,
+            logs_emitted: 0,
+            memory_gas: 0,
         };
         unsafe { PROGRAMS.push(Box::new(program)) }
     }
@@ -196,15 +202,6 @@ impl Program {
         self.args.len()
     }
 
-    /// Ensures an access is within bounds
-    fn check_memory_access(&self, ptr: GuestPtr, bytes: u32) -> Result<(), MemoryBoundsError> {
-        let last_page = ptr.saturating_add(bytes) / (WASM_PAGE_SIZE as u32);
-        if last_page > self.memory_size() {
-            return Err(MemoryBoundsError);
-        }
-        Ok(())
-    }
-
     pub fn request_handler(&mut self) -> &mut UserHostRequester {
         self.evm_api.request_handler()
     }
@@ -236,6 +233,18 @@ impl UserHost<VecReader> for Program {
         &mut self.evm_data.return_data_len
     }
 
+    fn logs_emitted(&mut self) -> &mut u32 {
+        &mut self.logs_emitted
+    }
+
+    fn memory_gas(&mut self) -> &mut u64 {
+        &mut self.memory_gas
+    }
+
+    fn config(&self) -> StylusConfig {
+        self.config
+    }
+
     fn read_slice(&self, ptr: GuestPtr, len: u32) -> Result<Vec<u8>, MemoryBoundsError> {
         self.check_memory_access(ptr, len)?;
         unsafe { Ok(STATIC_MEM.read_slice(ptr, len as usize)) }
@@ -256,6 +265,14 @@ impl UserHost<VecReader> for Program {
         unsafe { Ok(STATIC_MEM.write_slice(ptr, src)) }
     }
 
+    fn check_memory_access(&self, ptr: GuestPtr, bytes: u32) -> Result<(), MemoryBoundsError> {
+        let last_page = ptr.saturating_add(bytes) / (WASM_PAGE_SIZE as u32);
+        if last_page > self.memory_size() {
+            return Err(MemoryBoundsError);
+        }
+        Ok(())
+    }
+
     fn say<D: Display>(&self, text: D) {
         println!("{} {text}", "Stylus says:".yellow());
     }
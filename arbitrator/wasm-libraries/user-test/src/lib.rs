@@ -16,6 +16,8 @@ mod program;
 pub(crate) static mut ARGS: Vec<u8> = vec![];
 pub(crate) static mut OUTS: Vec<u8> = vec![];
 pub(crate) static mut LOGS: Vec<Vec<u8>> = vec![];
+pub(crate) static mut LOGS_EMITTED: u32 = 0;
+pub(crate) static mut MEMORY_GAS: u64 = 0;
 pub(crate) static mut CONFIG: Option<StylusConfig> = None;
 pub(crate) static mut OPEN_PAGES: u16 = 0;
 pub(crate) static mut EVER_PAGES: u16 = 0;
@@ -34,6 +36,8 @@ pub unsafe extern "C" fn user_test__prepare(
 ) -> *const u8 {
     let config = StylusConfig::new(version, max_depth, ink_price);
     CONFIG = Some(config);
+    LOGS_EMITTED = 0;
+    MEMORY_GAS = 0;
     ARGS = vec![0; len];
     ARGS.as_ptr()
 }
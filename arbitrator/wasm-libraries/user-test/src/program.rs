@@ -1,18 +1,20 @@
 // Copyright 2022-2024, Offchain Labs, Inc.
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
-use crate::{ARGS, EVER_PAGES, EVM_DATA, KEYS, LOGS, OPEN_PAGES, OUTS};
+use crate::{
+    ARGS, CONFIG, EVER_PAGES, EVM_DATA, KEYS, LOGS, LOGS_EMITTED, MEMORY_GAS, OPEN_PAGES, OUTS,
+};
 use arbutil::{
     evm::{
         api::{EvmApi, VecReader},
         user::UserOutcomeKind,
-        EvmData,
+        ChainParam, EvmData,
     },
     Bytes20, Bytes32, Color,
 };
 use caller_env::{static_caller::STATIC_MEM, GuestPtr, MemAccess};
 use eyre::{eyre, Result};
-use prover::programs::memory::MemoryModel;
+use prover::programs::{config::StylusConfig, memory::MemoryModel};
 use std::fmt::Display;
 use user_host_trait::UserHost;
 
@@ -56,6 +58,18 @@ impl UserHost<VecReader> for Program {
         unimplemented!()
     }
 
+    fn logs_emitted(&mut self) -> &mut u32 {
+        unsafe { &mut LOGS_EMITTED }
+    }
+
+    fn memory_gas(&mut self) -> &mut u64 {
+        unsafe { &mut MEMORY_GAS }
+    }
+
+    fn config(&self) -> StylusConfig {
+        unsafe { CONFIG.unwrap() }
+    }
+
     fn read_slice(&self, ptr: GuestPtr, len: u32) -> Result<Vec<u8>, MemoryBoundsError> {
         self.check_memory_access(ptr, len)?;
         unsafe { Ok(STATIC_MEM.read_slice(ptr, len as usize)) }
@@ -76,6 +90,10 @@ impl UserHost<VecReader> for Program {
         unsafe { Ok(STATIC_MEM.write_slice(ptr, src)) }
     }
 
+    fn check_memory_access(&self, _ptr: GuestPtr, _bytes: u32) -> Result<(), MemoryBoundsError> {
+        Ok(()) // pretend we did a check
+    }
+
     fn say<D: Display>(&self, text: D) {
         println!("{} {text}", "Stylus says:".yellow());
     }
@@ -93,10 +111,6 @@ impl Program {
             evm_api: MockEvmApi,
         }
     }
-
-    fn check_memory_access(&self, _ptr: GuestPtr, _bytes: u32) -> Result<(), MemoryBoundsError> {
-        Ok(()) // pretend we did a check
-    }
 }
 
 pub struct MockEvmApi;
@@ -214,4 +228,16 @@ impl EvmApi<VecReader> for MockEvmApi {
     ) {
         unimplemented!()
     }
+
+    fn checkpoint(&mut self) {
+        unimplemented!()
+    }
+
+    fn rollback(&mut self) {
+        unimplemented!()
+    }
+
+    fn chain_param(&mut self, _param: ChainParam) -> u64 {
+        unimplemented!()
+    }
 }
@@ -38,6 +38,17 @@ const (
 	AccountCodeHash
 	AddPages
 	CaptureHostIO
+	Checkpoint
+	Rollback
+	ChainParam
+)
+
+// The chain constants readable via the arb_chain_param hostio, mirroring arbutil's ChainParam.
+const (
+	ChainParamGenesisBlockNum uint32 = iota
+	ChainParamInitialArbOSVersion
+	ChainParamMaxCodeSize
+	ChainParamMaxInitCodeSize
 )
 
 type apiStatus uint8
@@ -55,11 +66,68 @@ func (s apiStatus) to_slice() []byte {
 
 const EvmApiMethodReqOffset = 0x10000000
 
+// The first Stylus version whose programs see EIP-161 empty accounts as having a zero codehash.
+const stylusVersionEmptyCodehash uint16 = 2
+
+// Hashes an account's code for the account_codehash hostio. Like EXTCODEHASH, nonexistent and
+// (since stylusVersionEmptyCodehash) EIP-161 empty accounts hash to zero, while other accounts
+// without code hash to keccak("").
+func codehashForVersion(db vm.StateDB, address common.Address, stylusVersion uint16) common.Hash {
+	if stylusVersion >= stylusVersionEmptyCodehash && db.Empty(address) {
+		return common.Hash{}
+	}
+	return db.GetCodeHash(address)
+}
+
+// Writes a program's packed storage slots, charging for each. Read-only frames may not write.
+func writeTrieSlots(
+	db vm.StateDB,
+	address common.Address,
+	readOnly bool,
+	tracingInfo *util.TracingInfo,
+	data []byte,
+	gasLeft *uint64,
+) apiStatus {
+	for len(data) > 0 {
+		key := common.BytesToHash(data[:32])
+		value := common.BytesToHash(data[32:64])
+		data = data[64:]
+
+		if tracingInfo != nil {
+			tracingInfo.RecordStorageSet(key, value)
+		}
+		if readOnly {
+			return WriteProtection
+		}
+
+		cost := vm.WasmStateStoreCost(db, address, key, value)
+		if cost > *gasLeft {
+			*gasLeft = 0
+			return OutOfGas
+		}
+		*gasLeft -= cost
+		db.SetState(address, key, value)
+	}
+	return Success
+}
+
+// Writes a program's transient storage slot. Read-only frames may not write.
+func writeTransientBytes32(
+	db vm.StateDB, address common.Address, readOnly bool, key, value common.Hash,
+) apiStatus {
+	if readOnly {
+		return WriteProtection
+	}
+	db.SetTransientState(address, key, value)
+	return Success
+}
+
 func newApiClosures(
 	interpreter *vm.EVMInterpreter,
 	tracingInfo *util.TracingInfo,
 	scope *vm.ScopeContext,
 	memoryModel *MemoryModel,
+	stylusVersion uint16,
 ) RequestHandler {
 	contract := scope.Contract
 	actingAddress := contract.Address() // not necessarily WASM
@@ -77,37 +145,13 @@ func newApiClosures(
 		return db.GetState(actingAddress, key), cost
 	}
 	setTrieSlots := func(data []byte, gasLeft *uint64) apiStatus {
-		for len(data) > 0 {
-			key := common.BytesToHash(data[:32])
-			value := common.BytesToHash(data[32:64])
-			data = data[64:]
-
-			if tracingInfo != nil {
-				tracingInfo.RecordStorageSet(key, value)
-			}
-			if readOnly {
-				return WriteProtection
-			}
-
-			cost := vm.WasmStateStoreCost(db, actingAddress, key, value)
-			if cost > *gasLeft {
-				*gasLeft = 0
-				return OutOfGas
-			}
-			*gasLeft -= cost
-			db.SetState(actingAddress, key, value)
-		}
-		return Success
+		return writeTrieSlots(db, actingAddress, readOnly, tracingInfo, data, gasLeft)
 	}
 	getTransientBytes32 := func(key common.Hash) common.Hash {
 		return db.GetTransientState(actingAddress, key)
 	}
 	setTransientBytes32 := func(key, value common.Hash) apiStatus {
-		if readOnly {
-			return WriteProtection
-		}
-		db.SetTransientState(actingAddress, key, value)
-		return Success
+		return writeTransientBytes32(db, actingAddress, readOnly, key, value)
 	}
 	doCall := func(
 		contract common.Address, opcode vm.OpCode, input []byte, gas uint64, value *big.Int,
@@ -262,7 +306,7 @@ func newApiClosures(
 	}
 	accountCodehash := func(address common.Address) (common.Hash, uint64) {
 		cost := vm.WasmAccountTouchCost(chainConfig, evm.StateDB, address, false)
-		return evm.StateDB.GetCodeHash(address), cost
+		return codehashForVersion(evm.StateDB, address, stylusVersion), cost
 	}
 	addPages := func(pages uint16) uint64 {
 		open, ever := db.AddStylusPages(pages)
@@ -271,6 +315,35 @@ func newApiClosures(
 	captureHostio := func(name string, args, outs []byte, startInk, endInk uint64) {
 		tracingInfo.Tracer.CaptureStylusHostio(name, args, outs, startInk, endInk)
 	}
+	// only dry runs checkpoint, since the EVM already reverts the state of failed calls
+	snapshots := []int{}
+	checkpoint := func() {
+		snapshots = append(snapshots, db.Snapshot())
+	}
+	rollback := func() {
+		if len(snapshots) == 0 {
+			// nothing to revert to, and crashing the node over it would be worse
+			log.Warn("ignoring rollback without checkpoint")
+			return
+		}
+		last := len(snapshots) - 1
+		db.RevertToSnapshot(snapshots[last])
+		snapshots = snapshots[:last]
+	}
+	chainParam := func(param uint32) uint64 {
+		switch param {
+		case ChainParamGenesisBlockNum:
+			return chainConfig.ArbitrumChainParams.GenesisBlockNum
+		case ChainParamInitialArbOSVersion:
+			return chainConfig.ArbitrumChainParams.InitialArbOSVersion
+		case ChainParamMaxCodeSize:
+			return chainConfig.MaxCodeSize()
+		case ChainParamMaxInitCodeSize:
+			return chainConfig.MaxInitCodeSize()
+		default:
+			return 0
+		}
+	}
 
 	return func(req RequestType, input []byte) ([]byte, []byte, uint64) {
 		original := input
@@ -422,6 +495,15 @@ func newApiClosures(
 
 			captureHostio(name, args, outs, startInk, endInk)
 			return []byte{}, nil, 0
+		case Checkpoint:
+			checkpoint()
+			return []byte{}, nil, 0
+		case Rollback:
+			rollback()
+			return []byte{}, nil, 0
+		case ChainParam:
+			param := takeU32()
+			return arbmath.UintToBytes(chainParam(param)), nil, 0
 		default:
 			log.Crit("unsupported call type", "req", req)
 			return []byte{}, nil, 0
@@ -0,0 +1,69 @@
+// Copyright 2024, Offchain Labs, Inc.
+// For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+package programs
+
+import (
+	"testing"
+
+	"github.com/ethereum/go-ethereum/common"
+	"github.com/ethereum/go-ethereum/core/rawdb"
+	"github.com/ethereum/go-ethereum/core/state"
+	"github.com/ethereum/go-ethereum/core/types"
+	"github.com/ethereum/go-ethereum/crypto"
+	"github.com/offchainlabs/nitro/util/arbmath"
+	"github.com/offchainlabs/nitro/util/testhelpers"
+)
+
+func TestCodehashForVersion(t *testing.T) {
+	db, err := state.New(common.Hash{}, state.NewDatabase(rawdb.NewMemoryDatabase()), nil)
+	testhelpers.RequireImpl(t, err)
+
+	missing := testhelpers.RandomAddress()
+	empty := testhelpers.RandomAddress()
+	used := testhelpers.RandomAddress()
+	contract := testhelpers.RandomAddress()
+	code := testhelpers.RandomSlice(32)
+	db.CreateAccount(empty)
+	db.SetNonce(used, 1)
+	db.SetCode(contract, code)
+
+	// earlier versions hash empty accounts that exist to keccak("")
+	old := stylusVersionEmptyCodehash - 1
+	AssertEq(t, codehashForVersion(db, missing, old), common.Hash{})
+	AssertEq(t, codehashForVersion(db, empty, old), types.EmptyCodeHash)
+	AssertEq(t, codehashForVersion(db, used, old), types.EmptyCodeHash)
+	AssertEq(t, codehashForVersion(db, contract, old), crypto.Keccak256Hash(code))
+
+	// later versions hash them to zero, like EXTCODEHASH
+	later := stylusVersionEmptyCodehash
+	AssertEq(t, codehashForVersion(db, missing, later), common.Hash{})
+	AssertEq(t, codehashForVersion(db, empty, later), common.Hash{})
+	AssertEq(t, codehashForVersion(db, used, later), types.EmptyCodeHash)
+	AssertEq(t, codehashForVersion(db, contract, later), crypto.Keccak256Hash(code))
+}
+
+func TestWriteProtection(t *testing.T) {
+	db, err := state.New(common.Hash{}, state.NewDatabase(rawdb.NewMemoryDatabase()), nil)
+	testhelpers.RequireImpl(t, err)
+
+	program := testhelpers.RandomAddress()
+	key := common.BytesToHash(testhelpers.RandomSlice(32))
+	value := common.BytesToHash(testhelpers.RandomSlice(32))
+	slots := arbmath.ConcatByteSlices(key[:], value[:])
+
+	// read-only frames, like those of static calls, can't write storage, and pay nothing trying
+	gas := uint64(1_000_000)
+	AssertEq(t, writeTrieSlots(db, program, true, nil, slots, &gas), WriteProtection)
+	AssertEq(t, writeTransientBytes32(db, program, true, key, value), WriteProtection)
+	AssertEq(t, gas, uint64(1_000_000))
+	AssertEq(t, db.GetState(program, key), common.Hash{})
+	AssertEq(t, db.GetTransientState(program, key), common.Hash{})
+
+	// other frames can
+	AssertEq(t, writeTrieSlots(db, program, false, nil, slots, &gas), Success)
+	AssertEq(t, writeTransientBytes32(db, program, false, key, value), Success)
+	AssertEq(t, gas < 1_000_000, true)
+	AssertEq(t, db.GetState(program, key), value)
+	AssertEq(t, db.GetTransientState(program, key), value)
+}
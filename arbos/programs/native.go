@@ -115,7 +115,7 @@ func callProgram(
 		db.RecordProgram(moduleHash)
 	}
 
-	evmApi := newApi(interpreter, tracingInfo, scope, memoryModel)
+	evmApi := newApi(interpreter, tracingInfo, scope, memoryModel, stylusParams.version)
 	defer evmApi.drop()
 
 	output := &rustBytes{}
@@ -236,26 +236,35 @@ func (params *goParams) encode() C.StylusConfig {
 		version:   u16(params.version),
 		max_depth: u32(params.maxDepth),
 		pricing:   pricing,
+		max_logs:  u32(params.maxLogs),
 	}
 }
 
 func (data *evmData) encode() C.EvmData {
 	return C.EvmData{
-		block_basefee:    hashToBytes32(data.blockBasefee),
-		chainid:          u64(data.chainId),
-		block_coinbase:   addressToBytes20(data.blockCoinbase),
-		block_gas_limit:  u64(data.blockGasLimit),
-		block_number:     u64(data.blockNumber),
-		block_timestamp:  u64(data.blockTimestamp),
-		contract_address: addressToBytes20(data.contractAddress),
-		module_hash:      hashToBytes32(data.moduleHash),
-		msg_sender:       addressToBytes20(data.msgSender),
-		msg_value:        hashToBytes32(data.msgValue),
-		tx_gas_price:     hashToBytes32(data.txGasPrice),
-		tx_origin:        addressToBytes20(data.txOrigin),
-		reentrant:        u32(data.reentrant),
-		return_data_len:  0,
-		cached:           cbool(data.cached),
-		tracing:          cbool(data.tracing),
+		block_basefee:         hashToBytes32(data.blockBasefee),
+		block_blob_basefee:    hashToBytes32(data.blockBlobBasefee),
+		block_excess_blob_gas: u64(data.blockExcessBlobGas),
+		chainid:               u64(data.chainId),
+		block_coinbase:        addressToBytes20(data.blockCoinbase),
+		block_gas_limit:       u64(data.blockGasLimit),
+		block_number:          u64(data.blockNumber),
+		l2_block_number:       u64(data.l2BlockNumber),
+		block_timestamp:       u64(data.blockTimestamp),
+		block_prevrandao:      hashToBytes32(data.blockPrevrandao),
+		contract_address:      addressToBytes20(data.contractAddress),
+		module_hash:           hashToBytes32(data.moduleHash),
+		msg_sender:            addressToBytes20(data.msgSender),
+		msg_value:             hashToBytes32(data.msgValue),
+		tx_gas_price:          hashToBytes32(data.txGasPrice),
+		tx_gas_fee_cap:        hashToBytes32(data.txGasFeeCap),
+		tx_gas_tip_cap:        hashToBytes32(data.txGasTipCap),
+		tx_origin:             addressToBytes20(data.txOrigin),
+		l1_sender:             addressToBytes20(data.l1Sender),
+		reentrant:             u32(data.reentrant),
+		is_static:             cbool(data.isStatic),
+		return_data_len:       0,
+		cached:                cbool(data.cached),
+		tracing:               cbool(data.tracing),
 	}
 }
@@ -47,8 +47,9 @@ func newApi(
 	tracingInfo *util.TracingInfo,
 	scope *vm.ScopeContext,
 	memoryModel *MemoryModel,
+	stylusVersion uint16,
 ) NativeApi {
-	handler := newApiClosures(interpreter, tracingInfo, scope, memoryModel)
+	handler := newApiClosures(interpreter, tracingInfo, scope, memoryModel, stylusVersion)
 	apiId := atomic.AddUintptr(&apiIds, 1)
 	id := usize(apiId)
 	api := NativeApi{
@@ -162,6 +162,9 @@ func (p Programs) CallProgram(
 	tracingInfo *util.TracingInfo,
 	calldata []byte,
 	reentrant bool,
+	l1Sender common.Address,
+	gasFeeCap *big.Int,
+	gasTipCap *big.Int,
 ) ([]byte, error) {
 	evm := interpreter.Evm()
 	contract := scope.Contract
@@ -207,22 +210,41 @@ func (p Programs) CallProgram(
 	statedb.AddStylusPages(program.footprint)
 	defer statedb.SetStylusPagesOpen(open)
 
+	prevrandao := common.Hash{}
+	if evm.Context.Random != nil {
+		prevrandao = *evm.Context.Random
+	}
+
+	// the blob basefee is unset before Cancun, and L2 blocks don't carry blobs so none are in excess
+	blobBasefee := common.Hash{}
+	if evm.Context.BlobBaseFee != nil {
+		blobBasefee = common.BigToHash(evm.Context.BlobBaseFee)
+	}
+
 	evmData := &evmData{
-		blockBasefee:    common.BigToHash(evm.Context.BaseFee),
-		chainId:         evm.ChainConfig().ChainID.Uint64(),
-		blockCoinbase:   evm.Context.Coinbase,
-		blockGasLimit:   evm.Context.GasLimit,
-		blockNumber:     l1BlockNumber,
-		blockTimestamp:  evm.Context.Time,
-		contractAddress: scope.Contract.Address(),
-		moduleHash:      moduleHash,
-		msgSender:       scope.Contract.Caller(),
-		msgValue:        common.BigToHash(scope.Contract.Value()),
-		txGasPrice:      common.BigToHash(evm.TxContext.GasPrice),
-		txOrigin:        evm.TxContext.Origin,
-		reentrant:       arbmath.BoolToUint32(reentrant),
-		cached:          program.cached,
-		tracing:         tracingInfo != nil,
+		blockBasefee:       common.BigToHash(evm.Context.BaseFee),
+		blockBlobBasefee:   blobBasefee,
+		blockExcessBlobGas: 0,
+		chainId:            evm.ChainConfig().ChainID.Uint64(),
+		blockCoinbase:      evm.Context.Coinbase,
+		blockGasLimit:      evm.Context.GasLimit,
+		blockNumber:        l1BlockNumber,
+		l2BlockNumber:      evm.Context.BlockNumber.Uint64(),
+		blockTimestamp:     evm.Context.Time,
+		blockPrevrandao:    prevrandao,
+		contractAddress:    scope.Contract.Address(),
+		moduleHash:         moduleHash,
+		msgSender:          scope.Contract.Caller(),
+		msgValue:           common.BigToHash(scope.Contract.Value()),
+		txGasPrice:         common.BigToHash(evm.TxContext.GasPrice),
+		txGasFeeCap:        common.BigToHash(gasFeeCap),
+		txGasTipCap:        common.BigToHash(gasTipCap),
+		txOrigin:           evm.TxContext.Origin,
+		l1Sender:           l1Sender,
+		reentrant:          arbmath.BoolToUint32(reentrant),
+		isStatic:           interpreter.ReadOnly(),
+		cached:             program.cached,
+		tracing:            tracingInfo != nil,
 	}
 
 	address := contract.Address()
@@ -438,6 +460,7 @@ type goParams struct {
 	version   uint16
 	maxDepth  uint32
 	inkPrice  uint24
+	maxLogs   uint32 // 0 for no limit
 	debugMode bool
 }
 
@@ -446,26 +469,35 @@ func (p Programs) goParams(version uint16, debug bool, params *StylusParams) *go
 		version:   version,
 		maxDepth:  params.MaxStackDepth,
 		inkPrice:  params.InkPrice,
+		maxLogs:   0, // onchain calls are only bounded by gas
 		debugMode: debug,
 	}
 }
 
 type evmData struct {
-	blockBasefee    common.Hash
-	chainId         uint64
-	blockCoinbase   common.Address
-	blockGasLimit   uint64
-	blockNumber     uint64
-	blockTimestamp  uint64
-	contractAddress common.Address
-	moduleHash      common.Hash
-	msgSender       common.Address
-	msgValue        common.Hash
-	txGasPrice      common.Hash
-	txOrigin        common.Address
-	reentrant       uint32
-	cached          bool
-	tracing         bool
+	blockBasefee       common.Hash
+	blockBlobBasefee   common.Hash
+	blockExcessBlobGas uint64
+	chainId            uint64
+	blockCoinbase      common.Address
+	blockGasLimit      uint64
+	blockNumber        uint64
+	l2BlockNumber      uint64
+	blockTimestamp     uint64
+	blockPrevrandao    common.Hash
+	contractAddress    common.Address
+	moduleHash         common.Hash
+	msgSender          common.Address
+	msgValue           common.Hash
+	txGasPrice         common.Hash
+	txGasFeeCap        common.Hash
+	txGasTipCap        common.Hash
+	txOrigin           common.Address
+	l1Sender           common.Address
+	reentrant          uint32
+	isStatic           bool
+	cached             bool
+	tracing            bool
 }
 
 type activationInfo struct {
@@ -69,6 +69,15 @@ func testConstants() error {
 	if err := errIfNotEq(CaptureHostIO, C.EvmApiMethod_CaptureHostIO); err != nil {
 		return err
 	}
+	if err := errIfNotEq(Checkpoint, C.EvmApiMethod_Checkpoint); err != nil {
+		return err
+	}
+	if err := errIfNotEq(Rollback, C.EvmApiMethod_Rollback); err != nil {
+		return err
+	}
+	if err := errIfNotEq(ChainParam, C.EvmApiMethod_ChainParam); err != nil {
+		return err
+	}
 	if err := errIfNotEq(EvmApiMethodReqOffset, C.EVM_API_METHOD_REQ_OFFSET); err != nil {
 		return err
 	}
@@ -136,7 +136,7 @@ func callProgram(
 	params *goParams,
 	memoryModel *MemoryModel,
 ) ([]byte, error) {
-	reqHandler := newApiClosures(interpreter, tracingInfo, scope, memoryModel)
+	reqHandler := newApiClosures(interpreter, tracingInfo, scope, memoryModel, params.version)
 	configHandler := params.createHandler()
 	dataHandler := evmData.createHandler()
 	debug := params.debugMode
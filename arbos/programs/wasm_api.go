@@ -16,48 +16,64 @@ import (
 type stylusConfigHandler uint64
 
 //go:wasmimport programs create_stylus_config
-func createStylusConfig(version uint32, max_depth uint32, ink_price uint32, debug uint32) stylusConfigHandler
+func createStylusConfig(version uint32, max_depth uint32, ink_price uint32, max_logs uint32, debug uint32) stylusConfigHandler
 
 type evmDataHandler uint64
 
 //go:wasmimport programs create_evm_data
 func createEvmData(
 	blockBaseFee unsafe.Pointer,
+	blockBlobBaseFee unsafe.Pointer,
+	excessBlobGas uint64,
 	chainid uint64,
 	blockCoinbase unsafe.Pointer,
 	gasLimit uint64,
 	blockNumber uint64,
+	l2BlockNumber uint64,
 	blockTimestamp uint64,
+	blockPrevrandao unsafe.Pointer,
 	contractAddress unsafe.Pointer,
 	moduleHash unsafe.Pointer,
 	msgSender unsafe.Pointer,
 	msgValue unsafe.Pointer,
 	txGasPrice unsafe.Pointer,
+	txGasFeeCap unsafe.Pointer,
+	txGasTipCap unsafe.Pointer,
 	txOrigin unsafe.Pointer,
+	l1Sender unsafe.Pointer,
 	cached uint32,
 	reentrant uint32,
+	isStatic uint32,
 ) evmDataHandler
 
 func (params *goParams) createHandler() stylusConfigHandler {
 	debug := arbmath.BoolToUint32(params.debugMode)
-	return createStylusConfig(uint32(params.version), params.maxDepth, params.inkPrice.ToUint32(), debug)
+	return createStylusConfig(uint32(params.version), params.maxDepth, params.inkPrice.ToUint32(), params.maxLogs, debug)
 }
 
 func (data *evmData) createHandler() evmDataHandler {
 	return createEvmData(
 		arbutil.SliceToUnsafePointer(data.blockBasefee[:]),
+		arbutil.SliceToUnsafePointer(data.blockBlobBasefee[:]),
+		data.blockExcessBlobGas,
 		data.chainId,
 		arbutil.SliceToUnsafePointer(data.blockCoinbase[:]),
 		data.blockGasLimit,
 		data.blockNumber,
+		data.l2BlockNumber,
 		data.blockTimestamp,
+		arbutil.SliceToUnsafePointer(data.blockPrevrandao[:]),
 		arbutil.SliceToUnsafePointer(data.contractAddress[:]),
 		arbutil.SliceToUnsafePointer(data.moduleHash[:]),
 		arbutil.SliceToUnsafePointer(data.msgSender[:]),
 		arbutil.SliceToUnsafePointer(data.msgValue[:]),
 		arbutil.SliceToUnsafePointer(data.txGasPrice[:]),
+		arbutil.SliceToUnsafePointer(data.txGasFeeCap[:]),
+		arbutil.SliceToUnsafePointer(data.txGasTipCap[:]),
 		arbutil.SliceToUnsafePointer(data.txOrigin[:]),
+		arbutil.SliceToUnsafePointer(data.l1Sender[:]),
 		arbmath.BoolToUint32(data.cached),
 		data.reentrant,
+		arbmath.BoolToUint32(data.isStatic),
 	)
 }
@@ -119,6 +119,18 @@ func (p *TxProcessor) ExecuteWASM(scope *vm.ScopeContext, input []byte, interpre
 	// reentrant if more than one open same-actor context span exists
 	reentrant := p.Programs[acting] > 1
 
+	// txs from L1 have aliased origins, so programs get the unaliased L1 sender too
+	l1Sender := common.Address{}
+	if util.DoesTxTypeAlias(p.TopTxType) {
+		l1Sender = util.InverseRemapL1Address(p.evm.TxContext.Origin)
+	}
+
+	// programs derive the effective gas price from the tx's own fee cap and tip
+	gasFeeCap, gasTipCap := p.evm.TxContext.GasPrice, common.Big0
+	if p.msg != nil {
+		gasFeeCap, gasTipCap = p.msg.GasFeeCap, p.msg.GasTipCap
+	}
+
 	return p.state.Programs().CallProgram(
 		scope,
 		p.evm.StateDB,
@@ -126,6 +138,9 @@ func (p *TxProcessor) ExecuteWASM(scope *vm.ScopeContext, input []byte, interpre
 		tracingInfo,
 		input,
 		reentrant,
+		l1Sender,
+		gasFeeCap,
+		gasTipCap,
 	)
 }
 